@@ -13,3 +13,25 @@ pub fn unix_timestamp_ms() -> u64 {
         .unwrap()
         .as_millis() as u64
 }
+
+pub const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+const SECONDS_PER_WEEK: u64 = 7 * SECONDS_PER_DAY;
+
+// Daily and weekly resets happen at this hour (UTC)
+pub const RESET_HOUR: u64 = 4;
+// Unix epoch was a Thursday, weekly reset happens on Monday
+const WEEKLY_RESET_OFFSET: u64 = 4 * SECONDS_PER_DAY + RESET_HOUR * 60 * 60;
+
+// Timestamp of the first daily reset after given timestamp
+pub fn next_daily_reset(timestamp: u64) -> u64 {
+    next_period_start(timestamp, SECONDS_PER_DAY, RESET_HOUR * 60 * 60)
+}
+
+// Timestamp of the first weekly reset after given timestamp
+pub fn next_weekly_reset(timestamp: u64) -> u64 {
+    next_period_start(timestamp, SECONDS_PER_WEEK, WEEKLY_RESET_OFFSET)
+}
+
+fn next_period_start(timestamp: u64, period: u64, offset: u64) -> u64 {
+    (timestamp + period - offset) / period * period + offset
+}
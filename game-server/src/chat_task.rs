@@ -0,0 +1,160 @@
+use std::sync::{Arc, OnceLock};
+use tokio::sync::mpsc;
+
+use shorekeeper_database::{models, query, query_as, Error, PgPool};
+
+use crate::{
+    logic::{
+        player::{ChatEvent, ChatMessage, PlayerChat, WorldChatMessage},
+        thread_mgr::LogicInput,
+    },
+    session::SessionManager,
+};
+
+static SENDER: OnceLock<mpsc::Sender<ChatDelivery>> = OnceLock::new();
+
+pub enum ChatDelivery {
+    World(WorldChatMessage),
+    Private {
+        target_id: i32,
+        message: ChatMessage,
+    },
+}
+
+pub fn start(db: Arc<PgPool>, session_mgr: &'static SessionManager) {
+    let _ = SENDER.get_or_init(|| {
+        let (tx, rx) = mpsc::channel(32);
+        tokio::spawn(async move { task_loop(rx, db, session_mgr).await });
+
+        tx
+    });
+}
+
+pub fn push(delivery: ChatDelivery) {
+    let _ = SENDER.get().unwrap().blocking_send(delivery);
+}
+
+// Returns the latest private messages of every conversation of player as (peer_id, message), oldest first
+pub async fn load_private_chats(
+    db: &PgPool,
+    player_id: i32,
+) -> Result<Vec<(i32, ChatMessage)>, Error> {
+    let rows: Vec<models::PrivateChatRow> = query_as(
+        "SELECT msg_id, sender_id, target_id, content_type, content, send_time, is_offline FROM (\
+            SELECT *, ROW_NUMBER() OVER (\
+                PARTITION BY CASE WHEN sender_id = ($1) THEN target_id ELSE sender_id END \
+                ORDER BY send_time DESC\
+            ) AS row_num FROM t_private_chat WHERE sender_id = ($1) OR target_id = ($1)\
+        ) AS chats WHERE row_num <= ($2) ORDER BY send_time DESC",
+    )
+    .bind(player_id)
+    .bind(PlayerChat::MAX_HISTORY_PER_PLAYER as i64)
+    .fetch_all(db)
+    .await?;
+
+    // Offline messages are delivered now
+    query("UPDATE t_private_chat SET is_offline = false WHERE target_id = ($1) AND is_offline")
+        .bind(player_id)
+        .execute(db)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .rev()
+        .map(|row| {
+            let peer_id = if row.sender_id == player_id {
+                row.target_id
+            } else {
+                row.sender_id
+            };
+
+            (
+                peer_id,
+                ChatMessage {
+                    msg_id: row.msg_id,
+                    sender_id: row.sender_id,
+                    content_type: row.content_type,
+                    content: row.content,
+                    send_time: row.send_time,
+                    is_offline: row.is_offline,
+                },
+            )
+        })
+        .collect())
+}
+
+async fn task_loop(
+    mut receiver: mpsc::Receiver<ChatDelivery>,
+    db: Arc<PgPool>,
+    session_mgr: &'static SessionManager,
+) {
+    loop {
+        let Some(delivery) = receiver.recv().await else {
+            tracing::warn!("chat_task: channel was closed, exitting");
+            return;
+        };
+
+        match delivery {
+            // Recipients filter out muted and blocked senders on their side
+            ChatDelivery::World(message) => {
+                for session in session_mgr.sessions() {
+                    session.logic_thread.input(LogicInput::ChatEvent {
+                        player_id: session.player_id,
+                        event: ChatEvent::World(message.clone()),
+                    });
+                }
+            }
+            ChatDelivery::Private { target_id, message } => {
+                if let Err(err) =
+                    deliver_private(db.as_ref(), session_mgr, target_id, message).await
+                {
+                    tracing::error!("chat_task: failed to deliver private message, err: {err}");
+                }
+            }
+        }
+    }
+}
+
+async fn deliver_private(
+    db: &PgPool,
+    session_mgr: &SessionManager,
+    target_id: i32,
+    mut message: ChatMessage,
+) -> Result<(), Error> {
+    if query("SELECT 1 FROM t_block_list WHERE player_id = ($1) AND blocked_id = ($2)")
+        .bind(target_id)
+        .bind(message.sender_id)
+        .fetch_optional(db)
+        .await?
+        .is_some()
+    {
+        tracing::debug!(
+            "chat_task: player with id {} is blocked by {target_id}",
+            message.sender_id
+        );
+        return Ok(());
+    }
+
+    let session = session_mgr.get_by_player_id(target_id);
+    message.is_offline = session.is_none();
+
+    query("INSERT INTO t_private_chat (msg_id, sender_id, target_id, content_type, content, send_time, is_offline) VALUES ($1, $2, $3, $4, $5, $6, $7)")
+        .bind(message.msg_id.as_str())
+        .bind(message.sender_id)
+        .bind(target_id)
+        .bind(message.content_type)
+        .bind(message.content.as_str())
+        .bind(message.send_time)
+        .bind(message.is_offline)
+        .execute(db)
+        .await?;
+
+    if let Some(session) = session {
+        session.logic_thread.input(LogicInput::ChatEvent {
+            player_id: target_id,
+            event: ChatEvent::Private(message),
+        });
+    }
+
+    Ok(())
+}
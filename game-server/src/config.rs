@@ -9,8 +9,22 @@ pub struct ServiceConfig {
     pub database: DatabaseSettings,
     pub service_end_point: ServiceEndPoint,
     pub gateway_end_point: ServiceEndPoint,
+    #[serde(default)]
+    pub gm: GmConfig,
+    #[serde(default)]
+    pub payment: PaymentConfig,
+}
+
+#[derive(Deserialize, Default)]
+pub struct GmConfig {
+    pub allowed_player_ids: Vec<i32>,
 }
 
 impl TomlConfig for ServiceConfig {
     const DEFAULT_TOML: &str = include_str!("../gameserver.default.toml");
 }
+
+#[derive(Deserialize, Default)]
+pub struct PaymentConfig {
+    pub use_mock_provider: bool,
+}
@@ -1,25 +1,25 @@
-use shorekeeper_data::BasePropertyData;
 use shorekeeper_protocol::{
     entity_component_pb::ComponentPb, AttrData, AttributeComponentPb, EAttributeType,
-    EntityComponentPb, LivingStatus,
+    EntityComponentPb, GameplayAttributeData, LivingStatus,
 };
 use std::collections::HashMap;
 
 use crate::logic::ecs::component::Component;
-use crate::logic::utils::load_role_info::attribute_from_data;
+use crate::logic::player::PlayerInventory;
+use crate::logic::role::Role;
 
 pub struct Attribute {
     pub attr_map: HashMap<EAttributeType, (i32, i32)>,
+    // Set by server side changes, broadcast and reset by AttributeSystem
+    pub changed: bool,
+    pub living_status_changed: bool,
+    // Increments granted by buffs, kept when attributes are rebuilt
+    pub buff_bonus: HashMap<EAttributeType, i32>,
 }
 
 impl Component for Attribute {
     fn set_pb_data(&self, pb: &mut shorekeeper_protocol::EntityPb) {
-        pb.living_status = (if self.is_alive() {
-            LivingStatus::Alive
-        } else {
-            LivingStatus::Dead
-        })
-        .into();
+        pb.living_status = self.build_living_status().into();
 
         pb.component_pbs.push(EntityComponentPb {
             component_pb: Some(ComponentPb::AttributeComponent(
@@ -40,12 +40,109 @@ impl Attribute {
     }
 
     #[inline(always)]
-    pub fn from_data(base_property: &BasePropertyData) -> Self {
+    pub fn new(attr_map: HashMap<EAttributeType, (i32, i32)>) -> Self {
         Self {
-            attr_map: attribute_from_data(base_property),
+            attr_map,
+            changed: false,
+            living_status_changed: false,
+            buff_bonus: HashMap::new(),
         }
     }
 
+    #[inline(always)]
+    pub fn from_role(role: &Role, inventory: &PlayerInventory) -> Self {
+        Self::new(role.build_attributes(inventory))
+    }
+
+    // Base value with increment applied
+    pub fn value(&self, ty: EAttributeType) -> i32 {
+        self.attr_map
+            .get(&ty)
+            .map(|(base, incr)| base + incr)
+            .unwrap_or_default()
+    }
+
+    // Returns damage actually dealt, life doesn't go below 0
+    pub fn apply_damage(&mut self, damage: i32) -> i32 {
+        let life = self.value(EAttributeType::Life);
+        let dealt = damage.clamp(0, life.max(0));
+        self.set_life(life - dealt);
+        dealt
+    }
+
+    // Life doesn't go beyond LifeMax, dead entities can't be healed
+    pub fn heal(&mut self, amount: i32) {
+        let life = self.value(EAttributeType::Life);
+        if life <= 0 || amount <= 0 {
+            return;
+        }
+
+        let life_max = self.value(EAttributeType::LifeMax);
+        if life < life_max {
+            self.set_life((life + amount).min(life_max));
+        }
+    }
+
+    // Replaces rebuilt attributes, buff increments are applied on top
+    pub fn set_attr_map(&mut self, attr_map: HashMap<EAttributeType, (i32, i32)>) {
+        self.attr_map = attr_map;
+        let buff_bonus = std::mem::take(&mut self.buff_bonus);
+        self.set_buff_bonus(buff_bonus);
+    }
+
+    pub fn set_buff_bonus(&mut self, buff_bonus: HashMap<EAttributeType, i32>) {
+        for (ty, value) in self.buff_bonus.drain() {
+            if let Some((_, incr)) = self.attr_map.get_mut(&ty) {
+                *incr -= value;
+            }
+        }
+
+        for (&ty, &value) in buff_bonus.iter() {
+            if let Some((_, incr)) = self.attr_map.get_mut(&ty) {
+                *incr += value;
+            }
+        }
+        self.buff_bonus = buff_bonus;
+
+        let (life, life_max) = (
+            self.value(EAttributeType::Life),
+            self.value(EAttributeType::LifeMax),
+        );
+        if life > life_max {
+            self.set_life(life_max);
+        }
+        self.changed = true;
+    }
+
+    pub fn revive(&mut self) {
+        self.set_life(self.value(EAttributeType::LifeMax));
+    }
+
+    fn set_life(&mut self, life: i32) {
+        let was_alive = self.is_alive();
+        self.attr_map.insert(EAttributeType::Life, (life, 0));
+        self.changed = true;
+        self.living_status_changed |= was_alive != self.is_alive();
+    }
+
+    pub fn build_living_status(&self) -> LivingStatus {
+        match self.is_alive() {
+            true => LivingStatus::Alive,
+            false => LivingStatus::Dead,
+        }
+    }
+
+    pub fn build_gameplay_attributes(&self) -> Vec<GameplayAttributeData> {
+        self.attr_map
+            .iter()
+            .map(|(ty, (base, incr))| GameplayAttributeData {
+                attribute_type: (*ty).into(),
+                base_value: *base,
+                increment: *incr,
+            })
+            .collect()
+    }
+
     #[inline(always)]
     pub fn build_entity_attribute(&self) -> AttributeComponentPb {
         AttributeComponentPb {
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+
+use crate::logic::ecs::component::Component;
+use shorekeeper_data::BuffData;
+use shorekeeper_protocol::combat_message::combat_notify_data;
+use shorekeeper_protocol::entity_component_pb::ComponentPb;
+use shorekeeper_protocol::{
+    apply_buff_s2c_request_notify, ApplyBuffS2cRequestNotify, BuffStackCountNotify, EAttributeType,
+    EntityComponentPb, ErrorCode, FightBuffComponentPb, FightBuffInformation,
+    RemoveBuffByIdS2cRequestNotify,
+};
+
+pub struct BuffInstance {
+    pub handle_id: i32,
+    pub data: &'static BuffData,
+    pub level: i32,
+    pub stack_count: i32,
+    pub instigator_id: i64,
+    // Unix timestamps in milliseconds
+    pub apply_time: u64,
+    pub next_period_time: u64,
+}
+
+#[derive(Default)]
+pub struct Buff {
+    pub buffs: Vec<BuffInstance>,
+    pub next_handle_id: i32,
+    // Set on buff changes, applied to Attribute and reset by BuffSystem
+    pub changed: bool,
+    // Server side changes, broadcast and drained by BuffSystem
+    pub notifies: Vec<combat_notify_data::Message>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum BuffError {
+    #[error("buff with handle {0} doesn't exist")]
+    HandleNotExist(i32),
+    #[error("buff with handle {0} has no effect {1}")]
+    EffectNotExist(i32, i32),
+    #[error("buff with handle {0} can't gain stacks from client, requested: {1}")]
+    StackCountIncreased(i32, i32),
+}
+
+impl From<BuffError> for ErrorCode {
+    fn from(err: BuffError) -> Self {
+        match err {
+            BuffError::HandleNotExist(_) => ErrorCode::ErrNoBuffConf,
+            BuffError::EffectNotExist(..) => ErrorCode::ErrNotFoundBuffEffect,
+            BuffError::StackCountIncreased(..) => ErrorCode::InvalidRequest,
+        }
+    }
+}
+
+impl Component for Buff {
+    fn set_pb_data(&self, pb: &mut shorekeeper_protocol::EntityPb) {
+        let now = common::time_util::unix_timestamp_ms();
+        pb.component_pbs.push(EntityComponentPb {
+            component_pb: Some(ComponentPb::FightBuffComponent(FightBuffComponentPb {
+                fight_buff_infos: self
+                    .buffs
+                    .iter()
+                    .map(|buff| buff.build_information(pb.id, now))
+                    .collect(),
+                list_buff_effect_cd: Vec::new(),
+            })),
+        })
+    }
+}
+
+impl BuffInstance {
+    pub fn expire_time(&self) -> Option<u64> {
+        match self.data.duration {
+            duration if duration > 0 => Some(self.apply_time + duration as u64),
+            _ => None,
+        }
+    }
+
+    fn duration_secs(&self) -> f32 {
+        self.data.duration.max(0) as f32 / 1000.0
+    }
+
+    fn build_information(&self, entity_id: i64, now: u64) -> FightBuffInformation {
+        let left_duration = self
+            .expire_time()
+            .map(|time| time.saturating_sub(now) as f32 / 1000.0)
+            .unwrap_or_default();
+
+        FightBuffInformation {
+            handle_id: self.handle_id,
+            buff_id: self.data.id,
+            level: self.level,
+            stack_count: self.stack_count,
+            instigator_id: self.instigator_id,
+            entity_id,
+            duration: self.duration_secs(),
+            left_duration,
+            is_active: true,
+            server_id: self.handle_id,
+            ..Default::default()
+        }
+    }
+}
+
+impl Buff {
+    // Applying an already present buff adds stacks and refreshes its duration
+    pub fn add(&mut self, data: &'static BuffData, instigator_id: i64, stacks: i32, now: u64) {
+        let max_stack = data.max_stack.max(1);
+
+        if let Some(buff) = self.buffs.iter_mut().find(|buff| buff.data.id == data.id) {
+            buff.stack_count = (buff.stack_count + stacks).clamp(1, max_stack);
+            buff.apply_time = now;
+            self.notifies
+                .push(combat_notify_data::Message::BuffStackCountNotify(
+                    BuffStackCountNotify {
+                        handle_id: buff.handle_id,
+                        new_stack_count: buff.stack_count,
+                    },
+                ));
+        } else {
+            self.next_handle_id += 1;
+            let buff = BuffInstance {
+                handle_id: self.next_handle_id,
+                data,
+                level: 1,
+                stack_count: stacks.clamp(1, max_stack),
+                instigator_id,
+                apply_time: now,
+                next_period_time: now + data.period.max(0) as u64,
+            };
+
+            self.notifies
+                .push(combat_notify_data::Message::ApplyBuffS2cRequestNotify(
+                    ApplyBuffS2cRequestNotify {
+                        time: (data.duration > 0).then_some(
+                            apply_buff_s2c_request_notify::Time::Duration(buff.duration_secs()),
+                        ),
+                        id: data.id,
+                        level: buff.level,
+                        instigator_id,
+                        server_id: buff.handle_id,
+                        stack_count: buff.stack_count,
+                        ..Default::default()
+                    },
+                ));
+            self.buffs.push(buff);
+        }
+
+        self.changed = true;
+    }
+
+    // Stack count reported by the client, it can only consume stacks which are added by server,
+    // buff is removed once no stacks are left
+    pub fn set_stack_count(&mut self, handle_id: i32, stack_count: i32) -> Result<(), BuffError> {
+        let index = self
+            .buffs
+            .iter()
+            .position(|buff| buff.handle_id == handle_id)
+            .ok_or(BuffError::HandleNotExist(handle_id))?;
+
+        match stack_count {
+            count if count > self.buffs[index].stack_count => {
+                return Err(BuffError::StackCountIncreased(handle_id, count));
+            }
+            count if count <= 0 => {
+                self.buffs.remove(index);
+            }
+            count => self.buffs[index].stack_count = count,
+        }
+
+        self.changed = true;
+        Ok(())
+    }
+
+    pub fn check_effect(&self, handle_id: i32, index: i32) -> Result<(), BuffError> {
+        let buff = self
+            .buffs
+            .iter()
+            .find(|buff| buff.handle_id == handle_id)
+            .ok_or(BuffError::HandleNotExist(handle_id))?;
+
+        match index >= 0 && (index as usize) < buff.data.modifiers.len() {
+            true => Ok(()),
+            false => Err(BuffError::EffectNotExist(handle_id, index)),
+        }
+    }
+
+    // Effect is applied again to Attribute on next BuffSystem tick
+    pub fn execute_effect(&mut self, handle_id: i32, index: i32) -> Result<(), BuffError> {
+        self.check_effect(handle_id, index)?;
+        self.changed = true;
+        Ok(())
+    }
+
+    // Removes expired buffs, returns ratio of LifeMax to heal by periodic effects
+    pub fn update(&mut self, now: u64) -> i32 {
+        let mut heal_ratio = 0;
+
+        for buff in self.buffs.iter_mut().filter(|buff| buff.data.period > 0) {
+            let end_time = buff.expire_time().unwrap_or(u64::MAX).min(now);
+            while buff.next_period_time <= end_time {
+                heal_ratio += buff.data.periodic_heal * buff.stack_count;
+                buff.next_period_time += buff.data.period as u64;
+            }
+        }
+
+        let notifies = &mut self.notifies;
+        let count = self.buffs.len();
+        self.buffs.retain(|buff| {
+            let expired = buff.expire_time().is_some_and(|time| time <= now);
+            if expired {
+                notifies.push(combat_notify_data::Message::RemoveBuffByIdS2cRequestNotify(
+                    RemoveBuffByIdS2cRequestNotify {
+                        buff_id: buff.data.id,
+                        stack_count: buff.stack_count,
+                        reason: 0,
+                    },
+                ));
+            }
+            !expired
+        });
+        self.changed |= count != self.buffs.len();
+
+        heal_ratio
+    }
+
+    // Attribute increments of all stacks, ratio modifiers scale base value
+    pub fn build_bonus(
+        &self,
+        base_value: impl Fn(EAttributeType) -> i32,
+    ) -> HashMap<EAttributeType, i32> {
+        let mut bonus = HashMap::new();
+
+        for buff in self.buffs.iter() {
+            for modifier in buff.data.modifiers.iter() {
+                let Ok(ty) = EAttributeType::try_from(modifier.id) else {
+                    continue;
+                };
+
+                let value = match modifier.is_ratio {
+                    true => base_value(ty) as f32 * modifier.value,
+                    false => modifier.value,
+                };
+                *bonus.entry(ty).or_default() += value as i32 * buff.stack_count;
+            }
+        }
+
+        bonus
+    }
+}
@@ -1,25 +1,314 @@
+use std::collections::HashMap;
+
 use crate::logic::ecs::component::Component;
+use shorekeeper_data::{FsmMachineData, FsmTransitionData};
 use shorekeeper_protocol::entity_component_pb::ComponentPb;
-use shorekeeper_protocol::{DFsm, DFsmBlackBoard, EntityComponentPb, EntityFsmComponentPb, FsmCustomBlackboardDatas};
+use shorekeeper_protocol::{
+    ChangeStateNotify, DFsm, DFsmBlackBoard, DFsmBlackboardCustom, EFsmStateFlag,
+    EntityComponentPb, EntityFsmComponentPb, ErrorCode, FsmCustomBlackboardDatas,
+    GmEntityFsmMachineInfo, GmEntityFsmStateInfo, GmEntityFsmTransitionInfo,
+};
+
+pub struct FsmMachine {
+    pub fsm_id: i32,
+    // None for machines missing from FsmGroup data, their transitions are driven by the client
+    pub data: Option<&'static FsmMachineData>,
+    pub current_state: i32,
+    pub flag: EFsmStateFlag,
+    pub k_ts: i32,
+    // (from_state, to_state) -> passed conditions of the transition
+    pub conditions: HashMap<(i32, i32), Vec<bool>>,
+}
 
 pub struct Fsm {
-    pub fsms: Vec<DFsm>,
+    pub machines: Vec<FsmMachine>,
     pub hash_code: i32,
     pub common_hash_code: i32,
-    pub black_board: Vec<DFsmBlackBoard>,
-    pub fsm_custom_blackboard_datas: Option<FsmCustomBlackboardDatas>,
+    pub black_board: HashMap<i32, i32>,
+    pub custom_black_board: HashMap<String, i32>,
+    // Set by server side changes, broadcast and reset by FsmSystem
+    pub state_changes: Vec<ChangeStateNotify>,
+    pub black_board_changed: bool,
+    pub custom_black_board_changed: bool,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum FsmError {
+    #[error("fsm {0} doesn't exist")]
+    MachineNotExist(i32),
+    #[error("fsm {0} is in state {1}")]
+    StateIncorrect(i32, i32),
+    #[error("conditions of fsm {0} transition from {1} to {2} aren't passed")]
+    ConditionNotPassed(i32, i32, i32),
+    #[error("fsm {0} has no condition {1} for transition from {2}")]
+    ConditionNotExist(i32, i32, i32),
+    #[error("fsm {0} has no state {1}")]
+    StateNotExist(i32, i32),
+    #[error("fsm {0} state isn't waiting for confirm")]
+    ConfirmNotWait(i32),
+}
+
+impl From<FsmError> for ErrorCode {
+    fn from(err: FsmError) -> Self {
+        match err {
+            FsmError::MachineNotExist(_) => ErrorCode::ErrEntityFsmMachineNotExist,
+            FsmError::StateIncorrect(..) => ErrorCode::ErrEntityFsmStateIncorrect,
+            FsmError::ConditionNotPassed(..) => ErrorCode::ErrIEntityFsmTransitCondition,
+            FsmError::ConditionNotExist(..) => ErrorCode::ErrIEntityFsmCondCantPass,
+            FsmError::StateNotExist(..) => ErrorCode::ErrIEntityFsmTransitToState,
+            FsmError::ConfirmNotWait(_) => ErrorCode::ErrIEntityFsmConfirmNotWait,
+        }
+    }
 }
 
 impl Component for Fsm {
     fn set_pb_data(&self, pb: &mut shorekeeper_protocol::EntityPb) {
         pb.component_pbs.push(EntityComponentPb {
-            component_pb: Some(ComponentPb::EntityFsmComponentPb(EntityFsmComponentPb {
-                fsms: self.fsms.clone(),
-                hash_code: self.hash_code,
-                common_hash_code: self.common_hash_code,
-                black_board: self.black_board.clone(),
-                fsm_custom_blackboard_datas: self.fsm_custom_blackboard_datas.clone(),
-            })),
+            component_pb: Some(ComponentPb::EntityFsmComponentPb(self.build_component_pb())),
         })
     }
 }
+
+impl FsmMachine {
+    fn new(data: &'static FsmMachineData) -> Self {
+        Self {
+            fsm_id: data.fsm_id,
+            data: Some(data),
+            current_state: data.init_state,
+            flag: EFsmStateFlag::Changed,
+            k_ts: 0,
+            conditions: HashMap::new(),
+        }
+    }
+
+    fn client_driven(fsm_id: i32, state: i32) -> Self {
+        Self {
+            fsm_id,
+            data: None,
+            current_state: state,
+            flag: EFsmStateFlag::Changed,
+            k_ts: 0,
+            conditions: HashMap::new(),
+        }
+    }
+
+    fn transition(&self, from: i32, to: i32) -> Option<&'static FsmTransitionData> {
+        self.data?
+            .states
+            .iter()
+            .find(|state| state.id == from)
+            .and_then(|state| state.transitions.iter().find(|t| t.to_state == to))
+    }
+
+    fn state_index(&self, state: i32) -> Option<usize> {
+        self.data?.states.iter().position(|s| s.id == state)
+    }
+
+    fn is_passed(&self, from: i32, transition: &FsmTransitionData) -> bool {
+        let passed = self.conditions.get(&(from, transition.to_state));
+        (0..transition.condition_count as usize)
+            .all(|i| passed.and_then(|p| p.get(i)).copied().unwrap_or_default())
+    }
+
+    // Conditions are bound to the state that is left
+    fn enter(&mut self, state: i32) {
+        self.current_state = state;
+        self.flag = EFsmStateFlag::Changed;
+        self.conditions.clear();
+    }
+
+    fn build_gm_info(&self) -> GmEntityFsmMachineInfo {
+        GmEntityFsmMachineInfo {
+            state_index: self.state_index(self.current_state).unwrap_or_default() as i32,
+            gm_entity_fsm_state_infos: self
+                .data
+                .iter()
+                .flat_map(|data| data.states.iter())
+                .enumerate()
+                .map(|(index, state)| GmEntityFsmStateInfo {
+                    state_index: index as i32,
+                    gm_entity_fsm_transition_infos: state
+                        .transitions
+                        .iter()
+                        .map(|transition| {
+                            let passed = self.conditions.get(&(state.id, transition.to_state));
+                            GmEntityFsmTransitionInfo {
+                                to_state_index: self
+                                    .state_index(transition.to_state)
+                                    .unwrap_or_default()
+                                    as i32,
+                                gm_entity_fsm_conditions: (0..transition.condition_count as usize)
+                                    .map(|i| {
+                                        passed.and_then(|p| p.get(i)).copied().unwrap_or_default()
+                                    })
+                                    .collect(),
+                            }
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Fsm {
+    pub fn new(common_hash_code: i32, machines: &'static [FsmMachineData]) -> Self {
+        Self {
+            machines: machines.iter().map(FsmMachine::new).collect(),
+            hash_code: 0,
+            common_hash_code,
+            black_board: HashMap::new(),
+            custom_black_board: HashMap::new(),
+            state_changes: Vec::new(),
+            black_board_changed: false,
+            custom_black_board_changed: false,
+        }
+    }
+
+    // Transition reported by the client, only conditions of transitions known from FsmGroup data
+    // are checked, the rest is accepted as is
+    pub fn change_state(&mut self, fsm_id: i32, from: i32, to: i32) -> Result<(), FsmError> {
+        let Ok(machine) = self.machine_mut(fsm_id) else {
+            self.machines.push(FsmMachine::client_driven(fsm_id, to));
+            return Ok(());
+        };
+
+        if machine.current_state != from {
+            return Err(FsmError::StateIncorrect(fsm_id, machine.current_state));
+        }
+
+        if machine
+            .transition(from, to)
+            .is_some_and(|transition| !machine.is_passed(from, transition))
+        {
+            return Err(FsmError::ConditionNotPassed(fsm_id, from, to));
+        }
+
+        machine.enter(to);
+        Ok(())
+    }
+
+    // Transition triggered by the server, skips conditions and gets broadcast
+    pub fn force_state(&mut self, fsm_id: i32, to: i32) -> Result<(), FsmError> {
+        let machine = self.machine_mut(fsm_id)?;
+        if machine.data.is_some() && machine.state_index(to).is_none() {
+            return Err(FsmError::StateNotExist(fsm_id, to));
+        }
+
+        let from = machine.current_state;
+        machine.enter(to);
+        self.state_changes.push(ChangeStateNotify {
+            fsm_id,
+            from_state: from,
+            to_state: to,
+        });
+        Ok(())
+    }
+
+    pub fn confirm_state(&mut self, fsm_id: i32, state: i32) -> Result<(), FsmError> {
+        let machine = self.machine_mut(fsm_id)?;
+        if machine.current_state != state {
+            return Err(FsmError::StateIncorrect(fsm_id, machine.current_state));
+        }
+
+        if machine.flag != EFsmStateFlag::Changed {
+            return Err(FsmError::ConfirmNotWait(fsm_id));
+        }
+
+        machine.flag = EFsmStateFlag::Confirmed;
+        Ok(())
+    }
+
+    pub fn pass_condition(
+        &mut self,
+        fsm_id: i32,
+        from: i32,
+        to: i32,
+        index: i32,
+        value: bool,
+    ) -> Result<(), FsmError> {
+        let machine = self.machine_mut(fsm_id)?;
+        // Nothing to track for transitions that are accepted without conditions
+        let Some(transition) = machine.transition(from, to) else {
+            return Ok(());
+        };
+        if index < 0 || index >= transition.condition_count {
+            return Err(FsmError::ConditionNotExist(fsm_id, index, from));
+        }
+
+        let passed = machine.conditions.entry((from, to)).or_default();
+        passed.resize(transition.condition_count as usize, false);
+        passed[index as usize] = value;
+        Ok(())
+    }
+
+    pub fn set_black_board(&mut self, key: i32, value: i32) {
+        if self.black_board.insert(key, value) != Some(value) {
+            self.black_board_changed = true;
+        }
+    }
+
+    pub fn set_custom_black_board(&mut self, key: String, value: i32) {
+        if self.custom_black_board.insert(key, value) != Some(value) {
+            self.custom_black_board_changed = true;
+        }
+    }
+
+    pub fn build_black_board(&self) -> Vec<DFsmBlackBoard> {
+        let mut black_board = self
+            .black_board
+            .iter()
+            .map(|(&key, &value)| DFsmBlackBoard { key, value })
+            .collect::<Vec<_>>();
+        black_board.sort_unstable_by_key(|b| b.key);
+        black_board
+    }
+
+    pub fn build_custom_black_board(&self) -> FsmCustomBlackboardDatas {
+        let mut blackboard_int_values = self
+            .custom_black_board
+            .iter()
+            .map(|(key, &value)| DFsmBlackboardCustom {
+                key: key.clone(),
+                value,
+            })
+            .collect::<Vec<_>>();
+        blackboard_int_values.sort_unstable_by(|a, b| a.key.cmp(&b.key));
+        FsmCustomBlackboardDatas {
+            blackboard_int_values,
+        }
+    }
+
+    pub fn build_component_pb(&self) -> EntityFsmComponentPb {
+        EntityFsmComponentPb {
+            fsms: self
+                .machines
+                .iter()
+                .map(|machine| DFsm {
+                    fsm_id: machine.fsm_id,
+                    current_state: machine.current_state,
+                    flag: machine.flag.into(),
+                    k_ts: machine.k_ts,
+                })
+                .collect(),
+            hash_code: self.hash_code,
+            common_hash_code: self.common_hash_code,
+            black_board: self.build_black_board(),
+            fsm_custom_blackboard_datas: Some(self.build_custom_black_board()),
+        }
+    }
+
+    pub fn build_gm_infos(&self) -> Vec<GmEntityFsmMachineInfo> {
+        self.machines
+            .iter()
+            .map(FsmMachine::build_gm_info)
+            .collect()
+    }
+
+    fn machine_mut(&mut self, fsm_id: i32) -> Result<&mut FsmMachine, FsmError> {
+        self.machines
+            .iter_mut()
+            .find(|machine| machine.fsm_id == fsm_id)
+            .ok_or(FsmError::MachineNotExist(fsm_id))
+    }
+}
@@ -1,4 +1,5 @@
 mod attribute;
+mod buff;
 mod entity_config;
 mod equip;
 mod movement;
@@ -11,6 +12,7 @@ mod monster_ai;
 mod fsm;
 
 pub use attribute::Attribute;
+pub use buff::{Buff, BuffError};
 pub use entity_config::EntityConfig;
 pub use equip::Equip;
 pub use movement::Movement;
@@ -20,4 +22,4 @@ pub use position::Position;
 pub use visibility::Visibility;
 pub use vision_skill::VisionSkill;
 pub use monster_ai::MonsterAi;
-pub use fsm::Fsm;
+pub use fsm::{Fsm, FsmError};
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use crate::logic::ecs::component::Component;
 use shorekeeper_protocol::entity_component_pb::ComponentPb;
 use shorekeeper_protocol::{EntityComponentPb, MonsterAiComponentPb};
@@ -7,6 +9,12 @@ pub struct MonsterAi {
     pub hatred_group_id: i64,
     pub ai_team_init_id: i32,
     pub combat_message_id: i64,
+    // Player whose client runs AI of the monster
+    pub controller_id: i32,
+    // Set on controller change, broadcast and reset by AiSystem
+    pub controller_changed: bool,
+    // entity_id -> hatred value
+    pub hate_list: HashMap<i64, i32>,
 }
 
 impl Component for MonsterAi {
@@ -21,3 +29,27 @@ impl Component for MonsterAi {
         })
     }
 }
+
+impl MonsterAi {
+    pub fn set_controller(&mut self, player_id: i32) {
+        if self.controller_id != player_id {
+            self.controller_id = player_id;
+            self.controller_changed = true;
+        }
+    }
+
+    pub fn add_hatred(&mut self, entity_id: i64, value: i32) {
+        let hatred = self.hate_list.entry(entity_id).or_default();
+        *hatred = hatred.saturating_add(value);
+    }
+
+    // Hated entities, most hated first
+    pub fn hated_entities(&self) -> Vec<i64> {
+        let mut hate_list = self.hate_list.iter().collect::<Vec<_>>();
+        hate_list.sort_unstable_by_key(|(&entity_id, &hatred)| (-hatred, entity_id));
+        hate_list
+            .into_iter()
+            .map(|(&entity_id, _)| entity_id)
+            .collect()
+    }
+}
@@ -34,6 +34,7 @@ impl_component_container! {
     VisionSkill;
     MonsterAi;
     Fsm;
+    Buff;
 }
 
 pub trait Component {
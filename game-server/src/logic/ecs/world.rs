@@ -1,6 +1,7 @@
 use super::component::ComponentContainer;
 use super::entity::{Entity, EntityBuilder, EntityManager};
 use crate::logic::player::InWorldPlayer;
+use shorekeeper_protocol::SceneTimeInfo;
 use std::cell::{RefCell, RefMut};
 use std::collections::hash_map::{Keys, Values};
 use std::collections::HashMap;
@@ -10,8 +11,15 @@ pub struct WorldEntity {
     entity_manager: EntityManager,
 }
 
+#[derive(Clone, Copy)]
+pub struct SceneTime {
+    pub hour: i32,
+    pub minute: i32,
+}
+
 pub struct World {
     pub player_cur_map_id: i32,
+    pub scene_time: SceneTime,
     pub world_entitys: HashMap<i32, WorldEntity>, // i32 -> map_id
     pub in_world_players: HashMap<i32, InWorldPlayer>, // joined players metadata
 }
@@ -20,6 +28,7 @@ impl World {
     pub fn new() -> Self {
         Self {
             player_cur_map_id: 8,
+            scene_time: SceneTime { hour: 8, minute: 0 },
             world_entitys: HashMap::new(),
             in_world_players: HashMap::new(),
         }
@@ -38,6 +47,14 @@ impl World {
             .insert(in_world_player.player_id, in_world_player);
     }
 
+    pub fn build_scene_time_info(&self) -> SceneTimeInfo {
+        SceneTimeInfo {
+            hour: self.scene_time.hour,
+            minute: self.scene_time.minute,
+            owner_time_clock_time_span: 0,
+        }
+    }
+
     pub fn get_mut_world_entity(&mut self) -> &mut WorldEntity {
         self.world_entitys
             .get_mut(&self.player_cur_map_id)
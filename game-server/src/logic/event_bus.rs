@@ -0,0 +1,25 @@
+use super::{
+    player::Player,
+    utils::{achievement_util, calabash_util},
+};
+
+// Gameplay events emitted by logic, dispatched synchronously on the player's logic thread
+pub enum GameEvent {
+    RoleObtained { role_id: i32 },
+    MonsterKilled { config_id: i64 },
+    TeleportUnlocked { teleporter_id: i32 },
+    ItemCrafted { item_id: i32, count: i32 },
+}
+
+pub type EventSubscriber = fn(&mut Player, &GameEvent);
+
+const SUBSCRIBERS: &[EventSubscriber] = &[
+    achievement_util::on_game_event,
+    calabash_util::on_game_event,
+];
+
+pub fn emit(player: &mut Player, event: GameEvent) {
+    SUBSCRIBERS
+        .iter()
+        .for_each(|subscriber| subscriber(player, &event));
+}
@@ -0,0 +1,320 @@
+use shorekeeper_data::{
+    function_condition_data, instance_dungeon_data, level_entity_config_data, role_info_data,
+};
+use shorekeeper_protocol::{MailAttachment, SendMailPush, SyncSceneTimeNotify, TeleportReason};
+
+use crate::logic::{
+    math::{Transform, Vector3f},
+    player::{ItemUsage, Player, PlayerBasicInfo},
+    utils::{fsm_util, world_util},
+};
+
+use crate::mail_task;
+
+use super::{GmArgs, GmCommand, GmError};
+
+// Mails sent by commands expire in 30 days
+const MAIL_VALID_TIME: i64 = 30 * 86400;
+
+pub(super) const COMMANDS: &[GmCommand] = &[
+    GmCommand {
+        name: "help",
+        usage: "help",
+        description: "list available commands",
+        handler: help,
+    },
+    GmCommand {
+        name: "give",
+        usage: "give <item_id> [count]",
+        description: "add items to inventory",
+        handler: give_item,
+    },
+    GmCommand {
+        name: "mail",
+        usage: "mail <player_id> <item_id> [count]",
+        description: "send mail with attachment, player_id 0 sends to everyone",
+        handler: send_mail,
+    },
+    GmCommand {
+        name: "role",
+        usage: "role <role_id>",
+        description: "add role to role list",
+        handler: give_role,
+    },
+    GmCommand {
+        name: "level",
+        usage: "level <level>",
+        description: "set player level",
+        handler: set_level,
+    },
+    GmCommand {
+        name: "tp",
+        usage: "tp <x> <y> <z>",
+        description: "teleport to coordinates in current instance",
+        handler: teleport,
+    },
+    GmCommand {
+        name: "tpi",
+        usage: "tpi <instance_id>",
+        description: "teleport to born position of instance",
+        handler: teleport_instance,
+    },
+    GmCommand {
+        name: "unlock",
+        usage: "unlock <function_id>",
+        description: "unlock game function",
+        handler: unlock_func,
+    },
+    GmCommand {
+        name: "spawn",
+        usage: "spawn <entity_config_id>",
+        description: "spawn level monster at player position",
+        handler: spawn_monster,
+    },
+    GmCommand {
+        name: "fsm",
+        usage: "fsm <entity_id> <fsm_id> <state>",
+        description: "switch entity state machine to state",
+        handler: set_fsm_state,
+    },
+    GmCommand {
+        name: "blackboard",
+        usage: "blackboard <entity_id> <key> <value>",
+        description: "set entity fsm blackboard value, non numeric keys are custom",
+        handler: set_fsm_black_board,
+    },
+    GmCommand {
+        name: "time",
+        usage: "time <hour> [minute]",
+        description: "set world time",
+        handler: set_world_time,
+    },
+];
+
+fn help(_: &mut Player, args: &mut GmArgs) -> Result<String, GmError> {
+    args.finish()?;
+
+    Ok(COMMANDS
+        .iter()
+        .map(|command| format!("{} - {}", command.usage, command.description))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn give_item(player: &mut Player, args: &mut GmArgs) -> Result<String, GmError> {
+    let id = args.next("item_id")?;
+    let quantity = args.next_or("count", 1)?;
+    args.finish()?;
+
+    player
+        .add_items(&[ItemUsage { id, quantity }])
+        .map_err(|err| GmError::Failed(err.to_string()))?;
+
+    Ok(format!("added {quantity} of item {id}"))
+}
+
+fn send_mail(player: &mut Player, args: &mut GmArgs) -> Result<String, GmError> {
+    let player_id = args.next("player_id")?;
+    let item_id = args.next("item_id")?;
+    let count = args.next_or("count", 1)?;
+    args.finish()?;
+
+    if count <= 0 {
+        return Err(GmError::InvalidArgument("count", count.to_string()));
+    }
+
+    mail_task::request_send(SendMailPush {
+        player_id,
+        title: "GM".to_string(),
+        content: format!("Sent by player {}", player.basic_info.id),
+        sender: "GM".to_string(),
+        attachments: vec![MailAttachment { item_id, count }],
+        valid_time: MAIL_VALID_TIME,
+    });
+
+    Ok(format!(
+        "sending {count} of item {item_id} to player {player_id}"
+    ))
+}
+
+fn give_role(player: &mut Player, args: &mut GmArgs) -> Result<String, GmError> {
+    let role_id = args.next("role_id")?;
+    args.finish()?;
+
+    if role_info_data::get(role_id).is_none() {
+        return Err(GmError::Failed(format!("role {role_id} doesn't exist")));
+    }
+
+    if !player.add_role(role_id) {
+        return Err(GmError::Failed(format!("role {role_id} is already owned")));
+    }
+
+    player.notify(player.build_role_list_notify());
+    Ok(format!("added role {role_id}"))
+}
+
+fn set_level(player: &mut Player, args: &mut GmArgs) -> Result<String, GmError> {
+    let level = args.next("level")?;
+    args.finish()?;
+
+    let max_level = PlayerBasicInfo::max_level();
+    if !(1..=max_level).contains(&level) {
+        return Err(GmError::Failed(format!(
+            "level should be in range 1..={max_level}"
+        )));
+    }
+
+    player.set_level(level);
+
+    Ok(format!("player level is set to {level}"))
+}
+
+fn teleport(player: &mut Player, args: &mut GmArgs) -> Result<String, GmError> {
+    let position = Vector3f {
+        x: args.next("x")?,
+        y: args.next("y")?,
+        z: args.next("z")?,
+    };
+    args.finish()?;
+
+    let transform = Transform {
+        position,
+        rotation: player.location.position.rotation.clone(),
+    };
+
+    let instance_id = player.location.instance_id;
+    world_util::teleport_player(player, instance_id, transform, TeleportReason::Gm);
+
+    Ok(format!(
+        "teleported to {:?}",
+        player.location.position.position
+    ))
+}
+
+fn teleport_instance(player: &mut Player, args: &mut GmArgs) -> Result<String, GmError> {
+    let instance_id = args.next("instance_id")?;
+    args.finish()?;
+
+    let Some(data) = instance_dungeon_data::iter().find(|data| data.id == instance_id) else {
+        return Err(GmError::Failed(format!(
+            "instance {instance_id} doesn't exist"
+        )));
+    };
+
+    let transform = Transform {
+        position: Vector3f::from_data(&data.born_position),
+        rotation: Vector3f::from_data(&data.born_rotation),
+    };
+
+    world_util::teleport_player(player, instance_id, transform, TeleportReason::Gm);
+    Ok(format!("teleported to instance {instance_id}"))
+}
+
+fn unlock_func(player: &mut Player, args: &mut GmArgs) -> Result<String, GmError> {
+    let function_id = args.next("function_id")?;
+    args.finish()?;
+
+    if !function_condition_data::iter().any(|data| data.function_id == function_id) {
+        return Err(GmError::Failed(format!(
+            "function {function_id} doesn't exist"
+        )));
+    }
+
+    player.func.unlock(function_id);
+    player.notify(player.func.build_func_open_notify());
+
+    Ok(format!("unlocked function {function_id}"))
+}
+
+fn spawn_monster(player: &mut Player, args: &mut GmArgs) -> Result<String, GmError> {
+    let config_id: i64 = args.next("entity_config_id")?;
+    args.finish()?;
+
+    let Some(data) = level_entity_config_data::get(config_id) else {
+        return Err(GmError::Failed(format!("entity {config_id} doesn't exist")));
+    };
+
+    if !data.blueprint_type.contains("Monster") {
+        return Err(GmError::Failed(format!(
+            "entity {config_id} is not a monster ({})",
+            data.blueprint_type
+        )));
+    }
+
+    let level = world_util::monster_level(player);
+    let entity = {
+        let mut world_ref = player.world.borrow_mut();
+        world_util::build_monster_entity(
+            world_ref.get_mut_world_entity(),
+            config_id as i32, // TODO: Should be i64
+            player.location.instance_id,
+            player.location.position.clone(),
+            level,
+            player.basic_info.id,
+        )
+    };
+
+    world_util::notify_entity_added(player, entity);
+    Ok(format!("spawned {} ({config_id})", data.blueprint_type))
+}
+
+fn set_fsm_state(player: &mut Player, args: &mut GmArgs) -> Result<String, GmError> {
+    let entity_id: i64 = args.next("entity_id")?;
+    let fsm_id = args.next("fsm_id")?;
+    let state = args.next("state")?;
+    args.finish()?;
+
+    let world_ref = player.world.borrow();
+    fsm_util::update_fsm(world_ref.get_world_entity(), entity_id, |fsm| {
+        fsm.force_state(fsm_id, state)
+    })
+    .map_err(|_| GmError::Failed(format!("can't switch fsm {fsm_id} of entity {entity_id}")))?;
+
+    Ok(format!(
+        "entity {entity_id} fsm {fsm_id} switched to {state}"
+    ))
+}
+
+fn set_fsm_black_board(player: &mut Player, args: &mut GmArgs) -> Result<String, GmError> {
+    let entity_id: i64 = args.next("entity_id")?;
+    let key: String = args.next("key")?;
+    let value = args.next("value")?;
+    args.finish()?;
+
+    let world_ref = player.world.borrow();
+    fsm_util::update_fsm(world_ref.get_world_entity(), entity_id, |fsm| {
+        match key.parse() {
+            Ok(key) => fsm.set_black_board(key, value),
+            Err(_) => fsm.set_custom_black_board(key.clone(), value),
+        }
+        Ok(())
+    })
+    .map_err(|_| GmError::Failed(format!("entity {entity_id} has no fsm")))?;
+
+    Ok(format!(
+        "entity {entity_id} blackboard {key} set to {value}"
+    ))
+}
+
+fn set_world_time(player: &mut Player, args: &mut GmArgs) -> Result<String, GmError> {
+    let hour = args.next("hour")?;
+    let minute = args.next_or("minute", 0)?;
+    args.finish()?;
+
+    if !(0..24).contains(&hour) || !(0..60).contains(&minute) {
+        return Err(GmError::Failed(format!("invalid time {hour}:{minute:02}")));
+    }
+
+    let time_info = {
+        let mut world = player.world.borrow_mut();
+        world.scene_time.hour = hour;
+        world.scene_time.minute = minute;
+        world.build_scene_time_info()
+    };
+
+    player.notify(SyncSceneTimeNotify {
+        time_info: Some(time_info),
+    });
+
+    Ok(format!("world time is set to {hour}:{minute:02}"))
+}
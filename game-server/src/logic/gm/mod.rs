@@ -0,0 +1,126 @@
+use std::collections::HashSet;
+use std::str::{FromStr, SplitWhitespace};
+use std::sync::OnceLock;
+
+use shorekeeper_protocol::ErrorCode;
+
+use crate::logic::player::Player;
+
+mod commands;
+
+// Ids of players allowed to run commands, nobody is allowed if not set
+static ALLOWED_PLAYERS: OnceLock<HashSet<i32>> = OnceLock::new();
+
+pub struct GmCommand {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub description: &'static str,
+    handler: fn(&mut Player, &mut GmArgs) -> Result<String, GmError>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GmError {
+    #[error("you are not allowed to use commands")]
+    NotAllowed,
+    #[error("unknown command '{0}', type 'help' to list commands")]
+    UnknownCommand(String),
+    #[error("missing argument <{0}>")]
+    MissingArgument(&'static str),
+    #[error("invalid value '{1}' for argument <{0}>")]
+    InvalidArgument(&'static str, String),
+    #[error("too many arguments")]
+    TooManyArguments,
+    #[error("{0}, usage: {1}")]
+    BadUsage(String, &'static str),
+    #[error("{0}")]
+    Failed(String),
+}
+
+impl From<GmError> for ErrorCode {
+    fn from(err: GmError) -> Self {
+        match err {
+            GmError::NotAllowed | GmError::Failed(_) => ErrorCode::GmFail,
+            _ => ErrorCode::GmParamError,
+        }
+    }
+}
+
+// Whitespace separated arguments of the command line
+pub struct GmArgs<'a>(SplitWhitespace<'a>);
+
+impl GmArgs<'_> {
+    pub fn next<T: FromStr>(&mut self, name: &'static str) -> Result<T, GmError> {
+        let value = self.0.next().ok_or(GmError::MissingArgument(name))?;
+        value
+            .parse()
+            .map_err(|_| GmError::InvalidArgument(name, value.to_string()))
+    }
+
+    pub fn next_or<T: FromStr>(&mut self, name: &'static str, default: T) -> Result<T, GmError> {
+        match self.0.clone().next() {
+            Some(_) => self.next(name),
+            None => Ok(default),
+        }
+    }
+
+    pub fn finish(&mut self) -> Result<(), GmError> {
+        match self.0.next() {
+            Some(_) => Err(GmError::TooManyArguments),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn set_allowed_players(player_ids: &[i32]) {
+    if ALLOWED_PLAYERS
+        .set(player_ids.iter().copied().collect())
+        .is_err()
+    {
+        tracing::error!("gm::set_allowed_players: allowed players are already set");
+    }
+}
+
+pub fn is_allowed(player: &Player) -> bool {
+    ALLOWED_PLAYERS
+        .get()
+        .is_some_and(|ids| ids.contains(&player.basic_info.id))
+}
+
+pub fn run(player: &mut Player, line: &str) -> Result<String, GmError> {
+    if !is_allowed(player) {
+        tracing::warn!(
+            "gm: player {} isn't allowed to execute '{line}'",
+            player.basic_info.id
+        );
+        return Err(GmError::NotAllowed);
+    }
+
+    let mut args = line.split_whitespace();
+    let name = args.next().unwrap_or_default();
+
+    let Some(command) = commands::COMMANDS
+        .iter()
+        .find(|command| command.name.eq_ignore_ascii_case(name))
+    else {
+        return Err(GmError::UnknownCommand(name.to_string()));
+    };
+
+    tracing::debug!("gm: player {} executes '{line}'", player.basic_info.id);
+
+    (command.handler)(player, &mut GmArgs(args)).map_err(|err| match err {
+        GmError::MissingArgument(_) | GmError::InvalidArgument(..) | GmError::TooManyArguments => {
+            GmError::BadUsage(err.to_string(), command.usage)
+        }
+        _ => err,
+    })
+}
+
+// Entry point for chat commands, errors are reported as text.
+// Lines of players that aren't allowed to use commands are left to the chat
+pub fn execute(player: &mut Player, line: &str) -> Option<String> {
+    if !is_allowed(player) {
+        return None;
+    }
+
+    Some(run(player, line).unwrap_or_else(|err| err.to_string()))
+}
@@ -0,0 +1,91 @@
+use shorekeeper_data::drop_package_data;
+use shorekeeper_protocol::{
+    AchievementFinishRequest, AchievementFinishResponse, AchievementGroupProgressNotify,
+    AchievementInfoRequest, AchievementInfoResponse, AchievementProgressNotify,
+    AchievementReceiveRequest, AchievementReceiveResponse, ErrorCode, UpdateAchievementInfoRequest,
+    UpdateAchievementInfoResponse,
+};
+
+use crate::logic::{player::Player, utils::achievement_util};
+
+pub fn on_achievement_info_request(
+    player: &Player,
+    _: AchievementInfoRequest,
+    response: &mut AchievementInfoResponse,
+) {
+    response.achievement_group_info_list = player.achievement.build_group_info_list();
+    response.achievement_finished_star = player.achievement.finished_star();
+    response.finished_achievement_num = player.achievement.finished_count();
+}
+
+pub fn on_update_achievement_info_request(
+    player: &Player,
+    _: UpdateAchievementInfoRequest,
+    response: &mut UpdateAchievementInfoResponse,
+) {
+    response.achievement_entry_list = player.achievement.build_all_entry_list();
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_achievement_finish_request(
+    player: &mut Player,
+    request: AchievementFinishRequest,
+    response: &mut AchievementFinishResponse,
+) {
+    match achievement_util::finish_achievement(player, request.id) {
+        Ok(()) => response.error_code = ErrorCode::Success.into(),
+        Err(err) => {
+            tracing::debug!("AchievementFinishRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+        }
+    }
+}
+
+pub fn on_achievement_receive_request(
+    player: &mut Player,
+    request: AchievementReceiveRequest,
+    response: &mut AchievementReceiveResponse,
+) {
+    let result = match request.is_group_id {
+        true => player.achievement.check_receive_group(request.id),
+        false => player.achievement.check_receive(request.id),
+    };
+
+    let drop_id = match result {
+        Ok(drop_id) => drop_id,
+        Err(err) => {
+            tracing::debug!("AchievementReceiveRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    };
+
+    let Some(drop) = drop_package_data::iter().find(|drop| drop.id == drop_id) else {
+        tracing::warn!("AchievementReceiveRequest: drop package {drop_id} doesn't exist");
+        response.error_code = ErrorCode::AchievementEntryNoConfig.into();
+        return;
+    };
+
+    if let Err(err) = player.add_drop_package(drop) {
+        tracing::warn!("AchievementReceiveRequest: failed to grant drop {drop_id}, err: {err}");
+        response.error_code = ErrorCode::from(err).into();
+        return;
+    }
+
+    match request.is_group_id {
+        true => player.achievement.set_group_received(request.id),
+        false => player.achievement.set_received(request.id),
+    }
+
+    match request.is_group_id {
+        true => player.notify(AchievementGroupProgressNotify {
+            achievement_group_entry: Some(player.achievement.build_group_entry(request.id)),
+        }),
+        false => player.notify(AchievementProgressNotify {
+            achievement_entry: Some(player.achievement.build_entry(request.id)),
+        }),
+    }
+
+    response.item_map = drop.drop_preview.clone();
+    response.error_code = ErrorCode::Success.into();
+}
@@ -0,0 +1,55 @@
+use shorekeeper_protocol::{
+    fight_buff_effect_context, BuffEffectExecuteRequest, BuffEffectExecuteResponse,
+    BuffItemRequest, BuffItemResponse, ErrorCode,
+};
+
+use crate::logic::{player::Player, utils::buff_util};
+
+pub fn on_buff_item_request(
+    player: &mut Player,
+    request: BuffItemRequest,
+    response: &mut BuffItemResponse,
+) {
+    response.role_id = request.role_id;
+
+    match buff_util::use_buff_item(player, request.item_id, request.num, request.role_id) {
+        Ok(buff_item) => {
+            response.buff_item_info = Some(buff_item);
+            response.error_code = ErrorCode::Success.into();
+        }
+        Err(error_code) => {
+            tracing::debug!(
+                "BuffItemRequest: failed to use item {} on role {}",
+                request.item_id,
+                request.role_id
+            );
+            response.error_code = error_code.into();
+        }
+    }
+}
+
+pub fn on_buff_effect_execute_request(
+    player: &mut Player,
+    request: BuffEffectExecuteRequest,
+    response: &mut BuffEffectExecuteResponse,
+) {
+    let handle_ids = match request.context.and_then(|context| context.effect) {
+        Some(fight_buff_effect_context::Effect::AttributeEventEffectData(data)) => {
+            data.triggered_active_handles
+        }
+        None => Vec::new(),
+    };
+
+    response.error_code =
+        match buff_util::execute_buff_effect(player, request.index, &handle_ids) {
+            Ok(()) => ErrorCode::Success,
+            Err(error_code) => {
+                tracing::debug!(
+                "BuffEffectExecuteRequest: failed to execute effect {} of handles {handle_ids:?}",
+                request.index
+            );
+                error_code
+            }
+        }
+        .into();
+}
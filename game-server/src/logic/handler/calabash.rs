@@ -0,0 +1,115 @@
+use shorekeeper_data::{drop_package_data, phantom_exp_item_data};
+use shorekeeper_protocol::{
+    AddCountItemInfo, CalabashBatchRefiningRequest, CalabashBatchRefiningResponse,
+    CalabashLevelRewardRequest, CalabashLevelRewardResponse, CalabashLevelsRewardNotify,
+    CalabashMsgRequest, CalabashMsgResponse, ErrorCode,
+};
+
+use crate::logic::player::{ItemUsage, Player};
+
+const MAX_REFINING_COUNT: usize = 100;
+
+pub fn on_calabash_msg_request(
+    player: &Player,
+    _request: CalabashMsgRequest,
+    response: &mut CalabashMsgResponse,
+) {
+    response.calabash_msg = Some(player.calabash.build_msg());
+    response.calabash_cfg = Some(player.calabash.build_cfg());
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_calabash_level_reward_request(
+    player: &mut Player,
+    request: CalabashLevelRewardRequest,
+    response: &mut CalabashLevelRewardResponse,
+) {
+    let drop_id = match player.calabash.take_level_reward(request.level) {
+        Ok(drop_id) => drop_id,
+        Err(err) => {
+            tracing::debug!("CalabashLevelRewardRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    };
+
+    match drop_package_data::iter().find(|drop| drop.id == drop_id) {
+        Some(drop) => {
+            if let Err(err) = player.add_drop_package(drop) {
+                tracing::warn!(
+                    "CalabashLevelRewardRequest: failed to grant drop {drop_id}, err: {err}"
+                );
+            }
+        }
+        None => tracing::warn!("CalabashLevelRewardRequest: drop package {drop_id} doesn't exist"),
+    }
+
+    player.notify(CalabashLevelsRewardNotify {
+        rewarded_levels: player.calabash.rewarded_levels(),
+    });
+    response.error_code = ErrorCode::Success.into();
+}
+
+// Refines unneeded echoes into echo exp items, exp that doesn't make up a whole item is lost
+pub fn on_calabash_batch_refining_request(
+    player: &mut Player,
+    request: CalabashBatchRefiningRequest,
+    response: &mut CalabashBatchRefiningResponse,
+) {
+    if request.inc_id.is_empty() || request.inc_id.len() > MAX_REFINING_COUNT {
+        response.error_code = ErrorCode::ErrPhantomRefiningCount.into();
+        return;
+    }
+
+    let mut exp = 0i32;
+    for incr_id in request.inc_id.iter() {
+        let Some(phantom) = player.inventory.get_phantom(*incr_id) else {
+            response.error_code = ErrorCode::ErrPhantomItemNotExist.into();
+            return;
+        };
+        exp = exp.saturating_add(phantom.fodder_exp());
+    }
+
+    match player.inventory.consume_with_phantoms(&[], &request.inc_id) {
+        Ok(change) => player.notify_inventory_change(&change),
+        Err(err) => {
+            tracing::debug!("CalabashBatchRefiningRequest: failed to consume phantoms, err: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    }
+
+    let mut exp_items = phantom_exp_item_data::iter().collect::<Vec<_>>();
+    exp_items.sort_unstable_by_key(|item| -item.basic_exp);
+
+    let mut usages = Vec::new();
+    for item in exp_items.into_iter().filter(|item| item.basic_exp > 0) {
+        let count = exp / item.basic_exp;
+        if count > 0 {
+            exp -= count * item.basic_exp;
+            usages.push(ItemUsage {
+                id: item.id,
+                quantity: count,
+            });
+        }
+    }
+
+    if !usages.is_empty() {
+        match player.inventory.add_items(&usages) {
+            Ok(change) => player.notify_inventory_change(&change),
+            Err(err) => {
+                tracing::warn!("CalabashBatchRefiningRequest: failed to add exp items, err: {err}")
+            }
+        }
+    }
+
+    response.items = usages
+        .iter()
+        .map(|usage| AddCountItemInfo {
+            id: usage.id,
+            count: usage.quantity,
+            incr_id: 0,
+        })
+        .collect();
+    response.error_code = ErrorCode::Success.into();
+}
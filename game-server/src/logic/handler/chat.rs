@@ -0,0 +1,193 @@
+use std::sync::OnceLock;
+
+use shorekeeper_protocol::{
+    ChannelChatMessageNotify, ChatChannelType, ChatContentType, ChatMutePlayerRequest,
+    ChatMutePlayerResponse, ChatNotify, ChatRequest, ChatResponse, ErrorCode,
+    PrivateChatDataRequest, PrivateChatDataResponse, PrivateChatHistoryRequest,
+    PrivateChatHistoryResponse, PrivateChatRequest, PrivateChatResponse, PrivateMessageNotify,
+};
+
+use crate::chat_task::{self, ChatDelivery};
+use crate::logic::player::{ChatError, ChatEvent, Player, WorldChatMessage};
+
+// Handles chat line without the leading slash, returned text is sent back to the player.
+// None means the player can't run commands and the line is sent as a normal message
+pub type ChatCommandHook = fn(&mut Player, &str) -> Option<String>;
+
+static COMMAND_HOOK: OnceLock<ChatCommandHook> = OnceLock::new();
+
+pub fn set_chat_command_hook(hook: ChatCommandHook) {
+    if COMMAND_HOOK.set(hook).is_err() {
+        tracing::error!("set_chat_command_hook: hook is already set");
+    }
+}
+
+// Returns false if content is not a command
+fn try_handle_command(player: &mut Player, content: &str) -> bool {
+    let Some(command) = content.strip_prefix('/') else {
+        return false;
+    };
+
+    let Some(reply) = COMMAND_HOOK.get().and_then(|hook| hook(player, command)) else {
+        return false;
+    };
+
+    player.notify(ChatNotify { content: reply });
+    true
+}
+
+pub fn on_chat_request(player: &mut Player, request: ChatRequest, response: &mut ChatResponse) {
+    response.error_code = ErrorCode::Success.into();
+    if try_handle_command(player, &request.content) {
+        return;
+    }
+
+    // ChatRequest carries no content type, world chat only accepts plain text
+    let player_id = player.basic_info.id;
+    let message = match player.chat.create_message(
+        player_id,
+        ChatContentType::Text.into(),
+        request.content.as_str(),
+    ) {
+        Ok(message) => message,
+        Err(err) => {
+            tracing::debug!("ChatRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    };
+
+    chat_task::push(ChatDelivery::World(WorldChatMessage {
+        sender_id: player_id,
+        sender_name: player.basic_info.name.clone(),
+        sender_icon: player.basic_info.head_photo,
+        content_type: message.content_type,
+        content: message.content,
+        send_time: message.send_time,
+    }));
+}
+
+pub fn on_private_chat_request(
+    player: &mut Player,
+    request: PrivateChatRequest,
+    response: &mut PrivateChatResponse,
+) {
+    response.target_uid = request.target_uid;
+    if try_handle_command(player, &request.content) {
+        response.error_code = ErrorCode::Success.into();
+        return;
+    }
+
+    if let Err(err) = check_private_chat(player, request.target_uid) {
+        tracing::debug!("PrivateChatRequest: {err}");
+        response.error_code = ErrorCode::from(err).into();
+        return;
+    }
+
+    let message = match player.chat.create_message(
+        player.basic_info.id,
+        request.chat_content_type,
+        request.content.as_str(),
+    ) {
+        Ok(message) => message,
+        Err(err) => {
+            tracing::debug!("PrivateChatRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    };
+
+    response.msg_id = message.msg_id.clone();
+    response.filter_msg = message.content.clone();
+    response.error_code = ErrorCode::Success.into();
+
+    player.chat.add_history(request.target_uid, message.clone());
+    chat_task::push(ChatDelivery::Private {
+        target_id: request.target_uid,
+        message,
+    });
+}
+
+fn check_private_chat(player: &Player, target_id: i32) -> Result<(), ChatError> {
+    if player.social.is_blocked(target_id) {
+        return Err(ChatError::Blocked(target_id));
+    }
+
+    if !player.social.is_friend(target_id) {
+        return Err(ChatError::NotFriend(target_id));
+    }
+
+    Ok(())
+}
+
+pub fn on_private_chat_history_request(
+    player: &mut Player,
+    request: PrivateChatHistoryRequest,
+    response: &mut PrivateChatHistoryResponse,
+) {
+    response.data = Some(
+        player
+            .chat
+            .build_history(request.target_uid, request.start_index),
+    );
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_private_chat_data_request(
+    player: &mut Player,
+    _: PrivateChatDataRequest,
+    _: &mut PrivateChatDataResponse,
+) {
+    player.notify(player.chat.build_history_notify());
+}
+
+pub fn on_chat_mute_player_request(
+    player: &mut Player,
+    request: ChatMutePlayerRequest,
+    response: &mut ChatMutePlayerResponse,
+) {
+    let player_id = player.basic_info.id;
+    if let Err(err) = player
+        .chat
+        .set_mute(player_id, request.target_uid, request.mute)
+    {
+        tracing::debug!("ChatMutePlayerRequest: {err}");
+        response.error_code = ErrorCode::from(err).into();
+        return;
+    }
+
+    if !request.mute {
+        response.remove_mute_player_id.push(request.target_uid);
+    }
+
+    response.error_code = ErrorCode::Success.into();
+    player.notify(player.chat.build_mute_list_notify());
+}
+
+// Messages from other players, delivered by chat_task
+pub fn on_chat_event(player: &mut Player, event: ChatEvent) {
+    let sender_id = match &event {
+        ChatEvent::World(message) => message.sender_id,
+        ChatEvent::Private(message) => message.sender_id,
+    };
+
+    if sender_id != player.basic_info.id
+        && (player.chat.is_muted(sender_id) || player.social.is_blocked(sender_id))
+    {
+        return;
+    }
+
+    match event {
+        ChatEvent::World(message) => player.notify(ChannelChatMessageNotify {
+            channel_type: ChatChannelType::Default.into(),
+            message_info: Some(message.to_protobuf()),
+            ..Default::default()
+        }),
+        ChatEvent::Private(message) => {
+            player.notify(PrivateMessageNotify {
+                chat_content: Some(message.to_protobuf()),
+            });
+            player.chat.add_history(sender_id, message);
+        }
+    }
+}
@@ -1,10 +1,23 @@
+use crate::logic::components::{Buff, BuffError, Fsm, FsmError};
+use crate::logic::ecs::component::ComponentContainer;
+use crate::logic::ecs::world::WorldEntity;
 use crate::logic::player::Player;
+use crate::logic::utils::{ai_util, combat_util, fsm_util};
+use crate::query_components;
+use shorekeeper_protocol::ai::{
+    AiControlSwitchRequest, AiControlSwitchResponse, AiHateEntity, AiHateResponse,
+    AiInformationResponse,
+};
 use shorekeeper_protocol::combat_message::{
     combat_receive_data, combat_request_data, combat_response_data, combat_send_data,
     CombatReceiveData, CombatRequestData, CombatResponseData, CombatSendPackRequest,
     CombatSendPackResponse,
 };
-use shorekeeper_protocol::{ErrorCode, SwitchRoleRequest, SwitchRoleResponse};
+use shorekeeper_protocol::{
+    BuffEffectResponse, BuffStackCountResponse, ChangeStateConfirmResponse, ChangeStateResponse,
+    DErrorResult, DamageExecuteRequest, DamageExecuteResponse, ErrorCode, FsmConditionPassResponse,
+    HitRequest, HitResponse, ReviveRequest, ReviveResponse, SwitchRoleRequest, SwitchRoleResponse,
+};
 
 #[inline(always)]
 fn create_combat_response(
@@ -22,6 +35,19 @@ fn create_combat_response(
     }
 }
 
+#[inline(always)]
+fn push_combat_response(
+    response: &mut CombatSendPackResponse,
+    combat_request: &CombatRequestData,
+    message: combat_response_data::Message,
+) {
+    response
+        .receive_pack_notify
+        .get_or_insert_with(Default::default)
+        .data
+        .push(create_combat_response(combat_request, message));
+}
+
 pub fn on_combat_message_combat_send_pack_request(
     player: &mut Player,
     request: CombatSendPackRequest,
@@ -34,6 +60,117 @@ pub fn on_combat_message_combat_send_pack_request(
                     combat_request_data::Message::SwitchRoleRequest(ref request) => {
                         handle_switch_role_request(player, request_data, request, response);
                     }
+                    combat_request_data::Message::DamageExecuteRequest(ref request) => {
+                        handle_damage_execute_request(player, request_data, request, response);
+                    }
+                    combat_request_data::Message::HitRequest(ref request) => {
+                        handle_hit_request(request_data, request, response);
+                    }
+                    combat_request_data::Message::AiHateRequest(ref request) => {
+                        let error_code = update_hate_list(player, request_data, &request.hate_list);
+                        push_combat_response(
+                            response,
+                            request_data,
+                            combat_response_data::Message::AiHateResponse(AiHateResponse {
+                                error_code: error_code.into(),
+                            }),
+                        );
+                    }
+                    combat_request_data::Message::AiInformationRequest(ref request) => {
+                        let hate_list = request
+                            .ai_info
+                            .as_ref()
+                            .map(|info| info.hate_list.as_slice())
+                            .unwrap_or_default();
+                        let error_code = update_hate_list(player, request_data, hate_list);
+                        push_combat_response(
+                            response,
+                            request_data,
+                            combat_response_data::Message::AiInformationResponse(
+                                AiInformationResponse {
+                                    error_code: error_code.into(),
+                                },
+                            ),
+                        );
+                    }
+                    combat_request_data::Message::ChangeStateRequest(ref request) => {
+                        let result = update_fsm(player, request_data, |fsm| {
+                            fsm.change_state(request.fsm_id, request.from_state, request.to_state)
+                        });
+                        push_combat_response(
+                            response,
+                            request_data,
+                            combat_response_data::Message::ChangeStateResponse(
+                                ChangeStateResponse {
+                                    fsm_id: request.fsm_id,
+                                    error: Some(result),
+                                },
+                            ),
+                        );
+                    }
+                    combat_request_data::Message::ChangeStateConfirmRequest(ref request) => {
+                        let result = update_fsm(player, request_data, |fsm| {
+                            fsm.confirm_state(request.fsm_id, request.state)
+                        });
+                        push_combat_response(
+                            response,
+                            request_data,
+                            combat_response_data::Message::ChangeStateConfirmResponse(
+                                ChangeStateConfirmResponse {
+                                    fsm_id: request.fsm_id,
+                                    state: request.state,
+                                    error: Some(result),
+                                },
+                            ),
+                        );
+                    }
+                    combat_request_data::Message::FsmConditionPassRequest(ref request) => {
+                        let result = update_fsm(player, request_data, |fsm| {
+                            fsm.pass_condition(
+                                request.fsm_id,
+                                request.from_state,
+                                request.to_state,
+                                request.condition_index,
+                                request.value,
+                            )
+                        });
+                        push_combat_response(
+                            response,
+                            request_data,
+                            combat_response_data::Message::FsmConditionPassResponse(
+                                FsmConditionPassResponse {
+                                    fsm_id: request.fsm_id,
+                                    error: Some(result),
+                                },
+                            ),
+                        );
+                    }
+                    combat_request_data::Message::BuffStackCountRequest(ref request) => {
+                        let error_code = update_buff(player, request_data, |buff| {
+                            buff.set_stack_count(request.handle_id, request.new_stack_count)
+                        });
+                        push_combat_response(
+                            response,
+                            request_data,
+                            combat_response_data::Message::BuffStackCountResponse(
+                                BuffStackCountResponse {
+                                    error_code: error_code.into(),
+                                },
+                            ),
+                        );
+                    }
+                    combat_request_data::Message::BuffEffectRequest(ref request) => {
+                        let error_code = update_buff(player, request_data, |buff| {
+                            buff.check_effect(request.handle_id, request.index)
+                        });
+                        push_combat_response(
+                            response,
+                            request_data,
+                            combat_response_data::Message::BuffEffectResponse(BuffEffectResponse {
+                                error_code: error_code.into(),
+                            }),
+                        );
+                    }
                     _ => {}
                 }
             }
@@ -71,3 +208,202 @@ fn handle_switch_role_request(
 
     response.error_code = ErrorCode::Success.into();
 }
+
+fn handle_damage_execute_request(
+    player: &mut Player,
+    combat_request: &CombatRequestData,
+    request: &DamageExecuteRequest,
+    response: &mut CombatSendPackResponse,
+) {
+    let result = {
+        let world_ref = player.world.borrow();
+        combat_util::execute_damage(player, world_ref.get_world_entity(), request)
+    };
+
+    let damage_response = match result {
+        Ok(result) => {
+            if result.killed {
+                combat_util::on_entity_killed(player, request.target_entity_id);
+            }
+
+            DamageExecuteResponse {
+                error_code: ErrorCode::Success.into(),
+                attacker_entity_id: request.attacker_entity_id,
+                target_entity_id: request.target_entity_id,
+                damage: result.damage,
+                part_id: request.part_id,
+                is_crit: result.is_crit,
+                killed_target: result.killed,
+                ..Default::default()
+            }
+        }
+        Err(error_code) => {
+            tracing::debug!(
+                "DamageExecuteRequest: entity {} can't damage entity {}",
+                request.attacker_entity_id,
+                request.target_entity_id
+            );
+
+            DamageExecuteResponse {
+                error_code: error_code.into(),
+                attacker_entity_id: request.attacker_entity_id,
+                target_entity_id: request.target_entity_id,
+                ..Default::default()
+            }
+        }
+    };
+
+    push_combat_response(
+        response,
+        combat_request,
+        combat_response_data::Message::DamageExecuteResponse(damage_response),
+    );
+}
+
+// Hits only carry presentation, damage is applied through DamageExecuteRequest
+fn handle_hit_request(
+    combat_request: &CombatRequestData,
+    request: &HitRequest,
+    response: &mut CombatSendPackResponse,
+) {
+    push_combat_response(
+        response,
+        combat_request,
+        combat_response_data::Message::HitResponse(HitResponse {
+            hit_info: request.hit_info.clone(),
+            error_code: ErrorCode::Success.into(),
+        }),
+    );
+}
+
+// Only controller of the monster's AI can report its hatred
+fn update_hate_list(
+    player: &Player,
+    combat_request: &CombatRequestData,
+    hate_list: &[AiHateEntity],
+) -> ErrorCode {
+    let entity_id = combat_request
+        .combat_common
+        .map(|common| common.entity_id)
+        .unwrap_or_default();
+
+    let world_ref = player.world.borrow();
+    match ai_util::update_hate_list(
+        world_ref.get_world_entity(),
+        player.basic_info.id,
+        entity_id,
+        hate_list,
+    ) {
+        Ok(()) => ErrorCode::Success,
+        Err(error_code) => {
+            tracing::debug!("AiHateRequest: can't update hate list of entity {entity_id}");
+            error_code
+        }
+    }
+}
+
+fn update_fsm(
+    player: &Player,
+    combat_request: &CombatRequestData,
+    f: impl FnOnce(&mut Fsm) -> Result<(), FsmError>,
+) -> DErrorResult {
+    let entity_id = combat_request
+        .combat_common
+        .map(|common| common.entity_id)
+        .unwrap_or_default();
+
+    let world_ref = player.world.borrow();
+    let world = world_ref.get_world_entity();
+    let error_code = match is_controlled(player, world, entity_id) {
+        true => match fsm_util::update_fsm(world, entity_id, f) {
+            Ok(()) => ErrorCode::Success,
+            Err(error_code) => error_code,
+        },
+        false => ErrorCode::ErrNoControlRights,
+    };
+
+    DErrorResult {
+        error_code: error_code.into(),
+        error_params: Vec::new(),
+    }
+}
+
+// Only own roles and monsters controlled by the player can be changed by client
+fn is_controlled(player: &Player, world: &WorldEntity, entity_id: i64) -> bool {
+    let (owner, monster_ai) = query_components!(world, entity_id, OwnerPlayer, MonsterAi);
+    let is_controlled = owner.is_some_and(|owner| owner.0 == player.basic_info.id)
+        || monster_ai.is_some_and(|ai| ai.controller_id == player.basic_info.id);
+    if !is_controlled {
+        tracing::debug!(
+            "CombatSendPack: player {} doesn't control entity {entity_id}",
+            player.basic_info.id
+        );
+    }
+    is_controlled
+}
+
+fn update_buff(
+    player: &Player,
+    combat_request: &CombatRequestData,
+    f: impl FnOnce(&mut Buff) -> Result<(), BuffError>,
+) -> ErrorCode {
+    let entity_id = combat_request
+        .combat_common
+        .map(|common| common.entity_id)
+        .unwrap_or_default();
+
+    let world_ref = player.world.borrow();
+    let world = world_ref.get_world_entity();
+    let (Some(mut buff),) = query_components!(world, entity_id, Buff) else {
+        return ErrorCode::ErrNoBuffConf;
+    };
+
+    if !is_controlled(player, world, entity_id) {
+        return ErrorCode::ErrNoControlRights;
+    }
+
+    match f(&mut buff) {
+        Ok(()) => ErrorCode::Success,
+        Err(err) => {
+            tracing::debug!("CombatSendPack: entity {entity_id}, {err}");
+            err.into()
+        }
+    }
+}
+
+pub fn on_ai_ai_control_switch_request(
+    player: &mut Player,
+    request: AiControlSwitchRequest,
+    response: &mut AiControlSwitchResponse,
+) {
+    let world_ref = player.world.borrow();
+    response.error_code = match ai_util::take_control(
+        world_ref.get_world_entity(),
+        player.basic_info.id,
+        player.location.map_id(),
+        &player.location.position.position,
+        request.entity_id,
+    ) {
+        Ok(()) => ErrorCode::Success.into(),
+        Err(error_code) => error_code.into(),
+    };
+}
+
+pub fn on_revive_request(
+    player: &mut Player,
+    request: ReviveRequest,
+    response: &mut ReviveResponse,
+) {
+    if request.use_item {
+        response.error_code = ErrorCode::ErrCanNotUseItemRevive.into();
+        return;
+    }
+
+    if !combat_util::is_party_wiped(player) {
+        response.error_code = ErrorCode::ErrPlayerIsNotDead.into();
+        return;
+    }
+
+    combat_util::revive_party(player);
+    response.error_code = ErrorCode::Success.into();
+}
@@ -0,0 +1,285 @@
+use shorekeeper_data::drop_package_data;
+use shorekeeper_protocol::{
+    ErrorCode, ForgeFormulaUnlockRequest, ForgeFormulaUnlockResponse, ForgeInfoRequest,
+    ForgeInfoResponse, ForgeItemRequest, ForgeItemResponse, SendCertificateLevelRewardRequest,
+    SendCertificateLevelRewardResponse, SendCookFoodRequest, SendCookFoodResponse,
+    SendCookFormulaRequest, SendCookFormulaResponse, SendCookingDataRequest,
+    SendCookingDataResponse, SingleItemInfo, SynthesisFormulaUnlockRequest,
+    SynthesisFormulaUnlockResponse, SynthesisInfoRequest, SynthesisInfoResponse,
+    SynthesisItemRequest, SynthesisItemResponse, SynthesisLevelRewardRequest,
+    SynthesisLevelRewardResponse, UpdateCookerInfoNotify,
+};
+
+use crate::logic::{
+    event_bus::{self, GameEvent},
+    player::{CraftType, ItemUsage, Player},
+};
+
+pub fn on_synthesis_info_request(
+    player: &Player,
+    _request: SynthesisInfoRequest,
+    response: &mut SynthesisInfoResponse,
+) {
+    response.synthesis_info_list = player.crafting.build_synthesis_info_list();
+    response.level_info = Some(player.crafting.build_synthesis_level_info());
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_synthesis_item_request(
+    player: &mut Player,
+    request: SynthesisItemRequest,
+    response: &mut SynthesisItemResponse,
+) {
+    response.id = request.id;
+    response.role_id = request.role_id;
+
+    match craft(
+        player,
+        CraftType::Synthesis,
+        request.id,
+        request.role_id,
+        request.count,
+    ) {
+        Ok(item_infos) => {
+            response.item_infos = item_infos;
+            response.error_code = ErrorCode::Success.into();
+        }
+        Err(err) => response.error_code = err.into(),
+    }
+}
+
+pub fn on_synthesis_formula_unlock_request(
+    player: &mut Player,
+    request: SynthesisFormulaUnlockRequest,
+    response: &mut SynthesisFormulaUnlockResponse,
+) {
+    response.id = request.id;
+    response.error_code = match unlock_formula(player, CraftType::Synthesis, request.id) {
+        Ok(()) => ErrorCode::Success.into(),
+        Err(err) => err.into(),
+    };
+}
+
+pub fn on_synthesis_level_reward_request(
+    player: &mut Player,
+    _request: SynthesisLevelRewardRequest,
+    response: &mut SynthesisLevelRewardResponse,
+) {
+    response.error_code = match level_up(player, CraftType::Synthesis) {
+        Ok(()) => ErrorCode::Success.into(),
+        Err(err) => err.into(),
+    };
+    response.level_info = Some(player.crafting.build_synthesis_level_info());
+}
+
+pub fn on_forge_info_request(
+    player: &Player,
+    _request: ForgeInfoRequest,
+    response: &mut ForgeInfoResponse,
+) {
+    response.forge_info_list = player.crafting.build_forge_info_list();
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_forge_item_request(
+    player: &mut Player,
+    request: ForgeItemRequest,
+    response: &mut ForgeItemResponse,
+) {
+    response.id = request.id;
+    response.role_id = request.role_id;
+
+    match craft(
+        player,
+        CraftType::Forge,
+        request.id,
+        request.role_id,
+        request.count,
+    ) {
+        Ok(item_infos) => {
+            response.item_infos = item_infos;
+            response.error_code = ErrorCode::Success.into();
+        }
+        Err(err) => response.error_code = err.into(),
+    }
+}
+
+pub fn on_forge_formula_unlock_request(
+    player: &mut Player,
+    request: ForgeFormulaUnlockRequest,
+    response: &mut ForgeFormulaUnlockResponse,
+) {
+    response.id = request.id;
+    response.error_code = match unlock_formula(player, CraftType::Forge, request.id) {
+        Ok(()) => ErrorCode::Success.into(),
+        Err(err) => err.into(),
+    };
+}
+
+pub fn on_send_cooking_data_request(
+    player: &Player,
+    _request: SendCookingDataRequest,
+    response: &mut SendCookingDataResponse,
+) {
+    response.cooker_info = Some(player.crafting.build_cooker_info());
+    response.cooking_data = player.crafting.build_cooking_data_list();
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_send_cook_formula_request(
+    player: &mut Player,
+    request: SendCookFormulaRequest,
+    response: &mut SendCookFormulaResponse,
+) {
+    response.cook_formula_id = request.cook_formula_id;
+    response.error_code = match unlock_formula(player, CraftType::Cook, request.cook_formula_id) {
+        Ok(()) => ErrorCode::Success.into(),
+        Err(err) => err.into(),
+    };
+}
+
+pub fn on_send_cook_food_request(
+    player: &mut Player,
+    request: SendCookFoodRequest,
+    response: &mut SendCookFoodResponse,
+) {
+    response.id = request.id;
+    response.role_id = request.role_id;
+
+    match craft(
+        player,
+        CraftType::Cook,
+        request.id,
+        request.role_id,
+        request.cook_count,
+    ) {
+        Ok(item_infos) => {
+            player.notify(UpdateCookerInfoNotify {
+                cooker_info: Some(player.crafting.build_cooker_info()),
+            });
+            response.item_infos = item_infos;
+            response.error_code = ErrorCode::Success.into();
+        }
+        Err(err) => response.error_code = err.into(),
+    }
+}
+
+pub fn on_send_certificate_level_reward_request(
+    player: &mut Player,
+    _request: SendCertificateLevelRewardRequest,
+    response: &mut SendCertificateLevelRewardResponse,
+) {
+    match level_up(player, CraftType::Cook) {
+        Ok(()) => {
+            player.notify(UpdateCookerInfoNotify {
+                cooker_info: Some(player.crafting.build_cooker_info()),
+            });
+            response.error_code = ErrorCode::Success.into();
+        }
+        Err(err) => response.error_code = err.into(),
+    }
+}
+
+fn craft(
+    player: &mut Player,
+    craft_type: CraftType,
+    id: i32,
+    role_id: i32,
+    count: i32,
+) -> Result<Vec<SingleItemInfo>, ErrorCode> {
+    let formula = player
+        .crafting
+        .check_craft(craft_type, id, count)
+        .map_err(|err| {
+            tracing::debug!("craft: {err}");
+            ErrorCode::from(err)
+        })?;
+
+    let usages = formula
+        .consume
+        .iter()
+        .map(|(&id, &quantity)| ItemUsage {
+            id,
+            quantity: quantity * count,
+        })
+        .collect::<Vec<_>>();
+
+    // Output has to fit into the inventory before inputs are taken
+    let output = [ItemUsage {
+        id: formula.item_id,
+        quantity: formula.item_count * count,
+    }];
+    player.inventory.check_add(&output).map_err(|err| {
+        tracing::debug!("craft: output of {craft_type:?} formula {id} doesn't fit, err: {err}");
+        ErrorCode::from(err)
+    })?;
+
+    player.consume_items(&usages).map_err(|err| {
+        tracing::debug!("craft: {craft_type:?} formula {id}, err: {err}");
+        ErrorCode::from(err)
+    })?;
+
+    let change = player.inventory.add_items(&output)?;
+    player.notify_inventory_change(&change);
+
+    player
+        .crafting
+        .on_crafted(craft_type, &formula, role_id, count);
+    event_bus::emit(
+        player,
+        GameEvent::ItemCrafted {
+            item_id: formula.item_id,
+            count,
+        },
+    );
+
+    Ok(vec![SingleItemInfo {
+        item_id: formula.item_id,
+        item_num: formula.item_count * count,
+    }])
+}
+
+fn unlock_formula(player: &mut Player, craft_type: CraftType, id: i32) -> Result<(), ErrorCode> {
+    let formula = player
+        .crafting
+        .check_unlock(craft_type, id)
+        .map_err(|err| {
+            tracing::debug!("unlock_formula: {err}");
+            ErrorCode::from(err)
+        })?;
+
+    player
+        .consume_items(&[ItemUsage {
+            id: formula.formula_item_id,
+            quantity: 1,
+        }])
+        .map_err(|err| {
+            tracing::debug!("unlock_formula: {craft_type:?} formula {id}, err: {err}");
+            ErrorCode::from(err)
+        })?;
+
+    player.crafting.unlock(craft_type, id);
+    Ok(())
+}
+
+fn level_up(player: &mut Player, craft_type: CraftType) -> Result<(), ErrorCode> {
+    let drop_id = player.crafting.check_level_up(craft_type).map_err(|err| {
+        tracing::debug!("level_up: {err}");
+        ErrorCode::from(err)
+    })?;
+
+    let Some(drop) = drop_package_data::iter().find(|drop| drop.id == drop_id) else {
+        tracing::warn!("level_up: drop package {drop_id} of {craft_type:?} level doesn't exist");
+        player.crafting.level_up(craft_type);
+        return Ok(());
+    };
+
+    // Level reward is granted together with the level or not at all
+    player.add_drop_package(drop).map_err(|err| {
+        tracing::debug!("level_up: failed to grant drop {drop_id}, err: {err}");
+        ErrorCode::from(err)
+    })?;
+
+    player.crafting.level_up(craft_type);
+    Ok(())
+}
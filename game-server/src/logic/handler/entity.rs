@@ -121,8 +121,9 @@ pub fn on_move_package_push(player: &mut Player, push: MovePackagePush) {
                 .extend(moving_entity.move_infos);
         }
 
-        // TODO: review instance id vs map id in world
-        let map = logic::utils::quadrant_util::get_map(player.location.instance_id);
+        let Some(map) = logic::utils::quadrant_util::try_get_map(player.location.map_id()) else {
+            continue;
+        };
         let quadrant_id = map.get_quadrant_id(
             player.location.position.position.x * 100.0,
             player.location.position.position.y * 100.0,
@@ -136,4 +137,6 @@ pub fn on_move_package_push(player: &mut Player, push: MovePackagePush) {
             logic::utils::world_util::add_entities(player, &entities_to_add);
         }
     }
+
+    logic::utils::map_util::update_unlocked_area(player);
 }
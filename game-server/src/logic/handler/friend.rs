@@ -0,0 +1,199 @@
+use shorekeeper_protocol::{
+    BlockListRequest, BlockListResponse, BlockPlayerRequest, BlockPlayerResponse, ErrorCode,
+    FriendAddedNotify, FriendAllRequest, FriendAllResponse, FriendApplyDeletedNotify,
+    FriendApplyHandleRequest, FriendApplyHandleResponse, FriendApplyOperator,
+    FriendApplyReceivedNotify, FriendApplySendRequest, FriendApplySendResponse,
+    FriendDeleteRequest, FriendDeleteResponse, FriendDeletedNotify, FriendRemarkRequest,
+    FriendRemarkResponse, SysErrorNotify, UnblockPlayerRequest, UnblockPlayerResponse,
+};
+
+use crate::logic::player::{Player, SocialEvent};
+use crate::social_task::{self, SocialRequest};
+
+pub fn on_friend_all_request(
+    player: &mut Player,
+    _: FriendAllRequest,
+    response: &mut FriendAllResponse,
+) {
+    response.friend_info_list = player.social.friends().map(|f| f.to_protobuf()).collect();
+    response.friend_apply_list = player
+        .social
+        .applications()
+        .map(|apply| apply.to_protobuf())
+        .collect();
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_friend_apply_send_request(
+    player: &mut Player,
+    request: FriendApplySendRequest,
+    response: &mut FriendApplySendResponse,
+) {
+    let player_id = player.basic_info.id;
+    // Target's side is checked by social_task, failure comes back as SocialEvent::RequestFailed
+    if let Err(err) = player.social.check_apply(player_id, request.id) {
+        tracing::debug!("FriendApplySendRequest: {err}");
+        response.error_code = ErrorCode::from(err).into();
+        return;
+    }
+
+    social_task::push(SocialRequest::ApplySend {
+        player_id,
+        target_id: request.id,
+    });
+
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_friend_apply_handle_request(
+    player: &mut Player,
+    request: FriendApplyHandleRequest,
+    response: &mut FriendApplyHandleResponse,
+) {
+    if request.ids.is_empty() {
+        response.error_code = ErrorCode::ErrFriendRequestEmpty.into();
+        return;
+    }
+
+    let player_id = player.basic_info.id;
+    let approve = request.operator == i32::from(FriendApplyOperator::Approve);
+    for &applicant_id in request.ids.iter() {
+        // Applicant's side of approved application is checked by social_task
+        let result = match player.social.handle_apply(applicant_id, approve) {
+            Ok(()) => {
+                social_task::push(SocialRequest::ApplyHandle {
+                    player_id,
+                    applicant_id,
+                    approve,
+                });
+
+                player.notify(FriendApplyDeletedNotify { id: applicant_id });
+                ErrorCode::Success
+            }
+            Err(err) => {
+                tracing::debug!("FriendApplyHandleRequest: {err}");
+                ErrorCode::from(err)
+            }
+        };
+
+        response.handled_map.insert(applicant_id, result.into());
+    }
+
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_friend_delete_request(
+    player: &mut Player,
+    request: FriendDeleteRequest,
+    response: &mut FriendDeleteResponse,
+) {
+    if let Err(err) = player.social.delete_friend(request.id) {
+        tracing::debug!("FriendDeleteRequest: {err}");
+        response.error_code = ErrorCode::from(err).into();
+        return;
+    }
+
+    social_task::push(SocialRequest::DeleteFriend {
+        player_id: player.basic_info.id,
+        friend_id: request.id,
+    });
+
+    player.notify(FriendDeletedNotify { id: request.id });
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_friend_remark_request(
+    player: &mut Player,
+    request: FriendRemarkRequest,
+    response: &mut FriendRemarkResponse,
+) {
+    if let Err(err) = player.social.set_remark(request.id, &request.remark) {
+        tracing::debug!("FriendRemarkRequest: {err}");
+        response.error_code = ErrorCode::from(err).into();
+        return;
+    }
+
+    social_task::push(SocialRequest::Remark {
+        player_id: player.basic_info.id,
+        friend_id: request.id,
+        remark: request.remark,
+    });
+
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_block_list_request(
+    player: &mut Player,
+    _: BlockListRequest,
+    response: &mut BlockListResponse,
+) {
+    response.block_list = player.social.blocks().map(|b| b.to_protobuf()).collect();
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_block_player_request(
+    player: &mut Player,
+    request: BlockPlayerRequest,
+    response: &mut BlockPlayerResponse,
+) {
+    let player_id = player.basic_info.id;
+    match player.social.block(player_id, request.id) {
+        Ok(info) => {
+            social_task::push(SocialRequest::Block {
+                player_id,
+                target_id: request.id,
+            });
+
+            response.info = Some(info.to_protobuf());
+            response.error_code = ErrorCode::Success.into();
+        }
+        Err(err) => {
+            tracing::debug!("BlockPlayerRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+        }
+    }
+}
+
+pub fn on_unblock_player_request(
+    player: &mut Player,
+    request: UnblockPlayerRequest,
+    response: &mut UnblockPlayerResponse,
+) {
+    if let Err(err) = player.social.unblock(request.id) {
+        tracing::debug!("UnblockPlayerRequest: {err}");
+        response.error_code = ErrorCode::from(err).into();
+        return;
+    }
+
+    social_task::push(SocialRequest::Unblock {
+        player_id: player.basic_info.id,
+        target_id: request.id,
+    });
+
+    response.error_code = ErrorCode::Success.into();
+}
+
+// Changes made by other players, delivered by social_task
+pub fn on_social_event(player: &mut Player, event: SocialEvent) {
+    if !player.social.apply_event(&event) {
+        return;
+    }
+
+    match event {
+        SocialEvent::ApplyReceived(apply) => player.notify(FriendApplyReceivedNotify {
+            friend_apply: Some(apply.to_protobuf()),
+        }),
+        SocialEvent::FriendAdded(friend) => player.notify(FriendAddedNotify {
+            info: Some(friend.to_protobuf()),
+        }),
+        SocialEvent::FriendDeleted(id) => player.notify(FriendDeletedNotify { id }),
+        SocialEvent::PlayerInfo(_) => (),
+        SocialEvent::RequestFailed(err) => {
+            tracing::debug!("SocialEvent: request failed, err: {err}");
+            player.notify(SysErrorNotify {
+                error_code: ErrorCode::from(err).into(),
+                error_params: Vec::new(),
+            });
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use shorekeeper_data::role_info_data;
+use shorekeeper_protocol::{
+    ErrorCode, GachaInfoRequest, GachaInfoResponse, GachaPoolDetailRequest,
+    GachaPoolDetailResponse, GachaRequest, GachaResponse, GachaResult, GachaReward,
+    GachaUsePoolRequest, GachaUsePoolResponse,
+};
+
+use crate::logic::player::{ItemUsage, Player, PlayerGacha};
+
+pub fn on_gacha_info_request(
+    player: &mut Player,
+    _: GachaInfoRequest,
+    response: &mut GachaInfoResponse,
+) {
+    response.gacha_infos = player.gacha.build_gacha_infos();
+    response.record_id = player.basic_info.id.to_string();
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_gacha_request(player: &mut Player, request: GachaRequest, response: &mut GachaResponse) {
+    let (gacha, pool, rule) = match player
+        .gacha
+        .check_gacha(request.gacha_id, request.gacha_times)
+    {
+        Ok(res) => res,
+        Err(err) => {
+            tracing::debug!("GachaRequest: check failed, err: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    };
+
+    let prepared = player
+        .gacha
+        .prepare_pulls(gacha, rule, pool, request.gacha_times);
+
+    let mut gacha_results = Vec::with_capacity(prepared.pulls.len());
+    let mut rewards = Vec::new();
+    let mut new_roles = Vec::new();
+
+    for pull in prepared.pulls.iter() {
+        let mut transform_rewards = Vec::new();
+
+        if PlayerGacha::is_role(pull.item_id) {
+            if !player.role_list.contains_key(&pull.item_id) && !new_roles.contains(&pull.item_id) {
+                new_roles.push(pull.item_id);
+            } else {
+                // Duplicate roles are converted into resonance items
+                let Some(role_info) = role_info_data::get(pull.item_id) else {
+                    tracing::debug!("GachaRequest: role {} has no config", pull.item_id);
+                    response.error_code = ErrorCode::ErrRoleConfigNotRight.into();
+                    return;
+                };
+
+                for (&item_id, &item_count) in role_info.spillover_item.iter() {
+                    transform_rewards.push(GachaReward {
+                        item_id,
+                        item_count,
+                    });
+                    rewards.push(ItemUsage {
+                        id: item_id,
+                        quantity: item_count,
+                    });
+                }
+            }
+        } else {
+            rewards.push(ItemUsage {
+                id: pull.item_id,
+                quantity: 1,
+            });
+        }
+
+        gacha_results.push(GachaResult {
+            gacha_reward: Some(GachaReward {
+                item_id: pull.item_id,
+                item_count: 1,
+            }),
+            transform_rewards,
+            ..Default::default()
+        });
+    }
+
+    // Rewards have to fit into the inventory before currency is taken
+    if let Err(err) = player.inventory.check_add(&rewards) {
+        tracing::debug!("GachaRequest: rewards don't fit, err: {err}");
+        response.error_code = ErrorCode::from(err).into();
+        return;
+    }
+
+    if let Err(err) = player.consume_items(&[ItemUsage {
+        id: gacha.item_id,
+        quantity: request.gacha_times,
+    }]) {
+        tracing::debug!("GachaRequest: failed to consume items, err: {err}");
+        response.error_code = ErrorCode::from(err).into();
+        return;
+    }
+
+    player.gacha.commit_pulls(gacha, pool, prepared);
+
+    for &role_id in new_roles.iter() {
+        player.add_role(role_id);
+    }
+
+    if !rewards.is_empty() {
+        if let Err(err) = player.add_items(&rewards) {
+            tracing::error!("GachaRequest: failed to add rewards, err: {err}");
+        }
+    }
+
+    if !new_roles.is_empty() {
+        player.notify(player.build_role_list_notify());
+    }
+
+    response.gacha_results = gacha_results;
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_gacha_use_pool_request(
+    player: &mut Player,
+    request: GachaUsePoolRequest,
+    response: &mut GachaUsePoolResponse,
+) {
+    response.error_code = match player.gacha.set_use_pool(request.gacha_id, request.pool_id) {
+        Ok(()) => ErrorCode::Success.into(),
+        Err(err) => {
+            tracing::debug!("GachaUsePoolRequest: failed to set pool, err: {err}");
+            ErrorCode::from(err).into()
+        }
+    };
+}
+
+pub fn on_gacha_pool_detail_request(
+    _: &mut Player,
+    request: GachaPoolDetailRequest,
+    response: &mut GachaPoolDetailResponse,
+) {
+    match PlayerGacha::build_pool_detail(request.pool_id) {
+        Ok(detail) => {
+            response.gacha_pool_detail = Some(detail);
+            response.error_code = ErrorCode::Success.into();
+        }
+        Err(err) => response.error_code = ErrorCode::from(err).into(),
+    }
+}
@@ -0,0 +1,62 @@
+use serde::Deserialize;
+use shorekeeper_protocol::{
+    ChatNotify, ErrorCode, GmEntityFsmGroupInfoRequest, GmEntityFsmGroupInfoResponse,
+    GmLevelActionRequest, GmLevelActionResponse,
+};
+
+use crate::logic::{components::Fsm, gm, player::Player, utils::fsm_util};
+
+#[derive(Deserialize)]
+struct GmLevelAction {
+    #[serde(rename = "Command")]
+    command: String,
+}
+
+pub fn on_gm_level_action_request(
+    player: &mut Player,
+    request: GmLevelActionRequest,
+    response: &mut GmLevelActionResponse,
+) {
+    // Either {"Command": "..."} or plain command line
+    let line = serde_json::from_str::<GmLevelAction>(&request.json_str)
+        .map(|action| action.command)
+        .unwrap_or(request.json_str);
+
+    let content = match gm::run(player, line.trim_start_matches('/')) {
+        Ok(content) => {
+            response.error_code = ErrorCode::Success.into();
+            content
+        }
+        Err(err) => {
+            tracing::debug!("GmLevelActionRequest: {err}");
+            let content = err.to_string();
+            response.error_code = ErrorCode::from(err).into();
+            content
+        }
+    };
+
+    player.notify(ChatNotify { content });
+}
+
+pub fn on_gm_entity_fsm_group_info_request(
+    player: &mut Player,
+    request: GmEntityFsmGroupInfoRequest,
+    response: &mut GmEntityFsmGroupInfoResponse,
+) {
+    response.entity_id = request.entity_id;
+    if !gm::is_allowed(player) {
+        tracing::warn!(
+            "GmEntityFsmGroupInfoRequest: player {} isn't allowed to use gm",
+            player.basic_info.id
+        );
+        return;
+    }
+
+    let world_ref = player.world.borrow();
+    response.gm_entity_fsm_machine_infos = fsm_util::read_fsm(
+        world_ref.get_world_entity(),
+        request.entity_id,
+        Fsm::build_gm_infos,
+    )
+    .unwrap_or_default();
+}
@@ -1,12 +1,111 @@
-use shorekeeper_protocol::{GuideInfoRequest, GuideInfoResponse};
+use shorekeeper_data::drop_package_data;
+use shorekeeper_protocol::{
+    ErrorCode, GuideFinishRequest, GuideFinishResponse, GuideInfoRequest, GuideInfoResponse,
+    GuideTriggerRequest, GuideTriggerResponse, TutorialInfoRequest, TutorialInfoResponse,
+    TutorialReceiveRequest, TutorialReceiveResponse, TutorialUnlockNotify, TutorialUnlockRequest,
+    TutorialUnlockResponse,
+};
 
 use crate::logic::player::Player;
 
 pub fn on_guide_info_request(
-    _player: &Player,
+    player: &Player,
     _request: GuideInfoRequest,
     response: &mut GuideInfoResponse,
 ) {
-    // TODO: Implement this
-    response.guide_group_finish_list = Vec::new();
+    response.guide_group_finish_list = player.guide.finished_group_ids().collect();
+}
+
+pub fn on_guide_trigger_request(
+    player: &mut Player,
+    request: GuideTriggerRequest,
+    response: &mut GuideTriggerResponse,
+) {
+    match player.guide.trigger_group(request.group_id) {
+        Ok(()) => response.error_code = ErrorCode::Success.into(),
+        Err(err) => {
+            tracing::debug!("GuideTriggerRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+        }
+    }
+}
+
+pub fn on_guide_finish_request(
+    player: &mut Player,
+    request: GuideFinishRequest,
+    response: &mut GuideFinishResponse,
+) {
+    match player.guide.finish_group(request.group_id) {
+        Ok(()) => response.error_code = ErrorCode::Success.into(),
+        Err(err) => {
+            tracing::debug!("GuideFinishRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+        }
+    }
+}
+
+pub fn on_tutorial_info_request(
+    player: &Player,
+    _request: TutorialInfoRequest,
+    response: &mut TutorialInfoResponse,
+) {
+    response.unlock_list = player.guide.build_tutorial_list();
+}
+
+pub fn on_tutorial_unlock_request(
+    player: &mut Player,
+    request: TutorialUnlockRequest,
+    response: &mut TutorialUnlockResponse,
+) {
+    match player.guide.unlock_tutorial(request.id) {
+        Ok(info) => {
+            player.notify(TutorialUnlockNotify {
+                unlock_list: vec![info],
+            });
+            response.un_lock_info = Some(info);
+            response.error_code = ErrorCode::Success.into();
+        }
+        Err(err) => {
+            tracing::debug!("TutorialUnlockRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+        }
+    }
+}
+
+pub fn on_tutorial_receive_request(
+    player: &mut Player,
+    request: TutorialReceiveRequest,
+    response: &mut TutorialReceiveResponse,
+) {
+    let data = match player.guide.check_receive_tutorial(request.id) {
+        Ok(data) => data,
+        Err(err) => {
+            tracing::debug!("TutorialReceiveRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    };
+
+    let Some(drop) = drop_package_data::iter().find(|drop| drop.id == data.drop_id) else {
+        tracing::warn!(
+            "TutorialReceiveRequest: drop package {} of tutorial {} doesn't exist",
+            data.drop_id,
+            data.id
+        );
+        response.error_code = ErrorCode::GuideTutorialAwardConfigNotFind.into();
+        return;
+    };
+
+    if let Err(err) = player.add_drop_package(drop) {
+        tracing::warn!(
+            "TutorialReceiveRequest: failed to grant drop {}, err: {err}",
+            drop.id
+        );
+        response.error_code = ErrorCode::GuideTutorialAwardError.into();
+        return;
+    }
+
+    player.guide.set_tutorial_received(request.id);
+    response.item_map = drop.drop_preview.clone();
+    response.error_code = ErrorCode::Success.into();
 }
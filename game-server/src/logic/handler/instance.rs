@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use common::time_util;
+use shorekeeper_data::{drop_package_data, instance_dungeon_data};
+use shorekeeper_protocol::{
+    ErrorCode, InstDetailEntry, InstEntranceDetailRequest, InstEntranceDetailResponse,
+    InstFightEndPush, InstResultNotify, InstSettleNotify, InstTimeoutNotify,
+    LeaveInstanceDungeonRequest, LeaveInstanceDungeonResponse, PEl2, PrewarTeamFightRequest,
+    PrewarTeamFightResponse, RewardItemInfo, SysErrorNotify,
+};
+
+use crate::logic::{
+    math::{Transform, Vector3f},
+    player::{Player, PlayerInstance, PlayerLocation},
+    utils::world_util,
+};
+
+pub fn on_prewar_team_fight_request(
+    player: &mut Player,
+    request: PrewarTeamFightRequest,
+    response: &mut PrewarTeamFightResponse,
+) {
+    let data = match player
+        .instance
+        .check_enter(request.instance_id, request.pos_entity_id)
+    {
+        Ok(data) => data,
+        Err(err) => {
+            tracing::debug!("PrewarTeamFightRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    };
+
+    let return_location = PlayerLocation::from_recover_location(&data.recover_world_location)
+        .unwrap_or_else(|| player.location.clone());
+
+    let notify = player
+        .instance
+        .enter(data, return_location, time_util::unix_timestamp());
+
+    world_util::change_scene(
+        player,
+        PlayerLocation {
+            instance_id: data.id,
+            position: Transform {
+                position: Vector3f::from_data(&data.born_position),
+                rotation: Vector3f::from_data(&data.born_rotation),
+            },
+        },
+    );
+
+    player.notify(notify);
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_inst_entrance_detail_request(
+    player: &mut Player,
+    request: InstEntranceDetailRequest,
+    response: &mut InstEntranceDetailResponse,
+) {
+    response.entrance_id = request.entrance_id;
+    response.inst_detail_entry_list = instance_dungeon_data::iter()
+        .filter(|data| {
+            data.entrance_entities
+                .iter()
+                .any(|entrance| entrance.dungeon_id == request.entrance_id)
+        })
+        .map(|data| InstDetailEntry {
+            inst_id: data.id,
+            reset_time: 0,
+            count: player.instance.challenged_times(data.id),
+        })
+        .collect();
+
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_leave_instance_dungeon_request(
+    player: &mut Player,
+    request: LeaveInstanceDungeonRequest,
+    response: &mut LeaveInstanceDungeonResponse,
+) {
+    if let Err(err) = player.instance.check_leave(request.pos_entity_id) {
+        tracing::debug!("LeaveInstanceDungeonRequest: {err}");
+        response.error_code = ErrorCode::from(err).into();
+        return;
+    }
+
+    leave_instance(player);
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_inst_fight_end_push(player: &mut Player, push: InstFightEndPush) {
+    let now = time_util::unix_timestamp();
+    let first_clear = !player.instance.is_cleared(push.inst_id);
+    let drop_ids = match player
+        .instance
+        .check_settle(push.inst_id, push.cost_time, now)
+    {
+        Ok(drop_ids) => drop_ids,
+        Err(err) => {
+            tracing::debug!("InstFightEndPush: {err}");
+            return;
+        }
+    };
+
+    // Rewards of repeated clears cost energy, run stays unsettled if there's not enough of it
+    let now = now as i64;
+    let energy_cost =
+        PlayerInstance::energy_cost(push.inst_id).filter(|_| !first_clear && !drop_ids.is_empty());
+    if let Some(energy_cost) = energy_cost {
+        if let Err(err) =
+            player
+                .resource
+                .check_consume(energy_cost.energy_type, energy_cost.cost, now)
+        {
+            tracing::debug!(
+                "InstFightEndPush: instance {} not settled, err: {err}",
+                push.inst_id
+            );
+            player.notify(SysErrorNotify {
+                error_code: ErrorCode::from(err).into(),
+                error_params: Vec::new(),
+            });
+            return;
+        }
+    }
+
+    player.instance.settle(push.inst_id);
+    let consumed_energy = energy_cost.filter(|energy_cost| {
+        player
+            .resource
+            .consume(energy_cost.energy_type, energy_cost.cost, now)
+            .is_ok()
+    });
+
+    let mut reward_items = HashMap::new();
+    for drop_id in drop_ids {
+        let Some(drop) = drop_package_data::iter().find(|data| data.id == drop_id) else {
+            tracing::warn!("InstFightEndPush: drop package {drop_id} doesn't exist");
+            continue;
+        };
+
+        if let Err(err) = player.add_drop_package(drop) {
+            tracing::warn!("InstFightEndPush: failed to grant drop {drop_id}, err: {err}");
+            continue;
+        }
+
+        reward_items.insert(
+            drop_id,
+            PEl2 {
+                item_list: drop
+                    .drop_preview
+                    .iter()
+                    .map(|(&item_id, &count)| RewardItemInfo {
+                        item_id,
+                        count,
+                        ..Default::default()
+                    })
+                    .collect(),
+            },
+        );
+    }
+
+    // Nothing was granted, energy shouldn't be lost either
+    if let Some(energy_cost) = consumed_energy {
+        match reward_items.is_empty() {
+            true => player
+                .resource
+                .refund(energy_cost.energy_type, energy_cost.cost, now),
+            false => player.add_exp(energy_cost.union_exp),
+        }
+        player.notify_energy_update(&[energy_cost.energy_type]);
+    }
+
+    player.notify(InstSettleNotify {
+        is_success: true,
+        reward_items,
+        ..Default::default()
+    });
+
+    player.notify(InstResultNotify {
+        id: push.inst_id,
+        succ: true,
+        ..Default::default()
+    });
+}
+
+// Called from logic thread once instance time limit is exceeded
+pub fn on_instance_timeout(player: &mut Player) {
+    let Some(instance_id) = player.instance.current().map(|current| current.id) else {
+        return;
+    };
+
+    player.notify(InstTimeoutNotify {
+        timestamp: time_util::unix_timestamp() as i64,
+    });
+
+    player.notify(InstResultNotify {
+        id: instance_id,
+        succ: false,
+        ..Default::default()
+    });
+
+    leave_instance(player);
+}
+
+fn leave_instance(player: &mut Player) {
+    if let Some(location) = player.instance.leave() {
+        world_util::change_scene(player, location);
+    }
+}
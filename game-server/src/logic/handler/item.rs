@@ -0,0 +1,189 @@
+use common::time_util;
+use shorekeeper_protocol::{
+    BuffItemUpdateNotify, ErrorCode, ItemDestructRequest, ItemDestructResponse,
+    ItemFuncValueUpdateNotify, ItemLockRequest, ItemLockResponse, ItemUseRequest, ItemUseResponse,
+    NormalItemRequest, NormalItemResponse, PhantomItemRequest, PhantomItemResponse,
+    WeaponItemRequest, WeaponItemResponse,
+};
+
+use crate::logic::player::{ItemUsage, Player, PlayerResource};
+use crate::logic::role::Role;
+use crate::logic::utils::buff_util;
+
+// Upper bound of items used by a single request
+const MAX_USE_COUNT: i32 = 999;
+
+pub fn on_normal_item_request(
+    player: &Player,
+    _: NormalItemRequest,
+    response: &mut NormalItemResponse,
+) {
+    response.normal_item_list = player.inventory.build_normal_item_list();
+}
+
+pub fn on_weapon_item_request(
+    player: &Player,
+    _: WeaponItemRequest,
+    response: &mut WeaponItemResponse,
+) {
+    response.weapon_item_list = player.inventory.build_weapon_item_list();
+}
+
+pub fn on_phantom_item_request(
+    player: &Player,
+    _: PhantomItemRequest,
+    response: &mut PhantomItemResponse,
+) {
+    response.phantom_item_list = player.inventory.build_phantom_item_list();
+    response.equip_info_list = player
+        .role_list
+        .values()
+        .filter(|role| !role.phantom_map.is_empty())
+        .map(Role::build_phantom_equip_info)
+        .collect();
+    response.max_cost = Role::PHANTOM_MAX_COST;
+}
+
+pub fn on_item_lock_request(
+    player: &mut Player,
+    request: ItemLockRequest,
+    response: &mut ItemLockResponse,
+) {
+    match player
+        .inventory
+        .toggle_lock(request.item_id, request.incr_id)
+    {
+        Ok(func_value) => {
+            player.notify(ItemFuncValueUpdateNotify {
+                incr_id: request.incr_id,
+                func_value,
+            });
+            response.error_code = ErrorCode::Success.into();
+        }
+        Err(err) => {
+            tracing::debug!("ItemLockRequest: failed to toggle lock, err: {err}");
+            response.error_code = ErrorCode::from(err).into();
+        }
+    }
+}
+
+pub fn on_item_use_request(
+    player: &mut Player,
+    request: ItemUseRequest,
+    response: &mut ItemUseResponse,
+) {
+    if request.count <= 0 {
+        response.error_code = ErrorCode::ErrItemInvalidParams.into();
+        return;
+    }
+
+    if request.count > MAX_USE_COUNT {
+        response.error_code = ErrorCode::ErrItemMaxUseCount.into();
+        return;
+    }
+
+    if player.inventory.get_item_count(request.item_id) < request.count {
+        response.error_code = ErrorCode::ErrItemNotEnough.into();
+        return;
+    }
+
+    if let Some((energy_type, amount)) = PlayerResource::refill_of(request.item_id, request.count) {
+        let now = time_util::unix_timestamp() as i64;
+        if let Err(err) = player.resource.check_add(energy_type, amount, now) {
+            tracing::debug!("ItemUseRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+
+        if let Err(err) = player.consume_items(&[ItemUsage {
+            id: request.item_id,
+            quantity: request.count,
+        }]) {
+            tracing::debug!("ItemUseRequest: failed to consume refill item, err: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+
+        if let Err(err) = player.resource.add(energy_type, amount, now) {
+            tracing::warn!("ItemUseRequest: failed to add energy after consuming item, err: {err}");
+        }
+
+        player.notify_energy_update(&[energy_type]);
+        response.error_code = ErrorCode::Success.into();
+        return;
+    }
+
+    if buff_util::buff_item(request.item_id).is_some() {
+        let cur_role_id = player
+            .formation_list
+            .get(&player.cur_formation_id)
+            .map(|f| f.cur_role)
+            .unwrap_or_default();
+
+        match buff_util::use_buff_item(player, request.item_id, request.count, cur_role_id) {
+            Ok(buff_item) => {
+                player.notify(BuffItemUpdateNotify {
+                    buff_item: Some(buff_item),
+                });
+                response.error_code = ErrorCode::Success.into();
+            }
+            Err(error_code) => {
+                tracing::debug!(
+                    "ItemUseRequest: failed to use buff item {}",
+                    request.item_id
+                );
+                response.error_code = error_code.into();
+            }
+        }
+        return;
+    }
+
+    // Use effects are data driven, items that have no entry in EnergyItem or BuffItem
+    // (materials, exp items and such) are only consumed by their own systems
+    tracing::debug!(
+        "ItemUseRequest: item with id {} can't be used directly",
+        request.item_id
+    );
+    response.error_code = ErrorCode::ErrItemUseFail.into();
+}
+
+pub fn on_item_destruct_request(
+    player: &mut Player,
+    request: ItemDestructRequest,
+    response: &mut ItemDestructResponse,
+) {
+    let mut usages = Vec::new();
+    let mut weapon_incr_ids = Vec::new();
+    let mut phantom_incr_ids = Vec::new();
+
+    for item in request.item_list {
+        if item.incr_id == 0 {
+            usages.push(ItemUsage {
+                id: item.item_id,
+                quantity: item.count,
+            });
+        } else if player
+            .inventory
+            .get_weapon(item.incr_id)
+            .is_some_and(|w| w.id == item.item_id)
+        {
+            weapon_incr_ids.push(item.incr_id);
+        } else {
+            phantom_incr_ids.push(item.incr_id);
+        }
+    }
+
+    match player
+        .inventory
+        .destruct_items(&usages, &weapon_incr_ids, &phantom_incr_ids)
+    {
+        Ok(change) => {
+            player.notify_inventory_change(&change);
+            response.error_code = ErrorCode::Success.into();
+        }
+        Err(err) => {
+            tracing::debug!("ItemDestructRequest: failed to destruct items, err: {err}");
+            response.error_code = ErrorCode::from(err).into();
+        }
+    }
+}
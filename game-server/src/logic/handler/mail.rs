@@ -1,6 +1,12 @@
-use shorekeeper_protocol::{MailBind, MailBindInfoRequest, MailBindInfoResponse};
+use shorekeeper_protocol::{
+    ErrorCode, MailBind, MailBindInfoRequest, MailBindInfoResponse, MailDeleteRequest,
+    MailDeleteResponse, MailGetAttachmentRequest, MailGetAttachmentResponse, MailReadRequest,
+    MailReadResponse,
+};
 
-use crate::logic::player::Player;
+use crate::logic::player::{ItemUsage, Player};
+use crate::mail_task::{self, MailUpdate};
+use crate::player_save_task::{self, PlayerSaveReason};
 
 pub fn on_mail_bind_info_request(
     _: &Player,
@@ -14,3 +20,130 @@ pub fn on_mail_bind_info_request(
         close_time: -1,
     });
 }
+
+pub fn on_mail_read_request(
+    player: &mut Player,
+    request: MailReadRequest,
+    response: &mut MailReadResponse,
+) {
+    response.id = request.id.clone();
+
+    let mail_id = match player.mail.get(&request.id) {
+        Ok(mail) => mail.id,
+        Err(err) => {
+            tracing::debug!("MailReadRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    };
+
+    if player.mail.read(mail_id) {
+        let read_time = player.mail.get(&request.id).unwrap().read_time;
+        mail_task::push(
+            player.basic_info.id,
+            MailUpdate::Read { mail_id, read_time },
+        );
+    }
+
+    let mail = player.mail.get(&request.id).unwrap();
+    response.state = mail.state();
+    response.read_time = mail.read_time;
+    response.expiry_time = mail.expire_time;
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_mail_get_attachment_request(
+    player: &mut Player,
+    request: MailGetAttachmentRequest,
+    response: &mut MailGetAttachmentResponse,
+) {
+    let mut last_error = ErrorCode::ErrNoMailCanGet;
+
+    for id in request.mail_ids.iter() {
+        let (mail_id, attachments) = match player.mail.check_claim(id) {
+            Ok(mail) => (
+                mail.id,
+                mail.attachments
+                    .iter()
+                    .map(|item| ItemUsage {
+                        id: item.id,
+                        quantity: item.quantity,
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            Err(err) => {
+                tracing::debug!("MailGetAttachmentRequest: {err}");
+                last_error = err.into();
+                continue;
+            }
+        };
+
+        // Items of a single mail are granted all at once or not at all
+        if let Err(err) = player.add_items(&attachments) {
+            tracing::debug!("MailGetAttachmentRequest: failed to add items, err: {err}");
+            last_error = err.into();
+            continue;
+        }
+
+        if player.mail.read(mail_id) {
+            let read_time = player.mail.get(id).unwrap().read_time;
+            mail_task::push(
+                player.basic_info.id,
+                MailUpdate::Read { mail_id, read_time },
+            );
+        }
+
+        player.mail.set_claimed(mail_id);
+        response
+            .success_id_map
+            .insert(id.clone(), player.mail.get(id).unwrap().state());
+    }
+
+    if response.success_id_map.is_empty() {
+        response.error_code = last_error.into();
+        return;
+    }
+
+    // Claims are persisted in the same transaction as the granted items
+    player_save_task::push(
+        player.basic_info.id,
+        player.build_save_data(),
+        player.mail.take_unsaved_claims(),
+        PlayerSaveReason::MailClaimed,
+    );
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_mail_delete_request(
+    player: &mut Player,
+    request: MailDeleteRequest,
+    response: &mut MailDeleteResponse,
+) {
+    let mut last_error = ErrorCode::ErrNoMailCanDelete;
+    let mut mail_ids = Vec::with_capacity(request.mail_ids.len());
+
+    for id in request.mail_ids.iter() {
+        match player.mail.check_delete(id) {
+            Ok(mail) => {
+                mail_ids.push(mail.id);
+                response.success_ids.push(id.clone());
+            }
+            Err(err) => {
+                tracing::debug!("MailDeleteRequest: {err}");
+                last_error = err.into();
+            }
+        }
+    }
+
+    if mail_ids.is_empty() {
+        response.error_code = last_error.into();
+        return;
+    }
+
+    for &mail_id in mail_ids.iter() {
+        player.mail.remove(mail_id);
+    }
+
+    mail_task::push(player.basic_info.id, MailUpdate::Delete { mail_ids });
+    response.error_code = ErrorCode::Success.into();
+}
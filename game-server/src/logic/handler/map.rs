@@ -0,0 +1,134 @@
+use shorekeeper_protocol::{
+    ErrorCode, MapCancelTraceRequest, MapCancelTraceResponse, MapMarkInfoRequest,
+    MapMarkInfoResponse, MapMarkRequest, MapMarkResponse, MapReplaceMarkRequest,
+    MapReplaceMarkResponse, MapTraceInfoRequest, MapTraceInfoResponse, MapTraceRequest,
+    MapTraceResponse, MapUnlockFieldInfoRequest, MapUnlockFieldInfoResponse, RemoveMapMarkRequest,
+    RemoveMapMarkResponse, TrackMapMarkAddNotify, TrackMapMarkRemoveNotify,
+};
+
+use crate::logic::player::Player;
+
+pub fn on_map_mark_info_request(
+    player: &Player,
+    _request: MapMarkInfoRequest,
+    response: &mut MapMarkInfoResponse,
+) {
+    response.info_list = player.map.build_mark_info_list();
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_map_mark_request(
+    player: &mut Player,
+    request: MapMarkRequest,
+    response: &mut MapMarkResponse,
+) {
+    let Some(mark_point) = request.mark_point_request_info else {
+        response.error_code = ErrorCode::ErrMapMarkConfigIdNotExist.into();
+        return;
+    };
+
+    match player.map.add_mark(&mark_point) {
+        Ok(info) => {
+            if info.is_trace != 0 {
+                player.notify(TrackMapMarkAddNotify {
+                    mark_id: info.mark_id,
+                });
+            }
+            response.info = Some(info);
+            response.error_code = ErrorCode::Success.into();
+        }
+        Err(err) => {
+            tracing::debug!("MapMarkRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+        }
+    }
+}
+
+pub fn on_remove_map_mark_request(
+    player: &mut Player,
+    request: RemoveMapMarkRequest,
+    response: &mut RemoveMapMarkResponse,
+) {
+    response.mark_list = player.map.remove_marks(&request.mark_list);
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_map_replace_mark_request(
+    player: &mut Player,
+    request: MapReplaceMarkRequest,
+    response: &mut MapReplaceMarkResponse,
+) {
+    response.mark_id = request.mark_id;
+    response.config_id = request.config_id;
+
+    match player.map.replace_mark(request.mark_id, request.config_id) {
+        Ok(()) => response.error_code = ErrorCode::Success.into(),
+        Err(err) => {
+            tracing::debug!("MapReplaceMarkRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+        }
+    }
+}
+
+pub fn on_map_trace_request(
+    player: &mut Player,
+    request: MapTraceRequest,
+    response: &mut MapTraceResponse,
+) {
+    response.mark_id = request.mark_id;
+
+    match player.map.trace(request.mark_id) {
+        Ok(previous) => {
+            if let Some(mark_id) = previous {
+                player.notify(TrackMapMarkRemoveNotify { mark_id });
+            }
+            player.notify(TrackMapMarkAddNotify {
+                mark_id: request.mark_id,
+            });
+            response.error_code = ErrorCode::Success.into();
+        }
+        Err(err) => {
+            tracing::debug!("MapTraceRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+        }
+    }
+}
+
+pub fn on_map_cancel_trace_request(
+    player: &mut Player,
+    request: MapCancelTraceRequest,
+    response: &mut MapCancelTraceResponse,
+) {
+    response.mark_id = request.mark_id;
+
+    match player.map.cancel_trace(request.mark_id) {
+        Ok(()) => {
+            player.notify(TrackMapMarkRemoveNotify {
+                mark_id: request.mark_id,
+            });
+            response.error_code = ErrorCode::Success.into();
+        }
+        Err(err) => {
+            tracing::debug!("MapCancelTraceRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+        }
+    }
+}
+
+pub fn on_map_trace_info_request(
+    player: &Player,
+    _request: MapTraceInfoRequest,
+    response: &mut MapTraceInfoResponse,
+) {
+    response.mark_id_list = player.map.traced_mark_ids();
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_map_unlock_field_info_request(
+    player: &Player,
+    _request: MapUnlockFieldInfoRequest,
+    response: &mut MapUnlockFieldInfoResponse,
+) {
+    response.field_id = player.map.unlocked_area_ids();
+    response.error_code = ErrorCode::Success.into();
+}
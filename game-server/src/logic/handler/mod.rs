@@ -1,20 +1,56 @@
+mod achievement;
+mod buff;
+mod calabash;
+mod chat;
 mod combat;
+mod crafting;
 mod entity;
+mod friend;
+mod gacha;
+mod gm;
 mod guide;
+mod instance;
+mod item;
 mod mail;
+mod map;
 mod misc;
+mod phantom;
+mod quest;
+mod resource;
 mod role;
 mod scene;
+mod shop;
 mod skill;
+mod teleport;
+mod weapon;
+mod world_level;
 
+pub use achievement::*;
+pub use buff::*;
+pub use calabash::*;
+pub use chat::*;
 pub use combat::*;
+pub use crafting::*;
 pub use entity::*;
+pub use friend::*;
+pub use gacha::*;
+pub use gm::*;
 pub use guide::*;
+pub use instance::*;
+pub use item::*;
 pub use mail::*;
+pub use map::*;
 pub use misc::*;
+pub use phantom::*;
+pub use quest::*;
+pub use resource::*;
 pub use role::*;
 pub use scene::*;
+pub use shop::*;
 pub use skill::*;
+pub use teleport::*;
+pub use weapon::*;
+pub use world_level::*;
 
 use shorekeeper_protocol::message::Message;
 
@@ -88,8 +124,45 @@ macro_rules! handle_push {
 }
 
 handle_request! {
+    // Achievement
+    AchievementInfo;
+    UpdateAchievementInfo;
+    AchievementFinish;
+    AchievementReceive;
+
+    // Buff
+    BuffItem;
+    BuffEffectExecute;
+
+    // Calabash
+    CalabashMsg;
+    CalabashLevelReward;
+    CalabashBatchRefining;
+
+    // Chat
+    Chat;
+    PrivateChat;
+    PrivateChatHistory;
+    PrivateChatData;
+    ChatMutePlayer;
+
     // Combat
     CombatSendPack, combat_message;
+    Revive;
+    AiControlSwitch, ai;
+
+    // Crafting
+    SynthesisInfo;
+    SynthesisItem;
+    SynthesisFormulaUnlock;
+    SynthesisLevelReward;
+    ForgeInfo;
+    ForgeItem;
+    ForgeFormulaUnlock;
+    SendCookingData;
+    SendCookFormula;
+    SendCookFood;
+    SendCertificateLevelReward;
 
     // Role
     RoleShowListUpdate;
@@ -97,6 +170,13 @@ handle_request! {
     RoleFavorList;
     FormationAttr;
     UpdateFormation;
+    RoleLevelUpView;
+    PbUpLevelRole;
+    RoleBreakThroughView;
+    PbOverRole;
+    RoleSkillLevelUpView;
+    PbUpLevelSkill;
+    RoleActivateSkill;
 
     // Entity
     EntityActive;
@@ -104,11 +184,62 @@ handle_request! {
     EntityPosition;
     EntityLoadComplete;
 
+    // Friend
+    FriendAll;
+    FriendApplySend;
+    FriendApplyHandle;
+    FriendDelete;
+    FriendRemark;
+    BlockList;
+    BlockPlayer;
+    UnblockPlayer;
+
+    // Gacha
+    GachaInfo;
+    Gacha;
+    GachaUsePool;
+    GachaPoolDetail;
+
+    // Gm
+    GmLevelAction;
+    GmEntityFsmGroupInfo;
+
     // Guide
     GuideInfo;
+    GuideTrigger;
+    GuideFinish;
+    TutorialInfo;
+    TutorialUnlock;
+    TutorialReceive;
+
+    // Instance
+    PrewarTeamFight;
+    InstEntranceDetail;
+    LeaveInstanceDungeon;
+
+    // Item
+    NormalItem;
+    WeaponItem;
+    PhantomItem;
+    ItemLock;
+    ItemUse;
+    ItemDestruct;
 
     // Mail
     MailBindInfo;
+    MailRead;
+    MailGetAttachment;
+    MailDelete;
+
+    // Map
+    MapMarkInfo;
+    MapMark;
+    RemoveMapMark;
+    MapReplaceMark;
+    MapTrace;
+    MapCancelTrace;
+    MapTraceInfo;
+    MapUnlockFieldInfo;
 
     // Misc
     InputSetting;
@@ -116,19 +247,62 @@ handle_request! {
     LanguageSettingUpdate;
     ServerPlayStationPlayOnlyState;
 
+    // Phantom
+    PhantomLevelUp;
+    PhantomPutOn;
+
+    // Quest
+    QuestAction;
+    QuestFinishAction;
+    QuestNpcMoveOver;
+    RoleQuestAccept;
+    TraceQuest;
+
+    // Resource
+    EnergySync;
+
     // Scene
     SceneTrace;
     SceneLoadingFinish;
     UpdateSceneDate;
 
+    // Shop
+    ShopInfo;
+    ShopUpdate;
+    ShopBuy;
+    PayShopInfo;
+    PayShopUpdate;
+    PayShopItemUpdate;
+    PayShopBuy;
+    PayShopDirectBuy;
+
     // Skill
     VisionExploreSkillSet;
+
+    // Teleport
+    TeleportData;
+    UnlockTeleport;
+    TeleportTransfer;
+    TeleportFinish;
+
+    // Weapon
+    WeaponLevelUp;
+    WeaponBreach;
+    WeaponResonUp;
+    EquipTakeOn;
+
+    // World level
+    WorldLevelDown;
+    WorldLevelRegain;
 }
 
 handle_push! {
     // Entity
     MovePackage;
 
+    // Instance
+    InstFightEnd;
+
     // Misc
     VersionInfo;
 }
@@ -0,0 +1,249 @@
+use shorekeeper_data::phantom_exp_item_data;
+use shorekeeper_protocol::{
+    ErrorCode, PhantomLevelUpRequest, PhantomLevelUpResponse, PhantomPutOnNotify,
+    PhantomPutOnRequest, PhantomPutOnResponse,
+};
+
+use crate::logic::player::{ItemUsage, Player, PlayerInventory};
+use crate::logic::role::Role;
+
+use super::update_role_props;
+
+pub fn on_phantom_put_on_request(
+    player: &mut Player,
+    request: PhantomPutOnRequest,
+    response: &mut PhantomPutOnResponse,
+) {
+    let Some(role) = player.role_list.get(&request.role_id) else {
+        response.error_code = ErrorCode::ErrRoleNotActive.into();
+        return;
+    };
+
+    if !(0..Role::PHANTOM_SLOT_COUNT).contains(&request.pos) {
+        response.error_code = ErrorCode::ErrPhantomInvalidPos.into();
+        return;
+    }
+
+    let occupant = role.phantom_map.get(&request.pos).copied();
+
+    // Taking phantom off
+    if request.inc_id == 0 {
+        let Some(occupant) = occupant else {
+            response.error_code = ErrorCode::ErrPhantomCannotTakeOff.into();
+            return;
+        };
+
+        player
+            .role_list
+            .get_mut(&request.role_id)
+            .unwrap()
+            .phantom_map
+            .remove(&request.pos);
+        player.inventory.get_phantom_mut(occupant).unwrap().role_id = 0;
+
+        finish_put_on(player, &[request.role_id], response);
+        return;
+    }
+
+    let Some(phantom) = player.inventory.get_phantom(request.inc_id) else {
+        response.error_code = ErrorCode::ErrPhantomItemNotExist.into();
+        return;
+    };
+
+    let prev_owner = phantom.role_id;
+    let prev_pos = player
+        .role_list
+        .get(&prev_owner)
+        .and_then(|r| r.phantom_map.iter().find(|(_, &id)| id == request.inc_id))
+        .map(|(&pos, _)| pos);
+
+    if prev_owner == request.role_id && prev_pos == Some(request.pos) {
+        finish_put_on(player, &[request.role_id], response);
+        return;
+    }
+
+    // Cost of the target role with the new phantom in place of the occupant
+    let target_cost = role
+        .phantom_map
+        .iter()
+        .filter(|(&pos, &id)| pos != request.pos && id != request.inc_id)
+        .map(|(_, &id)| phantom_cost(&player.inventory, id))
+        .sum::<i32>()
+        + phantom.cost();
+
+    if target_cost > Role::PHANTOM_MAX_COST {
+        response.error_code = ErrorCode::ErrPhantomEquipTargetCost.into();
+        return;
+    }
+
+    // Occupant is swapped onto the previous owner of the new phantom
+    if let (Some(prev_role), Some(occupant)) = (player.role_list.get(&prev_owner), occupant) {
+        let source_cost = prev_role
+            .phantom_map
+            .values()
+            .map(|&id| phantom_cost(&player.inventory, id))
+            .sum::<i32>()
+            - phantom.cost()
+            + phantom_cost(&player.inventory, occupant);
+
+        if prev_owner != request.role_id && source_cost > Role::PHANTOM_MAX_COST {
+            response.error_code = ErrorCode::ErrPhantomEquipSourceCost.into();
+            return;
+        }
+    }
+
+    if let (Some(prev_role), Some(prev_pos)) = (player.role_list.get_mut(&prev_owner), prev_pos) {
+        prev_role.phantom_map.remove(&prev_pos);
+    }
+
+    if let Some(occupant) = occupant {
+        let occupant_owner = match (player.role_list.get_mut(&prev_owner), prev_pos) {
+            (Some(prev_role), Some(prev_pos)) => {
+                prev_role.phantom_map.insert(prev_pos, occupant);
+                prev_owner
+            }
+            _ => 0,
+        };
+
+        player.inventory.get_phantom_mut(occupant).unwrap().role_id = occupant_owner;
+    }
+
+    player
+        .role_list
+        .get_mut(&request.role_id)
+        .unwrap()
+        .phantom_map
+        .insert(request.pos, request.inc_id);
+    player
+        .inventory
+        .get_phantom_mut(request.inc_id)
+        .unwrap()
+        .role_id = request.role_id;
+
+    if prev_owner != 0 && prev_owner != request.role_id {
+        finish_put_on(player, &[request.role_id, prev_owner], response);
+    } else {
+        finish_put_on(player, &[request.role_id], response);
+    }
+}
+
+pub fn on_phantom_level_up_request(
+    player: &mut Player,
+    request: PhantomLevelUpRequest,
+    response: &mut PhantomLevelUpResponse,
+) {
+    let Some(phantom) = player.inventory.get_phantom(request.inc_id) else {
+        response.error_code = ErrorCode::ErrPhantomItemNotExist.into();
+        return;
+    };
+
+    if phantom.level >= phantom.level_limit() {
+        response.error_code = ErrorCode::ErrPhantomLvupMax.into();
+        return;
+    }
+
+    if request.consume_list.is_empty() {
+        response.error_code = ErrorCode::ErrPhantomLvupNoItem.into();
+        return;
+    }
+
+    let mut usages = Vec::with_capacity(request.consume_list.len() + 1);
+    let mut phantom_incr_ids = Vec::new();
+    let mut exp = 0i32;
+    let mut gold = 0i32;
+
+    for item in request.consume_list.iter() {
+        if item.inc_id != 0 {
+            if item.inc_id == request.inc_id {
+                response.error_code = ErrorCode::ErrPhantomLevelUpConsumeItemErr.into();
+                return;
+            }
+
+            let Some(fodder) = player.inventory.get_phantom(item.inc_id) else {
+                response.error_code = ErrorCode::ErrPhantomItemNotExist.into();
+                return;
+            };
+
+            exp = exp.saturating_add(fodder.fodder_exp());
+            phantom_incr_ids.push(item.inc_id);
+            continue;
+        }
+
+        if item.count <= 0 {
+            response.error_code = ErrorCode::ErrPhantomLevelUpConsumeItemNotEnough.into();
+            return;
+        }
+
+        if usages.iter().any(|u: &ItemUsage| u.id == item.item_id) {
+            response.error_code = ErrorCode::ErrPhantomLevelUpRepeatItem.into();
+            return;
+        }
+
+        let Some(exp_item) = phantom_exp_item_data::iter().find(|d| d.id == item.item_id) else {
+            response.error_code = ErrorCode::ErrPhantomExpItemNotExist.into();
+            return;
+        };
+
+        exp = exp.saturating_add(exp_item.basic_exp.saturating_mul(item.count));
+        gold = gold.saturating_add(exp_item.cost.saturating_mul(item.count));
+        usages.push(ItemUsage {
+            id: item.item_id,
+            quantity: item.count,
+        });
+    }
+
+    if gold > 0 {
+        usages.push(ItemUsage {
+            id: PlayerInventory::SHELL_CREDIT_ID,
+            quantity: gold,
+        });
+    }
+
+    match player
+        .inventory
+        .consume_with_phantoms(&usages, &phantom_incr_ids)
+    {
+        Ok(change) => player.notify_inventory_change(&change),
+        Err(err) => {
+            tracing::debug!("PhantomLevelUpRequest: failed to consume items, err: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    }
+
+    let phantom = player.inventory.get_phantom_mut(request.inc_id).unwrap();
+    phantom.add_exp(exp);
+
+    response.update_info = Some(phantom.to_protobuf());
+    response.error_code = ErrorCode::Success.into();
+
+    let role_id = phantom.role_id;
+    if role_id != 0 {
+        update_role_props(player, role_id);
+    }
+}
+
+fn phantom_cost(inventory: &PlayerInventory, incr_id: i32) -> i32 {
+    inventory
+        .get_phantom(incr_id)
+        .map(|phantom| phantom.cost())
+        .unwrap_or_default()
+}
+
+fn finish_put_on(player: &Player, role_ids: &[i32], response: &mut PhantomPutOnResponse) {
+    response.equip_info_list = role_ids
+        .iter()
+        .filter_map(|role_id| player.role_list.get(role_id))
+        .map(Role::build_phantom_equip_info)
+        .collect();
+
+    player.notify(PhantomPutOnNotify {
+        equip_info_list: response.equip_info_list.clone(),
+    });
+
+    for &role_id in role_ids {
+        update_role_props(player, role_id);
+    }
+
+    response.error_code = ErrorCode::Success.into();
+}
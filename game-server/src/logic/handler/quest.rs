@@ -0,0 +1,81 @@
+use shorekeeper_protocol::{
+    ErrorCode, QuestActionRequest, QuestActionResponse, QuestFinishActionRequest,
+    QuestFinishActionResponse, QuestNpcMoveOverRequest, QuestNpcMoveOverResponse,
+    RoleQuestAcceptRequest, RoleQuestAcceptResponse, TraceQuestNotify, TraceQuestRequest,
+    TraceQuestResponse,
+};
+
+use crate::logic::{player::Player, utils::quest_util};
+
+// Client reports that conditions of the quest node are fulfilled
+pub fn on_quest_action_request(
+    player: &mut Player,
+    request: QuestActionRequest,
+    response: &mut QuestActionResponse,
+) {
+    match quest_util::finish_node(player, request.quest_id, request.node_id) {
+        Ok(()) => response.error_id = ErrorCode::Success.into(),
+        Err(err) => {
+            tracing::debug!("QuestActionRequest: {err}");
+            response.error_id = ErrorCode::from(err).into();
+        }
+    }
+}
+
+// Finish actions are executed once the quest tree is complete,
+// client only reports that it has played them
+pub fn on_quest_finish_action_request(
+    player: &mut Player,
+    request: QuestFinishActionRequest,
+    response: &mut QuestFinishActionResponse,
+) {
+    match player.quest.check_finished(request.quest_id) {
+        Ok(()) => response.error_id = ErrorCode::Success.into(),
+        Err(err) => {
+            tracing::debug!("QuestFinishActionRequest: {err}");
+            response.error_id = ErrorCode::from(err).into();
+        }
+    }
+}
+
+pub fn on_quest_npc_move_over_request(
+    _player: &Player,
+    request: QuestNpcMoveOverRequest,
+    response: &mut QuestNpcMoveOverResponse,
+) {
+    tracing::debug!("QuestNpcMoveOverRequest: entity {}", request.entity_id);
+    response.error_id = ErrorCode::Success.into();
+}
+
+pub fn on_role_quest_accept_request(
+    player: &mut Player,
+    request: RoleQuestAcceptRequest,
+    response: &mut RoleQuestAcceptResponse,
+) {
+    match quest_util::accept_quest(player, request.quest_id) {
+        Ok(()) => response.error_id = ErrorCode::Success.into(),
+        Err(err) => {
+            tracing::debug!("RoleQuestAcceptRequest: {err}");
+            response.error_id = ErrorCode::from(err).into();
+        }
+    }
+}
+
+pub fn on_trace_quest_request(
+    player: &mut Player,
+    request: TraceQuestRequest,
+    response: &mut TraceQuestResponse,
+) {
+    match player.quest.trace(request.quest_id) {
+        Ok(()) => {
+            player.notify(TraceQuestNotify {
+                quest_id: request.quest_id,
+            });
+            response.error_id = ErrorCode::Success.into();
+        }
+        Err(err) => {
+            tracing::debug!("TraceQuestRequest: {err}");
+            response.error_id = ErrorCode::from(err).into();
+        }
+    }
+}
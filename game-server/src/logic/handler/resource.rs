@@ -0,0 +1,15 @@
+use common::time_util;
+use shorekeeper_protocol::{EnergySyncRequest, EnergySyncResponse, ErrorCode};
+
+use crate::logic::player::Player;
+
+pub fn on_energy_sync_request(
+    player: &Player,
+    request: EnergySyncRequest,
+    response: &mut EnergySyncResponse,
+) {
+    response.sync_info = player
+        .resource
+        .build_energy_info_list(&request.energy_types, time_util::unix_timestamp() as i64);
+    response.error_code = ErrorCode::Success.into();
+}
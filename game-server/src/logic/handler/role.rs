@@ -1,10 +1,17 @@
-use crate::logic::player::Player;
-use crate::logic::role::{Role, RoleFormation};
+use crate::logic::ecs::component::ComponentContainer;
+use crate::logic::player::{ItemUsage, Player};
+use crate::logic::role::{Role, RoleFormation, RoleSkillNode};
+use crate::query_components;
 use shorekeeper_protocol::{
-    ClientCurrentRoleReportRequest, ClientCurrentRoleReportResponse, ERemoveEntityType, ErrorCode,
-    FormationAttrRequest, FormationAttrResponse, RoleFavorListRequest, RoleFavorListResponse,
-    RoleShowListUpdateRequest, RoleShowListUpdateResponse, UpdateFormationRequest,
-    UpdateFormationResponse,
+    ArrayIntInt, ClientCurrentRoleReportRequest, ClientCurrentRoleReportResponse, EAttributeType,
+    ERemoveEntityType, ErrorCode, FormationAttrRequest, FormationAttrResponse, PbOverRoleRequest,
+    PbOverRoleResponse, PbRoleExpNotify, PbRolePropsNotify, PbRoleSkillLevelNotify,
+    PbUpLevelRoleRequest, PbUpLevelRoleResponse, PbUpLevelSkillRequest, PbUpLevelSkillResponse,
+    RoleActivateSkillRequest, RoleActivateSkillResponse, RoleBreakThroughViewRequest,
+    RoleBreakThroughViewResponse, RoleFavorListRequest, RoleFavorListResponse,
+    RoleLevelUpViewRequest, RoleLevelUpViewResponse, RoleShowListUpdateRequest,
+    RoleShowListUpdateResponse, RoleSkillLevelUpViewRequest, RoleSkillLevelUpViewResponse,
+    RoleSkillNodeNotify, SkillEffect, UpdateFormationRequest, UpdateFormationResponse,
 };
 use std::collections::HashSet;
 
@@ -96,7 +103,7 @@ pub fn on_update_formation_request(
             let added_roles: Vec<Role> = formation
                 .role_ids
                 .iter()
-                .map(|&role_id| Role::new(role_id))
+                .flat_map(|role_id| player.role_list.get(role_id).cloned())
                 .collect();
 
             if !added_roles.is_empty() {
@@ -139,3 +146,318 @@ pub fn on_update_formation_request(
 
     response.error_code = ErrorCode::Success.into();
 }
+
+pub fn on_role_level_up_view_request(
+    player: &Player,
+    request: RoleLevelUpViewRequest,
+    response: &mut RoleLevelUpViewResponse,
+) {
+    let Some(role) = player.role_list.get(&request.role_id) else {
+        response.error_code = ErrorCode::ErrRoleNotActive.into();
+        return;
+    };
+
+    match role.level_up_cost(&request.item_list) {
+        Ok((exp, usages)) => {
+            let mut preview = role.clone();
+            preview.add_exp(exp);
+
+            response.level = preview.level;
+            response.exp = preview.exp;
+            response.add_exp = exp;
+            response.final_prop = preview.build_final_prop(&player.inventory);
+            response.cost_list = build_cost_list(&usages);
+            response.item_list = request.item_list;
+            response.error_code = ErrorCode::Success.into();
+        }
+        Err(err) => {
+            tracing::debug!("RoleLevelUpViewRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+        }
+    }
+}
+
+pub fn on_pb_up_level_role_request(
+    player: &mut Player,
+    request: PbUpLevelRoleRequest,
+    response: &mut PbUpLevelRoleResponse,
+) {
+    response.role_id = request.role_id;
+
+    let Some(role) = player.role_list.get(&request.role_id) else {
+        response.error_code = ErrorCode::ErrRoleNotActive.into();
+        return;
+    };
+
+    let (exp, usages) = match role.level_up_cost(&request.item_list) {
+        Ok(cost) => cost,
+        Err(err) => {
+            tracing::debug!("PbUpLevelRoleRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    };
+
+    if let Err(err) = player.consume_items(&usages) {
+        tracing::debug!("PbUpLevelRoleRequest: failed to consume items, err: {err}");
+        response.error_code = ErrorCode::from(err).into();
+        return;
+    }
+
+    let role = player.role_list.get_mut(&request.role_id).unwrap();
+    role.add_exp(exp);
+
+    response.level = role.level;
+    response.exp = role.exp;
+    response.error_code = ErrorCode::Success.into();
+
+    player.notify(PbRoleExpNotify {
+        role_id: request.role_id,
+        exp: response.exp,
+        level: response.level,
+    });
+    update_role_props(player, request.role_id);
+}
+
+pub fn on_role_break_through_view_request(
+    player: &Player,
+    request: RoleBreakThroughViewRequest,
+    response: &mut RoleBreakThroughViewResponse,
+) {
+    let Some(role) = player.role_list.get(&request.role_id) else {
+        response.error_code = ErrorCode::ErrRoleNotActive.into();
+        return;
+    };
+
+    match role.breakthrough_cost() {
+        Ok((next_breach, usages)) => {
+            let mut preview = role.clone();
+            preview.breakthrough = next_breach.breach_level;
+
+            response.level_limit = next_breach.max_level;
+            response.cost_list = build_cost_list(&usages);
+            response.final_prop = preview.build_final_prop(&player.inventory);
+            response.is_condition_finish = role.check_breakthrough_level().is_ok();
+            response.error_code = ErrorCode::Success.into();
+        }
+        Err(err) => {
+            tracing::debug!("RoleBreakThroughViewRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+        }
+    }
+}
+
+pub fn on_pb_over_role_request(
+    player: &mut Player,
+    request: PbOverRoleRequest,
+    response: &mut PbOverRoleResponse,
+) {
+    response.role_id = request.role_id;
+
+    let Some(role) = player.role_list.get(&request.role_id) else {
+        response.error_code = ErrorCode::ErrRoleNotActive.into();
+        return;
+    };
+
+    let (next_breach, usages) = match role
+        .check_breakthrough_level()
+        .and_then(|_| role.breakthrough_cost())
+    {
+        Ok(cost) => cost,
+        Err(err) => {
+            tracing::debug!("PbOverRoleRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    };
+
+    if let Err(err) = player.consume_items(&usages) {
+        tracing::debug!("PbOverRoleRequest: failed to consume items, err: {err}");
+        response.error_code = ErrorCode::from(err).into();
+        return;
+    }
+
+    let role = player.role_list.get_mut(&request.role_id).unwrap();
+    role.breakthrough = next_breach.breach_level;
+
+    response.breakthrough = role.breakthrough;
+    response.error_code = ErrorCode::Success.into();
+
+    update_role_props(player, request.role_id);
+}
+
+pub fn on_role_skill_level_up_view_request(
+    player: &Player,
+    request: RoleSkillLevelUpViewRequest,
+    response: &mut RoleSkillLevelUpViewResponse,
+) {
+    let Some(role) = player.role_list.get(&request.role_id) else {
+        response.error_code = ErrorCode::ErrRoleNotActive.into();
+        return;
+    };
+
+    match role.skill_level_up_cost(request.skill_id) {
+        Ok(usages) => {
+            let level = role
+                .skill_map
+                .get(&request.skill_id)
+                .copied()
+                .unwrap_or_default();
+
+            // TODO: skill effect descriptions
+            response.skill_effect_list = vec![SkillEffect {
+                level: level + 1,
+                effect_desc_list: Vec::new(),
+            }];
+            response.cost_list = build_cost_list(&usages);
+            response.error_code = ErrorCode::Success.into();
+        }
+        Err(err) => {
+            tracing::debug!("RoleSkillLevelUpViewRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+        }
+    }
+}
+
+pub fn on_pb_up_level_skill_request(
+    player: &mut Player,
+    request: PbUpLevelSkillRequest,
+    response: &mut PbUpLevelSkillResponse,
+) {
+    response.role_id = request.role_id;
+
+    let Some(role) = player.role_list.get(&request.role_id) else {
+        response.error_code = ErrorCode::ErrRoleNotActive.into();
+        return;
+    };
+
+    let usages = match role.skill_level_up_cost(request.skill_id) {
+        Ok(usages) => usages,
+        Err(err) => {
+            tracing::debug!("PbUpLevelSkillRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    };
+
+    if let Err(err) = player.consume_items(&usages) {
+        tracing::debug!("PbUpLevelSkillRequest: failed to consume items, err: {err}");
+        response.error_code = ErrorCode::from(err).into();
+        return;
+    }
+
+    let role = player.role_list.get_mut(&request.role_id).unwrap();
+    let skill_info = ArrayIntInt {
+        key: request.skill_id,
+        value: role.level_up_skill(request.skill_id),
+    };
+
+    response.skill_info = Some(skill_info);
+    response.error_code = ErrorCode::Success.into();
+
+    player.notify(PbRoleSkillLevelNotify {
+        role_id: request.role_id,
+        skill_info: Some(skill_info),
+    });
+}
+
+pub fn on_role_activate_skill_request(
+    player: &mut Player,
+    request: RoleActivateSkillRequest,
+    response: &mut RoleActivateSkillResponse,
+) {
+    response.role_id = request.role_id;
+
+    let Some(role) = player.role_list.get(&request.role_id) else {
+        response.error_code = ErrorCode::ErrRoleNotActive.into();
+        return;
+    };
+
+    let usages = match role.skill_node_cost(request.skill_node_id) {
+        Ok(usages) => usages,
+        Err(err) => {
+            tracing::debug!("RoleActivateSkillRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    };
+
+    if let Err(err) = player.consume_items(&usages) {
+        tracing::debug!("RoleActivateSkillRequest: failed to consume items, err: {err}");
+        response.error_code = ErrorCode::from(err).into();
+        return;
+    }
+
+    let role = player.role_list.get_mut(&request.role_id).unwrap();
+    match role.activate_skill_node(request.skill_node_id) {
+        Ok(skill) => {
+            response.skill_info = skill.map(|(key, value)| ArrayIntInt { key, value });
+            response.error_code = ErrorCode::Success.into();
+        }
+        Err(err) => {
+            tracing::debug!("RoleActivateSkillRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    }
+
+    let notify = RoleSkillNodeNotify {
+        role_id: role.role_id,
+        skill_node_state: role
+            .skill_node_state
+            .iter()
+            .map(RoleSkillNode::to_protobuf)
+            .collect(),
+    };
+
+    player.notify(notify);
+    update_role_props(player, request.role_id);
+}
+
+fn build_cost_list(usages: &[ItemUsage]) -> Vec<ArrayIntInt> {
+    usages
+        .iter()
+        .map(|usage| ArrayIntInt {
+            key: usage.id,
+            value: usage.quantity,
+        })
+        .collect()
+}
+
+// Sends recalculated role properties and syncs Attribute component of its entity (if in current formation)
+pub fn update_role_props(player: &Player, role_id: i32) {
+    let Some(role) = player.role_list.get(&role_id) else {
+        return;
+    };
+
+    let mut attr_map = role.build_attributes(&player.inventory);
+    player.notify(PbRolePropsNotify {
+        role_id,
+        base_prop: Role::build_base_prop(&attr_map),
+        add_prop: Role::build_add_prop(&attr_map),
+    });
+
+    if !player
+        .formation_list
+        .get(&player.cur_formation_id)
+        .is_some_and(|f| f.role_ids.contains(&role_id))
+    {
+        return;
+    }
+
+    let world_ref = player.world.borrow();
+    let world = world_ref.get_world_entity();
+    let entity_id = world.get_entity_id(role_id);
+
+    // Rebuilt attributes come with full life, current one is kept so roles aren't healed or revived.
+    // AttributeSystem broadcasts the change
+    let (Some(mut attribute),) = query_components!(world, entity_id, Attribute) else {
+        return;
+    };
+
+    attr_map.insert(
+        EAttributeType::Life,
+        (attribute.value(EAttributeType::Life), 0),
+    );
+    attribute.set_attr_map(attr_map);
+}
@@ -0,0 +1,268 @@
+use common::time_util;
+use shorekeeper_data::pay_shop_item_data;
+use shorekeeper_protocol::{
+    ErrorCode, PayShopBuyRequest, PayShopBuyResponse, PayShopDirectBuyNotify,
+    PayShopDirectBuyRequest, PayShopDirectBuyResponse, PayShopInfoRequest, PayShopInfoResponse,
+    PayShopItemUpdateRequest, PayShopItemUpdateResponse, PayShopUpdateRequest,
+    PayShopUpdateResponse, ShopBuyRequest, ShopBuyResponse, ShopInfoRequest, ShopInfoResponse,
+    ShopUpdateRequest, ShopUpdateResponse,
+};
+
+use crate::logic::{
+    player::{ItemUsage, Player},
+    utils::payment_util,
+};
+
+pub fn on_shop_info_request(
+    player: &Player,
+    request: ShopInfoRequest,
+    response: &mut ShopInfoResponse,
+) {
+    response.version_str = request.version_str;
+    response.shop_list = player.shop.build_shop_info_list(now());
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_shop_update_request(
+    player: &Player,
+    request: ShopUpdateRequest,
+    response: &mut ShopUpdateResponse,
+) {
+    match player.shop.build_shop_info(request.shop_id, now()) {
+        Some(info) => {
+            response.info = Some(info);
+            response.error_code = ErrorCode::Success.into();
+        }
+        None => response.error_code = ErrorCode::ErrShopIdNotExit.into(),
+    }
+}
+
+pub fn on_shop_buy_request(
+    player: &mut Player,
+    request: ShopBuyRequest,
+    response: &mut ShopBuyResponse,
+) {
+    response.shop_id = request.shop_id;
+    response.id = request.id;
+
+    let now = now();
+    let data = match player.shop.check_buy(
+        request.shop_id,
+        request.id,
+        request.money_id,
+        request.num,
+        now,
+    ) {
+        Ok(data) => data,
+        Err(err) => {
+            tracing::debug!("ShopBuyRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    };
+
+    if let Err(err) = buy(
+        player,
+        (data.money_id, data.price),
+        (data.item_id, data.item_num),
+        request.num,
+    ) {
+        tracing::debug!("ShopBuyRequest: shop item {}, err: {err:?}", data.id);
+        response.error_code = err.into();
+        return;
+    }
+
+    response.bought_count = player.shop.on_bought(data, request.num, now);
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_pay_shop_info_request(
+    player: &Player,
+    request: PayShopInfoRequest,
+    response: &mut PayShopInfoResponse,
+) {
+    response.version = request.version;
+    response.infos = player.shop.build_pay_shop_info_list(now());
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_pay_shop_update_request(
+    player: &Player,
+    request: PayShopUpdateRequest,
+    response: &mut PayShopUpdateResponse,
+) {
+    match player.shop.build_pay_shop_info(request.id, now()) {
+        Some(info) => {
+            response.info = Some(info);
+            response.error_code = ErrorCode::Success.into();
+        }
+        None => response.error_code = ErrorCode::ErrPayShopNotExists.into(),
+    }
+}
+
+pub fn on_pay_shop_item_update_request(
+    player: &Player,
+    request: PayShopItemUpdateRequest,
+    response: &mut PayShopItemUpdateResponse,
+) {
+    let now = now();
+    response.items = request
+        .ids
+        .iter()
+        .filter_map(|id| pay_shop_item_data::iter().find(|d| d.id == *id))
+        .map(|data| player.shop.build_pay_shop_item(data, now))
+        .collect();
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_pay_shop_buy_request(
+    player: &mut Player,
+    request: PayShopBuyRequest,
+    response: &mut PayShopBuyResponse,
+) {
+    response.id = request.id;
+    response.count = request.count;
+
+    let now = now();
+    let data = match player
+        .shop
+        .check_pay_buy(request.id, request.count, false, now)
+    {
+        Ok(data) => data,
+        Err(err) => {
+            tracing::debug!("PayShopBuyRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    };
+
+    if let Err(err) = buy(
+        player,
+        (data.price_id, data.price_count),
+        (data.item_id, data.item_count),
+        request.count,
+    ) {
+        tracing::debug!("PayShopBuyRequest: pay shop item {}, err: {err:?}", data.id);
+        response.error_code = err.into();
+        return;
+    }
+
+    player.shop.on_pay_bought(data, request.count, now);
+    response.error_code = ErrorCode::Success.into();
+}
+
+// Real money purchase, charged through the payment provider
+pub fn on_pay_shop_direct_buy_request(
+    player: &mut Player,
+    request: PayShopDirectBuyRequest,
+    response: &mut PayShopDirectBuyResponse,
+) {
+    let now = now();
+    let data = match player.shop.check_pay_buy(request.id, 1, true, now) {
+        Ok(data) => data,
+        Err(err) => {
+            tracing::debug!("PayShopDirectBuyRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    };
+
+    // Goods have to fit into the inventory before the player is charged
+    let goods = [ItemUsage {
+        id: data.item_id,
+        quantity: data.item_count,
+    }];
+    if let Err(err) = player.inventory.check_add(&goods) {
+        tracing::debug!(
+            "PayShopDirectBuyRequest: pay shop item {}, err: {err}",
+            data.id
+        );
+        response.error_code = ErrorCode::from(err).into();
+        return;
+    }
+
+    let receipt_id = match payment_util::pay(player.basic_info.id, data.id, data.price_count) {
+        Ok(receipt_id) => receipt_id,
+        Err(err) => {
+            tracing::warn!("PayShopDirectBuyRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    };
+
+    match player.inventory.add_items(&goods) {
+        Ok(change) => player.notify_inventory_change(&change),
+        Err(err) => {
+            tracing::error!(
+                "PayShopDirectBuyRequest: failed to deliver pay shop item {}, receipt: {receipt_id}, err: {err}",
+                data.id
+            );
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    }
+
+    player.shop.on_pay_bought(data, 1, now);
+    player.notify(PayShopDirectBuyNotify {
+        receipt_id: receipt_id.clone(),
+        shop_item_id: data.id,
+        item_id: data.item_id,
+        item_count: data.item_count,
+    });
+
+    response.receipt_id = receipt_id;
+    response.error_code = ErrorCode::Success.into();
+}
+
+#[inline]
+fn now() -> i64 {
+    time_util::unix_timestamp() as i64
+}
+
+// Pays num times the (money_id, price) and grants num times the (item_id, count),
+// free if price is 0, payment is refunded if goods can't be granted
+fn buy(
+    player: &mut Player,
+    cost: (i32, i32),
+    goods: (i32, i32),
+    num: i32,
+) -> Result<(), ErrorCode> {
+    let (money_id, price) = cost;
+    let price = price
+        .checked_mul(num)
+        .ok_or(ErrorCode::ErrShopIllegalBuyCount)?;
+
+    let (item_id, count) = goods;
+    let goods = [ItemUsage {
+        id: item_id,
+        quantity: count
+            .checked_mul(num)
+            .ok_or(ErrorCode::ErrShopIllegalBuyCount)?,
+    }];
+    player.inventory.check_add(&goods)?;
+
+    let payment = [ItemUsage {
+        id: money_id,
+        quantity: price,
+    }];
+    if price > 0 {
+        player.consume_items(&payment)?;
+    }
+
+    match player.inventory.add_items(&goods) {
+        Ok(change) => {
+            player.notify_inventory_change(&change);
+            Ok(())
+        }
+        Err(err) => {
+            if price > 0 {
+                if let Err(err) = player.add_items(&payment) {
+                    tracing::error!(
+                        "shop: failed to refund {price} of item {money_id}, err: {err}"
+                    );
+                }
+            }
+            Err(err.into())
+        }
+    }
+}
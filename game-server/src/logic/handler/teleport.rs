@@ -0,0 +1,95 @@
+use shorekeeper_protocol::{
+    ErrorCode, TeleportDataRequest, TeleportDataResponse, TeleportFinishRequest,
+    TeleportFinishResponse, TeleportReason, TeleportTransferRequest, TeleportTransferResponse,
+    UnlockTeleportRequest, UnlockTeleportResponse,
+};
+
+use crate::logic::{
+    event_bus::{self, GameEvent},
+    player::Player,
+    utils::world_util,
+};
+
+pub fn on_teleport_data_request(
+    player: &mut Player,
+    _: TeleportDataRequest,
+    response: &mut TeleportDataResponse,
+) {
+    response.ids = player.teleport.unlocked_ids().collect();
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_unlock_teleport_request(
+    player: &mut Player,
+    request: UnlockTeleportRequest,
+    response: &mut UnlockTeleportResponse,
+) {
+    let map_id = player.location.map_id();
+    match player
+        .teleport
+        .unlock(request.id, map_id, &player.location.position.position)
+    {
+        Ok(notify) => {
+            player.notify(notify);
+            event_bus::emit(
+                player,
+                GameEvent::TeleportUnlocked {
+                    teleporter_id: request.id,
+                },
+            );
+            response.error_code = ErrorCode::Success.into();
+        }
+        Err(err) => {
+            tracing::debug!("UnlockTeleportRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+        }
+    }
+}
+
+pub fn on_teleport_transfer_request(
+    player: &mut Player,
+    request: TeleportTransferRequest,
+    response: &mut TeleportTransferResponse,
+) {
+    let teleporter = match player.teleport.start_transfer(request.id) {
+        Ok(teleporter) => teleporter,
+        Err(err) => {
+            tracing::debug!("TeleportTransferRequest: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    };
+
+    tracing::debug!(
+        "TeleportTransferRequest: teleporter {} (entity {}) on map {}",
+        request.id,
+        teleporter.entity_id,
+        teleporter.map_id
+    );
+
+    let transform = &teleporter.transform;
+    response.map_id = teleporter.map_id;
+    response.pos_x = transform.position.x;
+    response.pos_y = transform.position.y;
+    response.pos_z = transform.position.z;
+    response.pitch = transform.rotation.x;
+    response.yaw = transform.rotation.y;
+    response.roll = transform.rotation.z;
+    response.error_code = ErrorCode::Success.into();
+
+    world_util::teleport_player(
+        player,
+        teleporter.map_id,
+        teleporter.transform.clone(),
+        TeleportReason::Transfer,
+    );
+}
+
+pub fn on_teleport_finish_request(
+    player: &mut Player,
+    _: TeleportFinishRequest,
+    response: &mut TeleportFinishResponse,
+) {
+    player.teleport.finish_transfer();
+    response.error_code = ErrorCode::Success.into();
+}
@@ -0,0 +1,367 @@
+use std::collections::HashMap;
+
+use shorekeeper_data::{role_info_data, weapon_exp_item_data, weapon_reson_data};
+use shorekeeper_protocol::{
+    EntityEquipChangeNotify, EquipComponentPb, EquipTakeOnRequest, EquipTakeOnResponse, ErrorCode,
+    RoleLoadEquipData, WeaponBreachRequest, WeaponBreachResponse, WeaponLevelUpRequest,
+    WeaponLevelUpResponse, WeaponResonUpRequest, WeaponResonUpResponse,
+};
+
+use crate::logic::components::Equip;
+use crate::logic::ecs::component::ComponentContainer;
+use crate::logic::player::{ItemUsage, Player, PlayerInventory};
+use crate::modify_component;
+
+pub fn on_weapon_level_up_request(
+    player: &mut Player,
+    request: WeaponLevelUpRequest,
+    response: &mut WeaponLevelUpResponse,
+) {
+    response.inc_id = request.inc_id;
+
+    let Some(weapon) = player.inventory.get_weapon(request.inc_id) else {
+        response.error_code = ErrorCode::ErrItemNotFound.into();
+        return;
+    };
+
+    if weapon.level >= weapon.level_limit() {
+        response.error_code = ErrorCode::ErrWeaponLevelUpLevel.into();
+        return;
+    }
+
+    let mut usages = Vec::with_capacity(request.consume_list.len() + 1);
+    let mut exp = 0i32;
+    let mut gold = 0i32;
+
+    for item in request.consume_list.iter() {
+        // TODO: weapons as level up material
+        if item.inc_id != 0 {
+            response.error_code = ErrorCode::ErrWeaponConsumeInvalid.into();
+            return;
+        }
+
+        if item.count <= 0 {
+            response.error_code = ErrorCode::ErrWeaponLevelUpComsumeCount.into();
+            return;
+        }
+
+        if usages.iter().any(|u: &ItemUsage| u.id == item.item_id) {
+            response.error_code = ErrorCode::ErrWeaponLevelUpItemDuplicate.into();
+            return;
+        }
+
+        let Some(exp_item) = weapon_exp_item_data::iter().find(|d| d.id == item.item_id) else {
+            response.error_code = ErrorCode::ErrWeaponConsumeItemIdNotFound.into();
+            return;
+        };
+
+        exp = exp.saturating_add(exp_item.basic_exp.saturating_mul(item.count));
+        gold = gold.saturating_add(exp_item.cost.saturating_mul(item.count));
+        usages.push(ItemUsage {
+            id: item.item_id,
+            quantity: item.count,
+        });
+    }
+
+    if exp == 0 {
+        response.error_code = ErrorCode::ErrWeaponLevelUpNoExp.into();
+        return;
+    }
+
+    if gold > 0 {
+        usages.push(ItemUsage {
+            id: PlayerInventory::SHELL_CREDIT_ID,
+            quantity: gold,
+        });
+    }
+
+    if let Err(err) = player.consume_items(&usages) {
+        tracing::debug!("WeaponLevelUpRequest: failed to consume items, err: {err}");
+        response.error_code = ErrorCode::from(err).into();
+        return;
+    }
+
+    let weapon = player.inventory.get_weapon_mut(request.inc_id).unwrap();
+    weapon.add_exp(exp);
+
+    response.weapon_level = weapon.level;
+    response.weapon_exp = weapon.exp;
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_weapon_breach_request(
+    player: &mut Player,
+    request: WeaponBreachRequest,
+    response: &mut WeaponBreachResponse,
+) {
+    response.inc_id = request.inc_id;
+
+    let Some(weapon) = player.inventory.get_weapon(request.inc_id) else {
+        response.error_code = ErrorCode::ErrItemNotFound.into();
+        return;
+    };
+
+    let Some(next_breach) = weapon.next_breach() else {
+        response.error_code = ErrorCode::ErrWeaponBreachLimit.into();
+        return;
+    };
+
+    if weapon.level < weapon.level_limit() {
+        response.error_code = ErrorCode::ErrWeaponLevelLimit.into();
+        return;
+    }
+
+    let mut usages: Vec<ItemUsage> = next_breach
+        .consume
+        .iter()
+        .map(|(&id, &quantity)| ItemUsage { id, quantity })
+        .collect();
+
+    if next_breach.gold_consume > 0 {
+        usages.push(ItemUsage {
+            id: PlayerInventory::SHELL_CREDIT_ID,
+            quantity: next_breach.gold_consume,
+        });
+    }
+
+    if let Err(err) = player.consume_items(&usages) {
+        tracing::debug!("WeaponBreachRequest: failed to consume items, err: {err}");
+        response.error_code = ErrorCode::from(err).into();
+        return;
+    }
+
+    let weapon = player.inventory.get_weapon_mut(request.inc_id).unwrap();
+    weapon.breach = next_breach.level;
+
+    response.weapon_breach = weapon.breach;
+    response.error_code = ErrorCode::Success.into();
+
+    let role_id = weapon.role_id;
+    if role_id != 0 {
+        update_role_equip(player, role_id);
+    }
+}
+
+pub fn on_weapon_reson_up_request(
+    player: &mut Player,
+    request: WeaponResonUpRequest,
+    response: &mut WeaponResonUpResponse,
+) {
+    response.inc_id = request.inc_id;
+
+    let Some(weapon) = player.inventory.get_weapon(request.inc_id) else {
+        response.error_code = ErrorCode::ErrItemNotFound.into();
+        return;
+    };
+
+    let Some(conf) = weapon.conf() else {
+        response.error_code = ErrorCode::ErrWeaponConfigNotFound.into();
+        return;
+    };
+
+    if request.consume_list.is_empty() {
+        response.error_code = ErrorCode::ErrWeaponConsumeInvalid.into();
+        return;
+    }
+
+    let reson_level_limit = weapon_reson_data::iter()
+        .filter(|d| d.reson_id == conf.reson_id)
+        .map(|d| d.level)
+        .max()
+        .unwrap_or_default();
+
+    let target_level = weapon.reson_level + request.consume_list.len() as i32;
+    if target_level > reson_level_limit {
+        response.error_code = ErrorCode::ErrWeaponResonLevelLimit.into();
+        return;
+    }
+
+    for incr_id in request.consume_list.iter() {
+        if *incr_id == request.inc_id {
+            response.error_code = ErrorCode::ErrWeaponConsumeSelf.into();
+            return;
+        }
+
+        match player.inventory.get_weapon(*incr_id) {
+            Some(material) if material.id == weapon.id => (),
+            Some(_) => {
+                response.error_code = ErrorCode::ErrWeaponConsumeInvalid.into();
+                return;
+            }
+            None => {
+                response.error_code = ErrorCode::ErrWeaponConsumeItemNotFound.into();
+                return;
+            }
+        }
+    }
+
+    let mut required: HashMap<i32, i32> = HashMap::new();
+    for level in weapon.reson_level + 1..=target_level {
+        let Some(reson) =
+            weapon_reson_data::iter().find(|d| d.reson_id == conf.reson_id && d.level == level)
+        else {
+            response.error_code = ErrorCode::ErrWeaponResonConfigNotFound.into();
+            return;
+        };
+
+        for (&id, &quantity) in reson.consume.iter() {
+            *required.entry(id).or_default() += quantity;
+        }
+
+        if reson.gold_consume > 0 {
+            *required
+                .entry(PlayerInventory::SHELL_CREDIT_ID)
+                .or_default() += reson.gold_consume;
+        }
+    }
+
+    if request
+        .consume_item_list
+        .iter()
+        .any(|item| !required.contains_key(&item.item_id))
+    {
+        response.error_code = ErrorCode::ErrWeaponResonConsumeItem.into();
+        return;
+    }
+
+    let usages: Vec<ItemUsage> = required
+        .into_iter()
+        .map(|(id, quantity)| ItemUsage { id, quantity })
+        .collect();
+
+    match player
+        .inventory
+        .consume_with_weapons(&usages, &request.consume_list)
+    {
+        Ok(change) => player.notify_inventory_change(&change),
+        Err(err) => {
+            tracing::debug!("WeaponResonUpRequest: failed to consume items, err: {err}");
+            response.error_code = ErrorCode::from(err).into();
+            return;
+        }
+    }
+
+    let weapon = player.inventory.get_weapon_mut(request.inc_id).unwrap();
+    weapon.reson_level = target_level;
+
+    response.reson_level = weapon.reson_level;
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_equip_take_on_request(
+    player: &mut Player,
+    request: EquipTakeOnRequest,
+    response: &mut EquipTakeOnResponse,
+) {
+    let Some(data) = request.data else {
+        response.error_code = ErrorCode::ErrLoadEquipDefault.into();
+        return;
+    };
+
+    // Roles have a single weapon slot
+    if data.pos != 0 {
+        response.error_code = ErrorCode::ErrLoadEquipInvalidPos.into();
+        return;
+    }
+
+    if !player.role_list.contains_key(&data.role_id) {
+        response.error_code = ErrorCode::ErrLoadEquipInvalidRole.into();
+        return;
+    }
+
+    let Some(role_info) = role_info_data::get(data.role_id) else {
+        response.error_code = ErrorCode::ErrLoadEquipRoleConfig.into();
+        return;
+    };
+
+    let Some(weapon) = player.inventory.get_weapon(data.equip_inc_id) else {
+        response.error_code = ErrorCode::ErrItemNotFound.into();
+        return;
+    };
+
+    let Some(conf) = weapon.conf() else {
+        response.error_code = ErrorCode::ErrWeaponConfigNotFound.into();
+        return;
+    };
+
+    if conf.weapon_type != role_info.weapon_type {
+        response.error_code = ErrorCode::ErrLoadEquipRoleConfig.into();
+        return;
+    }
+
+    let prev_owner = weapon.role_id;
+    let prev_weapon = player
+        .inventory
+        .get_role_weapon(data.role_id)
+        .map(|w| w.incr_id);
+
+    response.error_code = ErrorCode::Success.into();
+    response.data_list.push(data);
+    if prev_owner == data.role_id {
+        return;
+    }
+
+    // Previously equipped weapon goes to the role that owned the new one (if any)
+    if let Some(prev_incr_id) = prev_weapon {
+        let prev = player.inventory.get_weapon_mut(prev_incr_id).unwrap();
+        prev.role_id = prev_owner;
+
+        if let Some(role) = player.role_list.get_mut(&prev_owner) {
+            role.equip_weapon = prev.id;
+            response.data_list.push(RoleLoadEquipData {
+                role_id: prev_owner,
+                pos: data.pos,
+                equip_inc_id: prev_incr_id,
+            });
+        }
+    }
+
+    let weapon = player.inventory.get_weapon_mut(data.equip_inc_id).unwrap();
+    weapon.role_id = data.role_id;
+    player
+        .role_list
+        .get_mut(&data.role_id)
+        .unwrap()
+        .equip_weapon = weapon.id;
+
+    update_role_equip(player, data.role_id);
+    if prev_owner != 0 {
+        update_role_equip(player, prev_owner);
+    }
+}
+
+// Syncs Equip component of role entity in current formation with its weapon
+fn update_role_equip(player: &Player, role_id: i32) {
+    let Some(weapon) = player.inventory.get_role_weapon(role_id) else {
+        return;
+    };
+
+    if !player
+        .formation_list
+        .get(&player.cur_formation_id)
+        .is_some_and(|f| f.role_ids.contains(&role_id))
+    {
+        return;
+    }
+
+    let world_ref = player.world.borrow();
+    let world = world_ref.get_world_entity();
+    let entity_id = world.get_entity_id(role_id);
+
+    modify_component!(
+        world.get_entity_components(entity_id as i32),
+        Equip,
+        |equip: &mut Equip| {
+            equip.weapon_id = weapon.id;
+            equip.weapon_breach_level = weapon.breach;
+        }
+    );
+
+    player.notify(EntityEquipChangeNotify {
+        entity_id,
+        equip_component: Some(EquipComponentPb {
+            weapon_id: weapon.id,
+            weapon_breach_level: weapon.breach,
+        }),
+    });
+}
@@ -0,0 +1,53 @@
+use common::time_util;
+use shorekeeper_protocol::{
+    ErrorCode, WorldLevelDownRequest, WorldLevelDownResponse, WorldLevelRegainRequest,
+    WorldLevelRegainResponse,
+};
+
+use crate::logic::{player::Player, utils::world_util};
+
+pub fn on_world_level_down_request(
+    player: &mut Player,
+    _: WorldLevelDownRequest,
+    response: &mut WorldLevelDownResponse,
+) {
+    if let Err(err) = player
+        .basic_info
+        .world_level_down(time_util::unix_timestamp() as i64)
+    {
+        tracing::debug!("WorldLevelDownRequest: {err}");
+        response.error_code = ErrorCode::from(err).into();
+        return;
+    }
+
+    player.notify(player.basic_info.build_level_notify());
+    world_util::update_monster_levels(player);
+
+    response.origin_world_level = player.basic_info.origin_world_level();
+    response.cur_world_level = player.basic_info.cur_world_level();
+    response.world_level_time_stamp = player.basic_info.world_level_time as i32;
+    response.error_code = ErrorCode::Success.into();
+}
+
+pub fn on_world_level_regain_request(
+    player: &mut Player,
+    _: WorldLevelRegainRequest,
+    response: &mut WorldLevelRegainResponse,
+) {
+    if let Err(err) = player
+        .basic_info
+        .world_level_regain(time_util::unix_timestamp() as i64)
+    {
+        tracing::debug!("WorldLevelRegainRequest: {err}");
+        response.error_code = ErrorCode::from(err).into();
+        return;
+    }
+
+    player.notify(player.basic_info.build_level_notify());
+    world_util::update_monster_levels(player);
+
+    response.origin_world_level = player.basic_info.origin_world_level();
+    response.cur_world_level = player.basic_info.cur_world_level();
+    response.world_level_time_stamp = player.basic_info.world_level_time as i32;
+    response.error_code = ErrorCode::Success.into();
+}
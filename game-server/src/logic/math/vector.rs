@@ -33,6 +33,11 @@ impl Vector3f {
         }
     }
 
+    pub fn distance_squared(&self, other: &Self) -> f32 {
+        let (dx, dy, dz) = (self.x - other.x, self.y - other.y, self.z - other.z);
+        dx * dx + dy * dy + dz * dz
+    }
+
     pub fn from_data(data: &shorekeeper_data::VectorData) -> Self {
         Self {
             x: data.get_x(),
@@ -1,5 +1,7 @@
 pub mod components;
 pub mod ecs;
+pub mod event_bus;
+pub mod gm;
 pub mod handler;
 pub mod math;
 pub mod player;
@@ -1,7 +1,12 @@
+use shorekeeper_data::{player_level_data, world_level_data, PlayerLevelData};
 use shorekeeper_protocol::{
-    player_attr, BasicInfoNotify, PlayerAttr, PlayerAttrKey, PlayerAttrType, PlayerBasicData,
+    player_attr, BasicInfoNotify, ErrorCode, PlayerAttr, PlayerAttrKey, PlayerAttrNotify,
+    PlayerAttrType, PlayerBasicData,
 };
 
+// Manual world level changes are limited to one per cooldown
+const WORLD_LEVEL_CD: i64 = 3600;
+
 pub struct PlayerBasicInfo {
     pub id: i32,
     pub name: String,
@@ -12,24 +17,59 @@ pub struct PlayerBasicInfo {
     pub head_frame: i32,
     pub cur_map_id: i32,
     pub role_show_list: Vec<i32>,
+    // World level is lowered by one below the one derived from account level
+    pub world_level_down: bool,
+    // Time of the last manual world level change
+    pub world_level_time: i64,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum WorldLevelError {
+    #[error("world level is already lowered")]
+    AlreadyDown,
+    #[error("world level is not lowered")]
+    NotDown,
+    #[error("world level can't be lowered below 0")]
+    Min,
+    #[error("world level can't be changed until {0}")]
+    Cooldown(i64),
+}
+
+impl From<WorldLevelError> for ErrorCode {
+    fn from(err: WorldLevelError) -> Self {
+        match err {
+            WorldLevelError::AlreadyDown => ErrorCode::ErrWorldLevelHadDown,
+            WorldLevelError::NotDown => ErrorCode::ErrWorldLevelNotDown,
+            WorldLevelError::Min => ErrorCode::ErrWorldLevelMin,
+            WorldLevelError::Cooldown(_) => ErrorCode::ErrWorldLevelCd,
+        }
+    }
 }
 
 impl PlayerBasicInfo {
     pub fn build_notify(&self) -> BasicInfoNotify {
+        let mut attributes = vec![
+            build_str_attr(PlayerAttrKey::Name, self.name.as_str()),
+            build_int_attr(PlayerAttrKey::Sex, self.sex),
+            build_int_attr(PlayerAttrKey::HeadPhoto, self.head_photo),
+            build_int_attr(PlayerAttrKey::HeadFrame, self.head_frame),
+        ];
+        attributes.extend(self.build_level_attrs());
+
         BasicInfoNotify {
             id: self.id,
-            attributes: vec![
-                build_str_attr(PlayerAttrKey::Name, self.name.as_str()),
-                build_int_attr(PlayerAttrKey::Level, self.level),
-                build_int_attr(PlayerAttrKey::Exp, self.exp),
-                build_int_attr(PlayerAttrKey::Sex, self.sex),
-                build_int_attr(PlayerAttrKey::HeadPhoto, self.head_photo),
-                build_int_attr(PlayerAttrKey::HeadFrame, self.head_frame),
-            ],
+            attributes,
             ..Default::default()
         }
     }
 
+    pub fn build_level_notify(&self) -> PlayerAttrNotify {
+        // Attributes field name is obfuscated in the dumped proto
+        PlayerAttrNotify {
+            is_stop_character: self.build_level_attrs(),
+        }
+    }
+
     pub fn load_from_save(data: PlayerBasicData) -> Self {
         Self {
             id: data.id,
@@ -41,6 +81,8 @@ impl PlayerBasicInfo {
             head_frame: data.head_frame,
             cur_map_id: data.cur_map_id,
             role_show_list: data.role_show_list,
+            world_level_down: data.world_level_down,
+            world_level_time: data.world_level_time,
         }
     }
 
@@ -55,7 +97,110 @@ impl PlayerBasicInfo {
             head_frame: self.head_frame,
             cur_map_id: self.cur_map_id,
             role_show_list: self.role_show_list.clone(),
+            world_level_down: self.world_level_down,
+            world_level_time: self.world_level_time,
+        }
+    }
+
+    pub fn max_level() -> i32 {
+        player_level_data::iter()
+            .map(|d| d.level)
+            .max()
+            .unwrap_or(1)
+    }
+
+    // Exp beyond max level is dropped
+    pub fn add_exp(&mut self, exp: i32) {
+        self.exp = self.exp.saturating_add(exp.max(0));
+
+        while let Some(need_exp) = self.level_data().map(|d| d.need_exp) {
+            if self.level >= Self::max_level() || self.exp < need_exp {
+                break;
+            }
+
+            self.exp -= need_exp;
+            self.level += 1;
+        }
+
+        if self.level >= Self::max_level() {
+            self.exp = 0;
+        }
+    }
+
+    pub fn set_level(&mut self, level: i32) {
+        self.level = level.clamp(1, Self::max_level());
+        self.exp = 0;
+    }
+
+    // World level derived from account level
+    pub fn origin_world_level(&self) -> i32 {
+        self.level_data().map(|d| d.world_level).unwrap_or_default()
+    }
+
+    pub fn cur_world_level(&self) -> i32 {
+        match self.world_level_down {
+            true => (self.origin_world_level() - 1).max(0),
+            false => self.origin_world_level(),
+        }
+    }
+
+    pub fn monster_level(&self) -> i32 {
+        let world_level = self.cur_world_level();
+        world_level_data::iter()
+            .find(|d| d.level == world_level)
+            .map(|d| d.monster_level)
+            .unwrap_or(1)
+    }
+
+    pub fn world_level_down(&mut self, now: i64) -> Result<(), WorldLevelError> {
+        if self.world_level_down {
+            return Err(WorldLevelError::AlreadyDown);
         }
+
+        if self.origin_world_level() == 0 {
+            return Err(WorldLevelError::Min);
+        }
+
+        self.check_world_level_cd(now)?;
+        self.world_level_down = true;
+        self.world_level_time = now;
+        Ok(())
+    }
+
+    pub fn world_level_regain(&mut self, now: i64) -> Result<(), WorldLevelError> {
+        if !self.world_level_down {
+            return Err(WorldLevelError::NotDown);
+        }
+
+        self.check_world_level_cd(now)?;
+        self.world_level_down = false;
+        self.world_level_time = now;
+        Ok(())
+    }
+
+    fn check_world_level_cd(&self, now: i64) -> Result<(), WorldLevelError> {
+        let available_time = self.world_level_time + WORLD_LEVEL_CD;
+        match now < available_time {
+            true => Err(WorldLevelError::Cooldown(available_time)),
+            false => Ok(()),
+        }
+    }
+
+    fn level_data(&self) -> Option<&'static PlayerLevelData> {
+        player_level_data::iter().find(|d| d.level == self.level)
+    }
+
+    fn build_level_attrs(&self) -> Vec<PlayerAttr> {
+        vec![
+            build_int_attr(PlayerAttrKey::Level, self.level),
+            build_int_attr(PlayerAttrKey::Exp, self.exp),
+            build_int_attr(PlayerAttrKey::OriginWorldLevel, self.origin_world_level()),
+            build_int_attr(PlayerAttrKey::CurWorldLevel, self.cur_world_level()),
+            build_int_attr(
+                PlayerAttrKey::WorldLevelTimeStamp,
+                self.world_level_time as i32,
+            ),
+        ]
     }
 }
 
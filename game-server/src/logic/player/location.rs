@@ -3,6 +3,7 @@ use shorekeeper_protocol::PlayerLocationData;
 
 use crate::logic::math::{Transform, Vector3f};
 
+#[derive(Clone)]
 pub struct PlayerLocation {
     pub instance_id: i32,
     pub position: Transform,
@@ -18,6 +19,37 @@ impl PlayerLocation {
         }
     }
 
+    // RecoverWorldLocation of instance data: [instance_id, x, y, z, roll, pitch, yaw]
+    pub fn from_recover_location(data: &[i32]) -> Option<Self> {
+        let &[instance_id, x, y, z, roll, pitch, yaw] = data else {
+            return None;
+        };
+
+        (instance_id != 0).then(|| Self {
+            instance_id,
+            position: Transform {
+                position: Vector3f {
+                    x: x as f32 / 100.0,
+                    y: y as f32 / 100.0,
+                    z: z as f32 / 100.0,
+                },
+                rotation: Vector3f {
+                    x: pitch as f32,
+                    y: yaw as f32,
+                    z: roll as f32,
+                },
+            },
+        })
+    }
+
+    // Level entities are grouped by map, several instances may share one
+    pub fn map_id(&self) -> i32 {
+        instance_dungeon_data::iter()
+            .find(|d| d.id == self.instance_id)
+            .map(|d| d.map_config_id)
+            .unwrap_or(self.instance_id)
+    }
+
     pub fn build_save_data(&self) -> PlayerLocationData {
         PlayerLocationData {
             instance_id: self.instance_id,
@@ -1,19 +1,21 @@
 use common::time_util;
 use shorekeeper_protocol::{
-    EEntityType, EntityState, ERemoveEntityType, EntityAddNotify, EntityConfigType, EntityPb, EntityRemoveInfo,
+    EEntityType, EnergyUpdateNotify, EntityState, ERemoveEntityType, EntityAddNotify, EntityConfigType, EntityPb, EntityRemoveInfo,
     EntityRemoveNotify, FightFormationNotifyInfo, FightRoleInfo, FightRoleInfos, FormationRoleInfo,
-    GroupFormation, ItemPkgOpenNotify, LivingStatus, PbGetRoleListNotify, PlayerBasicData,
-    PlayerFightFormations, PlayerRoleData, PlayerSaveData, ProtocolUnit, UpdateFormationNotify,
-    UpdateGroupFormationNotify,
+    GroupFormation, ItemObtainNotify, ItemPkgOpenNotify, ItemRewardNotify, LivingStatus,
+    NormalItemRemoveNotify, NormalItemUpdateNotify, PEl2, PbGetRoleListNotify, PhantomItemAddNotify,
+    PhantomItemRemoveNotify, PlayerBasicData, PlayerBuffData, PlayerFightFormations, PlayerRoleData,
+    PlayerSaveData, ProtocolUnit, RewardItemInfo, TraceQuestNotify, UpdateFormationNotify,
+    UpdateGroupFormationNotify, WeaponItemAddNotify, WeaponItemRemoveNotify,
 };
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use std::sync::Arc;
 
 use crate::logic::{
     components::{
-        Attribute, EntityConfig, Equip, Movement, OwnerPlayer, PlayerEntityMarker, Position,
+        Attribute, Buff, EntityConfig, Equip, Movement, OwnerPlayer, PlayerEntityMarker, Position,
         Visibility, VisionSkill,
     },
     ecs::component::ComponentContainer,
@@ -22,24 +24,59 @@ use crate::session::Session;
 
 use super::{
     ecs::world::World,
+    event_bus::{self, GameEvent},
     role::{Role, RoleFormation},
+    utils::quest_util,
 };
 
 mod basic_info;
 mod explore_tools;
 mod in_world_player;
 mod location;
+mod phantom;
+mod player_achievement;
+mod player_calabash;
+mod player_chat;
+mod player_crafting;
 mod player_func;
+mod player_gacha;
+mod player_guide;
+mod player_instance;
+mod player_inventory;
+mod player_mail;
+mod player_map;
+mod player_quest;
+mod player_resource;
+mod player_shop;
+mod player_social;
+mod player_teleport;
+mod weapon;
 
 use crate::create_player_entity_pb;
 use crate::logic::ecs::world::WorldEntity;
-use crate::logic::player::basic_info::PlayerBasicInfo;
 use crate::logic::player::explore_tools::ExploreTools;
-use crate::logic::player::location::PlayerLocation;
 use crate::logic::player::player_func::PlayerFunc;
+pub use basic_info::PlayerBasicInfo;
+pub use player_achievement::{AchievementError, PlayerAchievement};
+pub use player_calabash::PlayerCalabash;
+pub use player_crafting::{CraftType, PlayerCrafting};
+pub use player_chat::{ChatError, ChatEvent, ChatMessage, PlayerChat, WorldChatMessage};
+pub use player_gacha::PlayerGacha;
+pub use player_guide::PlayerGuide;
+pub use player_instance::PlayerInstance;
+pub use player_inventory::{InventoryChange, InventoryError, ItemUsage, PlayerInventory};
+pub use player_mail::{Mail, PlayerMail};
+pub use player_map::PlayerMap;
+pub use player_quest::{PlayerQuest, QuestError};
+pub use player_resource::PlayerResource;
+pub use player_shop::PlayerShop;
+pub use player_social::{
+    Friend, FriendApplication, PlayerSocial, SocialError, SocialEvent, SocialPlayer,
+};
+pub use location::PlayerLocation;
+pub use player_teleport::PlayerTeleport;
 pub use in_world_player::InWorldPlayer;
-use shorekeeper_data::base_property_data;
-use shorekeeper_data::role_info_data;
+use shorekeeper_data::{role_info_data, DropPackageData};
 use shorekeeper_protocol::message::Message;
 
 pub struct Player {
@@ -52,10 +89,27 @@ pub struct Player {
     pub location: PlayerLocation,
     pub func: PlayerFunc,
     pub explore_tools: ExploreTools,
+    pub inventory: PlayerInventory,
+    pub gacha: PlayerGacha,
+    pub chat: PlayerChat,
+    pub teleport: PlayerTeleport,
+    pub instance: PlayerInstance,
+    pub quest: PlayerQuest,
+    pub guide: PlayerGuide,
+    pub achievement: PlayerAchievement,
+    pub map: PlayerMap,
+    pub crafting: PlayerCrafting,
+    pub calabash: PlayerCalabash,
+    pub shop: PlayerShop,
+    pub resource: PlayerResource,
+    pub mail: PlayerMail,
+    pub social: PlayerSocial,
     // Runtime
     pub world: Rc<RefCell<World>>,
     pub last_save_time: u64,
     pub quadrant_id: u64,
+    // Buff item id -> time its cooldown ends
+    pub buff_item_cd: HashMap<i32, i64>,
 }
 
 impl Player {
@@ -65,14 +119,23 @@ impl Player {
         }
 
         self.ensure_basic_unlock_func();
+        self.ensure_role_weapons();
     }
 
     pub fn notify_general_data(&self) {
         self.notify(self.basic_info.build_notify());
+        self.notify_energy_update(&[]);
         self.notify(self.func.build_func_open_notify());
         self.notify(self.build_role_list_notify());
+        self.notify(self.mail.build_mail_infos_notify());
+        self.notify(self.chat.build_mute_list_notify());
         self.notify(self.explore_tools.build_explore_tool_all_notify());
         self.notify(self.explore_tools.build_roulette_update_notify());
+        self.notify(self.instance.build_inst_data_notify());
+        self.notify(self.quest.build_quest_list_notify());
+        self.notify(TraceQuestNotify {
+            quest_id: self.quest.trace_quest_id(),
+        });
 
         self.notify(ItemPkgOpenNotify {
             open_pkg: (0..8).collect(),
@@ -94,8 +157,8 @@ impl Player {
         self.role_list.insert(role.role_id, role);
 
         let required_role_ids: Vec<i32> = role_info_data::iter()
-            .filter(|role_info| role_info.role_type == 1)
-            .map(|role_info| role_info.id)
+            .filter(|(_, role_info)| role_info.role_type == 1)
+            .map(|(&role_id, _)| role_id)
             .collect();
         let formation = vec![1603, 1504, 1505];
 
@@ -132,6 +195,21 @@ impl Player {
         self.func.unlock(10026); // explore tools
     }
 
+    // Every role should own a weapon instance in inventory,
+    // roles created before weapons were persisted get their initial weapon
+    fn ensure_role_weapons(&mut self) {
+        for role in self.role_list.values_mut() {
+            match self.inventory.get_role_weapon(role.role_id) {
+                Some(weapon) => role.equip_weapon = weapon.id,
+                None if role.equip_weapon != 0 => {
+                    self.inventory
+                        .add_role_weapon(role.equip_weapon, role.role_id);
+                }
+                None => (),
+            }
+        }
+    }
+
     fn ensure_current_formation(&mut self) {
         // If the list off formation is empty, add a default formation
         if self.formation_list.is_empty() {
@@ -190,7 +268,8 @@ impl Player {
             world,
             self.basic_info.id,
             self.location.position.clone(),
-            self.explore_tools
+            self.explore_tools,
+            self.inventory
         )
     }
 
@@ -283,6 +362,18 @@ impl Player {
     pub fn load_from_save(save_data: PlayerSaveData) -> Self {
         let role_data = save_data.role_data.unwrap_or_default();
 
+        // Session ended inside instance, player is returned to the world
+        let mut instance = save_data
+            .instance_data
+            .map(PlayerInstance::load_from_save)
+            .unwrap_or_default();
+        let location = instance.leave().unwrap_or_else(|| {
+            save_data
+                .location_data
+                .map(PlayerLocation::load_from_save)
+                .unwrap_or_default()
+        });
+
         Self {
             session: None,
             basic_info: PlayerBasicInfo::load_from_save(
@@ -299,10 +390,7 @@ impl Player {
                 .map(|(k, v)| (k, RoleFormation::load_from_save(v)))
                 .collect(),
             cur_formation_id: role_data.cur_formation_id,
-            location: save_data
-                .location_data
-                .map(PlayerLocation::load_from_save)
-                .unwrap_or_default(),
+            location,
             func: save_data
                 .func_data
                 .map(PlayerFunc::load_from_save)
@@ -311,9 +399,66 @@ impl Player {
                 .explore_tools_data
                 .map(ExploreTools::load_from_save)
                 .unwrap_or_default(),
+            inventory: save_data
+                .inventory_data
+                .map(PlayerInventory::load_from_save)
+                .unwrap_or_default(),
+            gacha: save_data
+                .gacha_data
+                .map(PlayerGacha::load_from_save)
+                .unwrap_or_default(),
+            chat: save_data
+                .chat_data
+                .map(PlayerChat::load_from_save)
+                .unwrap_or_default(),
+            teleport: save_data
+                .teleport_data
+                .map(PlayerTeleport::load_from_save)
+                .unwrap_or_default(),
+            instance,
+            quest: save_data
+                .quest_data
+                .map(PlayerQuest::load_from_save)
+                .unwrap_or_default(),
+            guide: save_data
+                .guide_data
+                .map(PlayerGuide::load_from_save)
+                .unwrap_or_default(),
+            achievement: save_data
+                .achievement_data
+                .map(PlayerAchievement::load_from_save)
+                .unwrap_or_default(),
+            map: save_data
+                .map_data
+                .map(PlayerMap::load_from_save)
+                .unwrap_or_default(),
+            crafting: save_data
+                .crafting_data
+                .map(PlayerCrafting::load_from_save)
+                .unwrap_or_default(),
+            calabash: save_data
+                .calabash_data
+                .map(PlayerCalabash::load_from_save)
+                .unwrap_or_default(),
+            shop: save_data
+                .shop_data
+                .map(PlayerShop::load_from_save)
+                .unwrap_or_default(),
+            resource: save_data
+                .resource_data
+                .map(PlayerResource::load_from_save)
+                .unwrap_or_default(),
+            // Loaded separately from t_player_mail and social tables,
+            // private chat history is set the same way
+            mail: PlayerMail::default(),
+            social: PlayerSocial::default(),
             world: Rc::new(RefCell::new(World::new())),
             last_save_time: time_util::unix_timestamp(),
             quadrant_id: 0,
+            buff_item_cd: save_data
+                .buff_data
+                .map(|data| data.buff_item_cd)
+                .unwrap_or_default(),
         }
     }
 
@@ -336,6 +481,177 @@ impl Player {
             location_data: Some(self.location.build_save_data()),
             func_data: Some(self.func.build_save_data()),
             explore_tools_data: Some(self.explore_tools.build_save_data()),
+            inventory_data: Some(self.inventory.build_save_data()),
+            gacha_data: Some(self.gacha.build_save_data()),
+            chat_data: Some(self.chat.build_save_data()),
+            teleport_data: Some(self.teleport.build_save_data()),
+            instance_data: Some(self.instance.build_save_data()),
+            quest_data: Some(self.quest.build_save_data()),
+            guide_data: Some(self.guide.build_save_data()),
+            achievement_data: Some(self.achievement.build_save_data()),
+            map_data: Some(self.map.build_save_data()),
+            crafting_data: Some(self.crafting.build_save_data()),
+            calabash_data: Some(self.calabash.build_save_data()),
+            shop_data: Some(self.shop.build_save_data()),
+            resource_data: Some(self.resource.build_save_data()),
+            buff_data: Some(PlayerBuffData {
+                buff_item_cd: self.buff_item_cd.clone(),
+            }),
+        }
+    }
+
+    // Returns false if role is already owned
+    pub fn add_role(&mut self, role_id: i32) -> bool {
+        if self.role_list.contains_key(&role_id) {
+            return false;
+        }
+
+        let role = Role::new(role_id);
+        let mut change = InventoryChange::default();
+        if role.equip_weapon != 0 {
+            change
+                .added_weapons
+                .push(self.inventory.add_role_weapon(role.equip_weapon, role_id));
+        }
+
+        self.role_list.insert(role_id, role);
+        self.notify_inventory_change(&change);
+        event_bus::emit(self, GameEvent::RoleObtained { role_id });
+        true
+    }
+
+    pub fn add_items(&mut self, items: &[ItemUsage]) -> Result<(), InventoryError> {
+        let change = self.inventory.add_items(items)?;
+        self.notify_inventory_change(&change);
+
+        self.notify(ItemObtainNotify {
+            items: change.obtained,
+            ..Default::default()
+        });
+
+        Ok(())
+    }
+
+    pub fn add_drop_package(&mut self, drop: &DropPackageData) -> Result<(), InventoryError> {
+        let items = drop
+            .drop_preview
+            .iter()
+            .map(|(&id, &quantity)| ItemUsage { id, quantity })
+            .collect::<Vec<_>>();
+
+        self.add_reward_items(drop.id, &items)
+    }
+
+    pub fn add_reward_items(
+        &mut self,
+        drop_id: i32,
+        items: &[ItemUsage],
+    ) -> Result<(), InventoryError> {
+        let change = self.inventory.add_items(items)?;
+        self.notify_inventory_change(&change);
+
+        self.notify(ItemRewardNotify {
+            drop_id,
+            reward_items: HashMap::from([(
+                0,
+                PEl2 {
+                    item_list: change
+                        .obtained
+                        .iter()
+                        .map(|item| RewardItemInfo {
+                            show_plan_id: 0,
+                            item_id: item.id,
+                            count: item.count,
+                            incr_id: item.incr_id,
+                        })
+                        .collect(),
+                },
+            )]),
+            ..Default::default()
+        });
+
+        Ok(())
+    }
+
+    pub fn consume_items(&mut self, items: &[ItemUsage]) -> Result<(), InventoryError> {
+        let change = self.inventory.consume_items(items)?;
+        self.notify_inventory_change(&change);
+
+        Ok(())
+    }
+
+    // Notifies all energies if no types are specified
+    pub fn notify_energy_update(&self, energy_types: &[i32]) {
+        self.notify(EnergyUpdateNotify {
+            update_info: self
+                .resource
+                .build_energy_info_list(energy_types, time_util::unix_timestamp() as i64),
+        });
+    }
+
+    // Account exp from quests and instances, level gated quests are refreshed on level up
+    pub fn add_exp(&mut self, exp: i32) {
+        let old_level = self.basic_info.level;
+        self.basic_info.add_exp(exp);
+        self.on_level_change(old_level);
+    }
+
+    pub fn set_level(&mut self, level: i32) {
+        let old_level = self.basic_info.level;
+        self.basic_info.set_level(level);
+        self.on_level_change(old_level);
+    }
+
+    fn on_level_change(&mut self, old_level: i32) {
+        self.notify(self.basic_info.build_level_notify());
+        if self.basic_info.level != old_level {
+            quest_util::refresh_quests(self);
+        }
+    }
+
+    pub fn notify_inventory_change(&self, change: &InventoryChange) {
+        let (removed, updated): (HashSet<i32>, HashSet<i32>) = change
+            .normal_items
+            .iter()
+            .partition(|&&id| self.inventory.get_item_count(id) == 0);
+
+        if !updated.is_empty() {
+            self.notify(NormalItemUpdateNotify {
+                normal_item_list: self.inventory.build_normal_items(&updated),
+                no_tips: false,
+            });
+        }
+
+        if !removed.is_empty() {
+            self.notify(NormalItemRemoveNotify {
+                normal_item_id_list: removed.into_iter().collect(),
+            });
+        }
+
+        if !change.added_weapons.is_empty() {
+            self.notify(WeaponItemAddNotify {
+                weapon_item_list: self.inventory.build_weapon_items(&change.added_weapons),
+                ..Default::default()
+            });
+        }
+
+        if !change.removed_weapons.is_empty() {
+            self.notify(WeaponItemRemoveNotify {
+                weapon_item_incr_id_list: change.removed_weapons.clone(),
+            });
+        }
+
+        if !change.added_phantoms.is_empty() {
+            self.notify(PhantomItemAddNotify {
+                phantom_item_list: self.inventory.build_phantom_items(&change.added_phantoms),
+                ..Default::default()
+            });
+        }
+
+        if !change.removed_phantoms.is_empty() {
+            self.notify(PhantomItemRemoveNotify {
+                fws: change.removed_phantoms.clone(),
+            });
         }
     }
 
@@ -348,7 +664,7 @@ impl Player {
             role_list: self
                 .role_list
                 .iter()
-                .map(|(_, role)| role.to_protobuf())
+                .map(|(_, role)| role.to_protobuf(&self.inventory))
                 .collect(),
         }
     }
@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+
+use rand::seq::SliceRandom;
+use shorekeeper_data::{
+    phantom_fetter_data, phantom_item_data, phantom_level_data, phantom_main_prop_data,
+    phantom_sub_prop_data, PhantomItemData as PhantomConfData, PhantomMainPropData,
+};
+use shorekeeper_protocol::{
+    EAttributeType, PhantomItem, PhantomItemData, PhantomPropData, PhantomPropInfo,
+};
+
+// A substat is revealed every this many levels
+const SUB_PROP_REVEAL_INTERVAL: i32 = 5;
+// Experience of phantoms used as level up material is partially refunded
+const FODDER_EXP_RATIO: i32 = 75;
+
+pub struct PhantomProp {
+    pub prop_id: i32,
+    pub value: i32,
+}
+
+// Attribute bonus granted by an equipped phantom or its set (fetter)
+pub struct PropBonus {
+    pub attribute_type: EAttributeType,
+    pub is_ratio: bool,
+    pub value: i32,
+}
+
+pub struct PhantomInstance {
+    pub id: i32,
+    pub incr_id: i32,
+    pub func_value: i32,
+    pub role_id: i32,
+    pub level: i32,
+    pub exp: i32,
+    pub main_prop_id: i32,
+    pub sub_props: Vec<PhantomProp>,
+}
+
+impl PhantomInstance {
+    pub fn new(id: i32, incr_id: i32) -> Self {
+        let main_prop_id = phantom_item_data::iter()
+            .find(|d| d.item_id == id)
+            .and_then(|conf| {
+                phantom_main_prop_data::iter()
+                    .filter(|d| d.cost == conf.cost)
+                    .collect::<Vec<_>>()
+                    .choose(&mut rand::thread_rng())
+                    .map(|d| d.id)
+            })
+            .unwrap_or_default();
+
+        Self {
+            id,
+            incr_id,
+            func_value: 0,
+            role_id: 0,
+            level: 0,
+            exp: 0,
+            main_prop_id,
+            sub_props: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn is_phantom(id: i32) -> bool {
+        phantom_item_data::iter().any(|d| d.item_id == id)
+    }
+
+    pub fn conf(&self) -> Option<&'static PhantomConfData> {
+        phantom_item_data::iter().find(|d| d.item_id == self.id)
+    }
+
+    pub fn cost(&self) -> i32 {
+        self.conf().map(|conf| conf.cost).unwrap_or_default()
+    }
+
+    pub fn level_limit(&self) -> i32 {
+        let Some(conf) = self.conf() else {
+            return self.level;
+        };
+
+        phantom_level_data::iter()
+            .filter(|d| d.rarity == conf.rarity)
+            .map(|d| d.level + 1)
+            .max()
+            .unwrap_or(self.level)
+    }
+
+    // Adds experience and levels up until the level limit is reached,
+    // a new substat is revealed on every reveal interval
+    pub fn add_exp(&mut self, exp: i32) {
+        let Some(conf) = self.conf() else {
+            return;
+        };
+
+        let level_limit = self.level_limit();
+        self.exp = self.exp.saturating_add(exp);
+
+        while self.level < level_limit {
+            let Some(required) = phantom_level_data::iter()
+                .find(|d| d.rarity == conf.rarity && d.level == self.level)
+                .map(|d| d.exp)
+            else {
+                break;
+            };
+
+            if self.exp < required {
+                break;
+            }
+
+            self.exp -= required;
+            self.level += 1;
+
+            if self.level % SUB_PROP_REVEAL_INTERVAL == 0 {
+                self.reveal_sub_prop();
+            }
+        }
+
+        if self.level >= level_limit {
+            self.exp = 0;
+        }
+    }
+
+    // Experience granted when this phantom is consumed as level up material
+    pub fn fodder_exp(&self) -> i32 {
+        let Some(conf) = self.conf() else {
+            return 0;
+        };
+
+        let invested = phantom_level_data::iter()
+            .filter(|d| d.rarity == conf.rarity && d.level < self.level)
+            .map(|d| d.exp)
+            .sum::<i32>()
+            + self.exp;
+
+        conf.fodder_exp + invested * FODDER_EXP_RATIO / 100
+    }
+
+    pub fn main_prop_value(&self) -> i32 {
+        self.main_prop()
+            .map(|d| d.base_value + d.growth_value * self.level)
+            .unwrap_or_default()
+    }
+
+    // Bonuses of main and sub props, set bonuses are handled by build_fetter_bonuses
+    pub fn build_prop_bonuses(&self) -> Vec<PropBonus> {
+        let main = self
+            .main_prop()
+            .map(|d| (d.attribute_type, d.is_ratio, self.main_prop_value()));
+        let subs = self.sub_props.iter().filter_map(|prop| {
+            phantom_sub_prop_data::iter()
+                .find(|d| d.id == prop.prop_id)
+                .map(|d| (d.attribute_type, d.is_ratio, prop.value))
+        });
+
+        main.into_iter()
+            .chain(subs)
+            .filter_map(|(attribute_type, is_ratio, value)| {
+                Some(PropBonus {
+                    attribute_type: EAttributeType::try_from(attribute_type).ok()?,
+                    is_ratio,
+                    value,
+                })
+            })
+            .collect()
+    }
+
+    // Set bonuses activated by the number of equipped phantoms of the same fetter group
+    pub fn build_fetter_bonuses<'a>(phantoms: impl Iterator<Item = &'a Self>) -> Vec<PropBonus> {
+        let mut group_counts: HashMap<i32, i32> = HashMap::new();
+        for conf in phantoms.filter_map(Self::conf) {
+            *group_counts.entry(conf.fetter_group_id).or_default() += 1;
+        }
+
+        phantom_fetter_data::iter()
+            .filter(|d| {
+                group_counts
+                    .get(&d.group_id)
+                    .is_some_and(|&count| count >= d.num)
+            })
+            .filter_map(|d| {
+                Some(PropBonus {
+                    attribute_type: EAttributeType::try_from(d.attribute_type).ok()?,
+                    is_ratio: d.is_ratio,
+                    value: d.value,
+                })
+            })
+            .collect()
+    }
+
+    pub fn to_protobuf(&self) -> PhantomItem {
+        PhantomItem {
+            id: self.id,
+            incr_id: self.incr_id,
+            func_value: self.func_value,
+            phantom_level: self.level,
+            phantom_exp: self.exp,
+            phantom_main_prop: self
+                .main_prop()
+                .map(|d| PhantomPropInfo {
+                    phantom_prop_id: d.id,
+                    value: self.main_prop_value(),
+                })
+                .into_iter()
+                .collect(),
+            phantom_sub_prop: self
+                .sub_props
+                .iter()
+                .map(|prop| PhantomPropInfo {
+                    phantom_prop_id: prop.prop_id,
+                    value: prop.value,
+                })
+                .collect(),
+            fetter_group_id: self
+                .conf()
+                .map(|conf| conf.fetter_group_id)
+                .unwrap_or_default(),
+            skin_id: 0,
+        }
+    }
+
+    pub fn load_from_save(data: PhantomItemData) -> Self {
+        Self {
+            id: data.id,
+            incr_id: data.incr_id,
+            func_value: data.func_value,
+            role_id: data.role_id,
+            level: data.level,
+            exp: data.exp,
+            main_prop_id: data.main_prop_id,
+            sub_props: data
+                .sub_props
+                .into_iter()
+                .map(|prop| PhantomProp {
+                    prop_id: prop.prop_id,
+                    value: prop.value,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn build_save_data(&self) -> PhantomItemData {
+        PhantomItemData {
+            id: self.id,
+            incr_id: self.incr_id,
+            func_value: self.func_value,
+            role_id: self.role_id,
+            level: self.level,
+            exp: self.exp,
+            main_prop_id: self.main_prop_id,
+            sub_props: self
+                .sub_props
+                .iter()
+                .map(|prop| PhantomPropData {
+                    prop_id: prop.prop_id,
+                    value: prop.value,
+                })
+                .collect(),
+        }
+    }
+
+    fn main_prop(&self) -> Option<&'static PhantomMainPropData> {
+        phantom_main_prop_data::iter().find(|d| d.id == self.main_prop_id)
+    }
+
+    // Rolls a substat that isn't present on the phantom yet
+    fn reveal_sub_prop(&mut self) {
+        let mut rng = rand::thread_rng();
+        let candidates = phantom_sub_prop_data::iter()
+            .filter(|d| !self.sub_props.iter().any(|prop| prop.prop_id == d.id))
+            .collect::<Vec<_>>();
+
+        let Some(data) = candidates.choose(&mut rng) else {
+            return;
+        };
+
+        if let Some(&value) = data.values.choose(&mut rng) {
+            self.sub_props.push(PhantomProp {
+                prop_id: data.id,
+                value,
+            });
+        }
+    }
+}
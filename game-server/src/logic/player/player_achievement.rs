@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+
+use common::time_util;
+use shorekeeper_data::{
+    achievement_data, achievement_group_data, AchievementConditionData, AchievementData,
+};
+use shorekeeper_protocol::{
+    AchievementCountChangeNotify, AchievementEntry, AchievementGroupEntry, AchievementGroupInfo,
+    AchievementProgress, AchievementSaveData, ErrorCode, PlayerAchievementData,
+};
+
+use crate::logic::event_bus::GameEvent;
+
+#[derive(Default)]
+struct AchievementState {
+    progress: i32,
+    // 0 if not finished yet
+    finish_time: u32,
+    is_receive: bool,
+}
+
+#[derive(Default)]
+pub struct PlayerAchievement {
+    achievements: HashMap<i32, AchievementState>,
+    groups: HashMap<i32, AchievementState>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum AchievementError {
+    #[error("achievement with id {0} doesn't exist")]
+    NotExist(i32),
+    #[error("achievement with id {0} is not finished")]
+    NotFinished(i32),
+    #[error("achievement with id {0} is already finished")]
+    AlreadyFinished(i32),
+    #[error("reward of achievement {0} is already received")]
+    AlreadyReceived(i32),
+    #[error("achievement with id {0} can't be finished by client")]
+    NeedCondition(i32),
+    #[error("achievement group with id {0} doesn't exist")]
+    GroupNotExist(i32),
+    #[error("achievement group with id {0} is not finished")]
+    GroupNotFinished(i32),
+    #[error("reward of achievement group {0} is already received")]
+    GroupAlreadyReceived(i32),
+}
+
+impl From<AchievementError> for ErrorCode {
+    fn from(err: AchievementError) -> Self {
+        match err {
+            AchievementError::NotExist(_) => ErrorCode::AchievementEntryNotExist,
+            AchievementError::NotFinished(_) => ErrorCode::AchievementEntryNotFinish,
+            AchievementError::AlreadyFinished(_) => ErrorCode::AchievementEntryIsFinish,
+            AchievementError::AlreadyReceived(_) => ErrorCode::AchievementEntryIsReceive,
+            AchievementError::NeedCondition(_) => ErrorCode::AchievementEntryNeedCondition,
+            AchievementError::GroupNotExist(_) => ErrorCode::AchievementGroupEntryNotExist,
+            AchievementError::GroupNotFinished(_) => ErrorCode::AchievementGroupEntryNotFinish,
+            AchievementError::GroupAlreadyReceived(_) => ErrorCode::AchievementGroupEntryIsReceive,
+        }
+    }
+}
+
+impl AchievementState {
+    fn load_from_save(data: AchievementSaveData) -> Self {
+        Self {
+            progress: data.progress,
+            finish_time: data.finish_time,
+            is_receive: data.is_receive,
+        }
+    }
+
+    fn build_save_data(&self) -> AchievementSaveData {
+        AchievementSaveData {
+            progress: self.progress,
+            finish_time: self.finish_time,
+            is_receive: self.is_receive,
+        }
+    }
+
+    #[inline]
+    fn is_finished(&self) -> bool {
+        self.finish_time != 0
+    }
+}
+
+impl PlayerAchievement {
+    pub fn load_from_save(data: PlayerAchievementData) -> Self {
+        Self {
+            achievements: data
+                .achievements
+                .into_iter()
+                .map(|(id, state)| (id, AchievementState::load_from_save(state)))
+                .collect(),
+            groups: data
+                .groups
+                .into_iter()
+                .map(|(id, state)| (id, AchievementState::load_from_save(state)))
+                .collect(),
+        }
+    }
+
+    pub fn build_save_data(&self) -> PlayerAchievementData {
+        PlayerAchievementData {
+            achievements: self
+                .achievements
+                .iter()
+                .map(|(&id, state)| (id, state.build_save_data()))
+                .collect(),
+            groups: self
+                .groups
+                .iter()
+                .map(|(&id, state)| (id, state.build_save_data()))
+                .collect(),
+        }
+    }
+
+    // Returns ids of achievements whose progress was changed by the event
+    pub fn add_progress(&mut self, event: &GameEvent) -> Vec<i32> {
+        let mut updated = Vec::new();
+
+        for data in achievement_data::iter() {
+            let amount = Self::progress_of(&data.condition, event);
+            if amount == 0 {
+                continue;
+            }
+
+            let state = self.achievements.entry(data.id).or_default();
+            if state.is_finished() {
+                continue;
+            }
+
+            state.progress = (state.progress + amount).min(data.progress);
+            if state.progress == data.progress {
+                state.finish_time = time_util::unix_timestamp() as u32;
+            }
+
+            updated.push(data.id);
+        }
+
+        updated
+    }
+
+    pub fn finish(&mut self, id: i32) -> Result<(), AchievementError> {
+        let data = Self::get_data(id)?;
+        if !matches!(data.condition, AchievementConditionData::Client) {
+            return Err(AchievementError::NeedCondition(id));
+        }
+
+        let state = self.achievements.entry(id).or_default();
+        if state.is_finished() {
+            return Err(AchievementError::AlreadyFinished(id));
+        }
+
+        state.progress = data.progress;
+        state.finish_time = time_util::unix_timestamp() as u32;
+        Ok(())
+    }
+
+    pub fn is_finished(&self, id: i32) -> bool {
+        self.achievements
+            .get(&id)
+            .is_some_and(AchievementState::is_finished)
+    }
+
+    // Group is finished once every achievement in it is finished, returns true if newly finished
+    pub fn try_finish_group(&mut self, group_id: i32) -> bool {
+        if self
+            .groups
+            .get(&group_id)
+            .is_some_and(AchievementState::is_finished)
+        {
+            return false;
+        }
+
+        if !achievement_data::iter()
+            .filter(|data| data.group_id == group_id)
+            .all(|data| self.is_finished(data.id))
+        {
+            return false;
+        }
+
+        self.groups.entry(group_id).or_default().finish_time = time_util::unix_timestamp() as u32;
+        true
+    }
+
+    // Returns drop id of the reward, doesn't mark it as received
+    pub fn check_receive(&self, id: i32) -> Result<i32, AchievementError> {
+        let data = Self::get_data(id)?;
+        let state = self
+            .achievements
+            .get(&id)
+            .filter(|state| state.is_finished())
+            .ok_or(AchievementError::NotFinished(id))?;
+
+        if state.is_receive {
+            return Err(AchievementError::AlreadyReceived(id));
+        }
+
+        Ok(data.drop_id)
+    }
+
+    // Returns drop id of the reward, doesn't mark it as received
+    pub fn check_receive_group(&self, group_id: i32) -> Result<i32, AchievementError> {
+        let data = achievement_group_data::iter()
+            .find(|data| data.id == group_id)
+            .ok_or(AchievementError::GroupNotExist(group_id))?;
+
+        let state = self
+            .groups
+            .get(&group_id)
+            .filter(|state| state.is_finished())
+            .ok_or(AchievementError::GroupNotFinished(group_id))?;
+
+        if state.is_receive {
+            return Err(AchievementError::GroupAlreadyReceived(group_id));
+        }
+
+        Ok(data.drop_id)
+    }
+
+    pub fn set_received(&mut self, id: i32) {
+        if let Some(state) = self.achievements.get_mut(&id) {
+            state.is_receive = true;
+        }
+    }
+
+    pub fn set_group_received(&mut self, group_id: i32) {
+        if let Some(state) = self.groups.get_mut(&group_id) {
+            state.is_receive = true;
+        }
+    }
+
+    pub fn build_entry(&self, id: i32) -> AchievementEntry {
+        let state = self.achievements.get(&id);
+        AchievementEntry {
+            id,
+            finish_time: state.map(|s| s.finish_time).unwrap_or_default(),
+            is_receive: state.is_some_and(|s| s.is_receive),
+            progress: Some(AchievementProgress {
+                cur_progress: state.map(|s| s.progress).unwrap_or_default(),
+                total_progress: Self::get_data(id)
+                    .map(|data| data.progress)
+                    .unwrap_or_default(),
+            }),
+        }
+    }
+
+    pub fn build_group_entry(&self, group_id: i32) -> AchievementGroupEntry {
+        let state = self.groups.get(&group_id);
+        AchievementGroupEntry {
+            id: group_id,
+            finish_time: state.map(|s| s.finish_time).unwrap_or_default(),
+            is_receive: state.is_some_and(|s| s.is_receive),
+        }
+    }
+
+    pub fn build_group_info_list(&self) -> Vec<AchievementGroupInfo> {
+        achievement_group_data::iter()
+            .map(|group| AchievementGroupInfo {
+                achievement_group_entry: Some(self.build_group_entry(group.id)),
+                achievement_entry_list: self.build_entry_list(group.id),
+            })
+            .collect()
+    }
+
+    pub fn build_entry_list(&self, group_id: i32) -> Vec<AchievementEntry> {
+        achievement_data::iter()
+            .filter(|data| data.group_id == group_id)
+            .map(|data| self.build_entry(data.id))
+            .collect()
+    }
+
+    pub fn build_all_entry_list(&self) -> Vec<AchievementEntry> {
+        achievement_data::iter()
+            .map(|data| self.build_entry(data.id))
+            .collect()
+    }
+
+    pub fn finished_star(&self) -> i32 {
+        achievement_data::iter()
+            .filter(|data| self.is_finished(data.id))
+            .map(|data| data.level)
+            .sum()
+    }
+
+    pub fn finished_count(&self) -> i32 {
+        self.achievements
+            .values()
+            .filter(|state| state.is_finished())
+            .count() as i32
+    }
+
+    pub fn build_count_change_notify(&self) -> AchievementCountChangeNotify {
+        AchievementCountChangeNotify {
+            achievement_finished_star: self.finished_star(),
+            finished_achievement_num: self.finished_count(),
+        }
+    }
+
+    pub fn group_id_of(id: i32) -> Option<i32> {
+        Self::get_data(id).ok().map(|data| data.group_id)
+    }
+
+    fn get_data(id: i32) -> Result<&'static AchievementData, AchievementError> {
+        achievement_data::iter()
+            .find(|data| data.id == id)
+            .ok_or(AchievementError::NotExist(id))
+    }
+
+    fn progress_of(condition: &AchievementConditionData, event: &GameEvent) -> i32 {
+        match (condition, event) {
+            (
+                AchievementConditionData::RoleObtained { role_id },
+                GameEvent::RoleObtained { role_id: obtained },
+            ) if *role_id == 0 || role_id == obtained => 1,
+            (
+                AchievementConditionData::MonsterKilled { config_id },
+                GameEvent::MonsterKilled { config_id: killed },
+            ) if *config_id == 0 || config_id == killed => 1,
+            (
+                AchievementConditionData::TeleportUnlocked { teleporter_id },
+                GameEvent::TeleportUnlocked {
+                    teleporter_id: unlocked,
+                },
+            ) if *teleporter_id == 0 || teleporter_id == unlocked => 1,
+            (
+                AchievementConditionData::ItemCrafted { item_id },
+                GameEvent::ItemCrafted {
+                    item_id: crafted,
+                    count,
+                },
+            ) if *item_id == 0 || item_id == crafted => *count,
+            _ => 0,
+        }
+    }
+}
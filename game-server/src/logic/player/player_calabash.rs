@@ -0,0 +1,253 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use rand::{seq::SliceRandom, Rng};
+use shorekeeper_data::{
+    calabash_level_data, phantom_catch_data, phantom_item_data, CalabashLevelData, PhantomCatchData,
+};
+use shorekeeper_protocol::{
+    CalabashCfg, CalabashDevelopInfo, CalabashMsg, ErrorCode, PlayerCalabashData,
+};
+
+// Catch rates are in ten-thousandths
+const CATCH_RATE_BASE: i32 = 10000;
+
+pub struct PlayerCalabash {
+    level: i32,
+    exp: i32,
+    rewarded_levels: BTreeSet<i32>,
+    // monster_id -> catch count
+    caught_monsters: BTreeMap<i32, i32>,
+}
+
+pub struct CatchResult {
+    pub monster_id: i32,
+    pub phantom_item_id: i32,
+    pub add_exp: i32,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum CalabashError {
+    #[error("calabash level {0} is not reached yet")]
+    LevelNotReached(i32),
+    #[error("reward of calabash level {0} is already taken")]
+    RewardTaken(i32),
+    #[error("calabash level {0} has no reward")]
+    NoReward(i32),
+}
+
+impl From<CalabashError> for ErrorCode {
+    fn from(err: CalabashError) -> Self {
+        match err {
+            CalabashError::LevelNotReached(_) => ErrorCode::ErrCalabashLevelRequest,
+            CalabashError::RewardTaken(_) => ErrorCode::ErrCalabashLevelRewardDone,
+            CalabashError::NoReward(_) => ErrorCode::ErrCalabashLevelConfig,
+        }
+    }
+}
+
+impl Default for PlayerCalabash {
+    fn default() -> Self {
+        Self {
+            level: 1,
+            exp: 0,
+            rewarded_levels: BTreeSet::new(),
+            caught_monsters: BTreeMap::new(),
+        }
+    }
+}
+
+impl PlayerCalabash {
+    pub fn load_from_save(data: PlayerCalabashData) -> Self {
+        Self {
+            level: data.level.max(1),
+            exp: data.exp,
+            rewarded_levels: data.rewarded_levels.into_iter().collect(),
+            caught_monsters: data.caught_monsters.into_iter().collect(),
+        }
+    }
+
+    pub fn build_save_data(&self) -> PlayerCalabashData {
+        PlayerCalabashData {
+            level: self.level,
+            exp: self.exp,
+            rewarded_levels: self.rewarded_levels.iter().copied().collect(),
+            caught_monsters: self
+                .caught_monsters
+                .iter()
+                .map(|(&id, &count)| (id, count))
+                .collect(),
+        }
+    }
+
+    #[inline]
+    pub fn level(&self) -> i32 {
+        self.level
+    }
+
+    #[inline]
+    pub fn exp(&self) -> i32 {
+        self.exp
+    }
+
+    // Rolls capture of a killed monster, echo quality depends on current level
+    // Rolls catch of the killed monster, result is recorded by record_catch once echo is granted
+    pub fn roll_catch(&self, blueprint_type: &str) -> Option<CatchResult> {
+        let data = phantom_catch_data::iter().find(|d| d.blueprint_type == blueprint_type)?;
+
+        let mut rng = rand::thread_rng();
+        if rng.gen_range(0..CATCH_RATE_BASE) >= data.catch_rate {
+            return None;
+        }
+
+        let phantom_item_id = self.roll_phantom(data)?;
+        let add_exp = self
+            .level_data()
+            .and_then(|d| d.catch_gain.get(&data.monster_rarity).copied())
+            .unwrap_or_default();
+
+        Some(CatchResult {
+            monster_id: data.monster_id,
+            phantom_item_id,
+            add_exp,
+        })
+    }
+
+    // Returns true if the monster is caught for the first time
+    pub fn record_catch(&mut self, result: &CatchResult) -> bool {
+        let count = self.caught_monsters.entry(result.monster_id).or_default();
+        *count += 1;
+        let first_catch = *count == 1;
+
+        self.add_exp(result.add_exp);
+        first_catch
+    }
+
+    // Marks reward of the level as taken, returns its drop id
+    pub fn take_level_reward(&mut self, level: i32) -> Result<i32, CalabashError> {
+        if level > self.level {
+            return Err(CalabashError::LevelNotReached(level));
+        }
+
+        if self.rewarded_levels.contains(&level) {
+            return Err(CalabashError::RewardTaken(level));
+        }
+
+        let drop_id = calabash_level_data::iter()
+            .find(|d| d.level == level)
+            .map(|d| d.drop_id)
+            .filter(|&drop_id| drop_id != 0)
+            .ok_or(CalabashError::NoReward(level))?;
+
+        self.rewarded_levels.insert(level);
+        Ok(drop_id)
+    }
+
+    pub fn rewarded_levels(&self) -> Vec<i32> {
+        self.rewarded_levels.iter().copied().collect()
+    }
+
+    pub fn build_msg(&self) -> CalabashMsg {
+        CalabashMsg {
+            level: self.level,
+            exp: self.exp,
+            unlocked_levels: self.rewarded_levels(),
+            unlocked_develop_rewards: self
+                .caught_monsters
+                .keys()
+                .map(|&monster_id| Self::build_develop_info(monster_id))
+                .collect(),
+            identify_guarantee_count: 0,
+        }
+    }
+
+    pub fn build_cfg(&self) -> CalabashCfg {
+        let data = self.level_data();
+        CalabashCfg {
+            level_up_exp: data.map(|d| d.level_up_exp).unwrap_or_default(),
+            level_up_condition: 0,
+            catch_gain: data.map(|d| d.catch_gain.clone()).unwrap_or_default(),
+        }
+    }
+
+    pub fn build_develop_info(monster_id: i32) -> CalabashDevelopInfo {
+        CalabashDevelopInfo {
+            monster_id,
+            unlock_conditions: Vec::new(),
+        }
+    }
+
+    fn level_data(&self) -> Option<&'static CalabashLevelData> {
+        calabash_level_data::iter().find(|d| d.level == self.level)
+    }
+
+    fn is_max_level(&self) -> bool {
+        !calabash_level_data::iter().any(|d| d.level == self.level + 1)
+    }
+
+    fn add_exp(&mut self, exp: i32) {
+        self.exp = self.exp.saturating_add(exp);
+
+        while !self.is_max_level() {
+            let Some(required) = self.level_data().map(|d| d.level_up_exp) else {
+                break;
+            };
+
+            if self.exp < required {
+                break;
+            }
+
+            self.exp -= required;
+            self.level += 1;
+        }
+
+        if self.is_max_level() {
+            self.exp = 0;
+        }
+    }
+
+    // Picks echo of the monster closest to the rolled quality without exceeding it,
+    // the lowest quality available is used as a fallback
+    fn roll_phantom(&self, data: &PhantomCatchData) -> Option<i32> {
+        let mut rng = rand::thread_rng();
+        let weights = &self.level_data()?.quality_weights;
+        let total = weights.values().sum::<i32>();
+        if total <= 0 {
+            return None;
+        }
+
+        let mut roll = rng.gen_range(0..total);
+        let mut qualities = weights.iter().collect::<Vec<_>>();
+        qualities.sort_unstable();
+        let quality = qualities
+            .into_iter()
+            .find(|(_, &weight)| {
+                roll -= weight;
+                roll < 0
+            })
+            .map(|(&quality, _)| quality)?;
+
+        let candidates = data
+            .phantom_item_ids
+            .iter()
+            .filter_map(|&id| {
+                phantom_item_data::iter()
+                    .find(|d| d.item_id == id)
+                    .map(|d| (id, d.rarity))
+            })
+            .collect::<Vec<_>>();
+
+        let rarity = candidates
+            .iter()
+            .map(|&(_, rarity)| rarity)
+            .filter(|&rarity| rarity <= quality)
+            .max()
+            .or_else(|| candidates.iter().map(|&(_, rarity)| rarity).min())?;
+
+        candidates
+            .iter()
+            .filter(|&&(_, r)| r == rarity)
+            .collect::<Vec<_>>()
+            .choose(&mut rng)
+            .map(|&&(id, _)| id)
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::{HashMap, HashSet};
+
+use common::time_util;
+use shorekeeper_protocol::{
+    ChannelChatMessageInfo, ChatContentProto, ChatContentType, ChatMutePlayerListNotify, ErrorCode,
+    PlayerChatData, PrivateChatHistoryContentProto, PrivateChatHistoryNotify,
+};
+
+#[derive(Clone)]
+pub struct ChatMessage {
+    pub msg_id: String,
+    pub sender_id: i32,
+    pub content_type: i32,
+    pub content: String,
+    pub send_time: i64,
+    pub is_offline: bool,
+}
+
+#[derive(Clone)]
+pub struct WorldChatMessage {
+    pub sender_id: i32,
+    pub sender_name: String,
+    pub sender_icon: i32,
+    pub content_type: i32,
+    pub content: String,
+    pub send_time: i64,
+}
+
+// Messages sent by other players, routed to logic thread of the recipient
+pub enum ChatEvent {
+    World(WorldChatMessage),
+    Private(ChatMessage),
+}
+
+// Mute list is saved with player data, private history lives in t_private_chat
+#[derive(Default)]
+pub struct PlayerChat {
+    mute_list: HashSet<i32>,
+    histories: HashMap<i32, Vec<ChatMessage>>,
+    last_send_time_ms: u64,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ChatError {
+    #[error("content length {0} exceeds the limit")]
+    ContentTooLong(usize),
+    #[error("messages are sent too fast")]
+    SendTooFast,
+    #[error("player with id {0} can't be muted")]
+    MuteNotValidId(i32),
+    #[error("player with id {0} is not a friend")]
+    NotFriend(i32),
+    #[error("player with id {0} is blocked")]
+    Blocked(i32),
+    #[error("unknown content type {0}")]
+    InvalidContentType(i32),
+    #[error("mute list is full, can't mute player with id {0}")]
+    MuteListFull(i32),
+}
+
+impl From<ChatError> for ErrorCode {
+    fn from(err: ChatError) -> Self {
+        match err {
+            ChatError::ContentTooLong(_) => ErrorCode::ErrChatContentTooLong,
+            ChatError::SendTooFast => ErrorCode::ErrChatSendTooFast,
+            ChatError::MuteNotValidId(_) => ErrorCode::ErrChatMuteNotValidId,
+            ChatError::NotFriend(_) => ErrorCode::ErrChatNotFriendNorOnline,
+            ChatError::Blocked(_) => ErrorCode::ErrIsBlockedPlayer,
+            // Client has no dedicated error codes for these
+            ChatError::InvalidContentType(_) | ChatError::MuteListFull(_) => {
+                ErrorCode::InvalidRequest
+            }
+        }
+    }
+}
+
+impl ChatMessage {
+    pub fn to_protobuf(&self) -> ChatContentProto {
+        ChatContentProto {
+            sender_uid: self.sender_id,
+            chat_content_type: self.content_type,
+            content: self.content.clone(),
+            offline_msg: self.is_offline,
+            utc_time: self.send_time,
+            msg_id: self.msg_id.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+impl WorldChatMessage {
+    pub fn to_protobuf(&self) -> ChannelChatMessageInfo {
+        ChannelChatMessageInfo {
+            sender_id: self.sender_id,
+            sender_icon: self.sender_icon,
+            sender_name: self.sender_name.clone(),
+            chat_content_type: self.content_type,
+            content: self.content.clone(),
+            send_time: self.send_time,
+            ..Default::default()
+        }
+    }
+}
+
+impl PlayerChat {
+    pub const MAX_CONTENT_LENGTH: usize = 200;
+    pub const MAX_HISTORY_PER_PLAYER: usize = 100;
+    pub const HISTORY_PAGE_SIZE: usize = 20;
+    pub const MAX_MUTE_LIST_SIZE: usize = 50;
+    const MIN_SEND_INTERVAL_MS: u64 = 500;
+
+    pub fn load_from_save(data: PlayerChatData) -> Self {
+        Self {
+            mute_list: data.mute_list.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn build_save_data(&self) -> PlayerChatData {
+        PlayerChatData {
+            mute_list: self.mute_list.iter().copied().collect(),
+        }
+    }
+
+    // History is loaded separately, ordered from oldest to newest message
+    pub fn set_history(&mut self, history: Vec<(i32, ChatMessage)>) {
+        self.histories.clear();
+        for (peer_id, message) in history.into_iter() {
+            self.add_history(peer_id, message);
+        }
+    }
+
+    pub fn add_history(&mut self, peer_id: i32, message: ChatMessage) {
+        let history = self.histories.entry(peer_id).or_default();
+        history.push(message);
+
+        if history.len() > Self::MAX_HISTORY_PER_PLAYER {
+            let excess = history.len() - Self::MAX_HISTORY_PER_PLAYER;
+            history.drain(..excess);
+        }
+    }
+
+    // Validates outgoing message and creates it with unique id
+    pub fn create_message(
+        &mut self,
+        sender_id: i32,
+        content_type: i32,
+        content: &str,
+    ) -> Result<ChatMessage, ChatError> {
+        if ChatContentType::try_from(content_type).is_err() {
+            return Err(ChatError::InvalidContentType(content_type));
+        }
+
+        let length = content.chars().count();
+        if length > Self::MAX_CONTENT_LENGTH {
+            return Err(ChatError::ContentTooLong(length));
+        }
+
+        // Interval also guarantees that message ids of a player never repeat
+        let now = time_util::unix_timestamp_ms();
+        if now < self.last_send_time_ms + Self::MIN_SEND_INTERVAL_MS {
+            return Err(ChatError::SendTooFast);
+        }
+
+        self.last_send_time_ms = now;
+
+        Ok(ChatMessage {
+            msg_id: format!("{sender_id}-{now}"),
+            sender_id,
+            content_type,
+            content: content.to_string(),
+            send_time: (now / 1000) as i64,
+            is_offline: false,
+        })
+    }
+
+    #[inline]
+    pub fn is_muted(&self, player_id: i32) -> bool {
+        self.mute_list.contains(&player_id)
+    }
+
+    pub fn set_mute(
+        &mut self,
+        player_id: i32,
+        target_id: i32,
+        mute: bool,
+    ) -> Result<(), ChatError> {
+        if player_id == target_id {
+            return Err(ChatError::MuteNotValidId(target_id));
+        }
+
+        if mute {
+            if self.mute_list.len() >= Self::MAX_MUTE_LIST_SIZE
+                && !self.mute_list.contains(&target_id)
+            {
+                return Err(ChatError::MuteListFull(target_id));
+            }
+
+            self.mute_list.insert(target_id);
+        } else {
+            self.mute_list.remove(&target_id);
+        }
+
+        Ok(())
+    }
+
+    // Page of history counted from the newest message
+    pub fn build_history(&self, peer_id: i32, start_index: i32) -> PrivateChatHistoryContentProto {
+        let history = self
+            .histories
+            .get(&peer_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let end = history.len().saturating_sub(start_index.max(0) as usize);
+        let start = end.saturating_sub(Self::HISTORY_PAGE_SIZE);
+
+        PrivateChatHistoryContentProto {
+            target_uid: peer_id,
+            chats: history[start..end]
+                .iter()
+                .map(ChatMessage::to_protobuf)
+                .collect(),
+            history_is_end: start == 0,
+            total_nums: history.len() as i32,
+        }
+    }
+
+    pub fn build_history_notify(&self) -> PrivateChatHistoryNotify {
+        PrivateChatHistoryNotify {
+            all_chats: self
+                .histories
+                .keys()
+                .map(|&peer_id| self.build_history(peer_id, 0))
+                .collect(),
+        }
+    }
+
+    pub fn build_mute_list_notify(&self) -> ChatMutePlayerListNotify {
+        ChatMutePlayerListNotify {
+            player_id: self.mute_list.iter().copied().collect(),
+        }
+    }
+}
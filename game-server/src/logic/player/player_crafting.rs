@@ -0,0 +1,398 @@
+use std::collections::{HashMap, HashSet};
+
+use shorekeeper_data::{
+    cook_formula_data, cook_level_data, forge_formula_data, synthesis_formula_data,
+    synthesis_level_data, CookFormulaData, ForgeFormulaData, SynthesisFormulaData,
+};
+use shorekeeper_protocol::{
+    CookerInfo, CookingData, CraftFormulaSaveData, CraftWorkshopData, ErrorCode, OneForgeInfo,
+    OneSynthesisInfo, PlayerCraftingData, SynthesisLevelInfo,
+};
+
+const MAX_CRAFT_COUNT: i32 = 99;
+
+#[derive(Clone, Copy, Debug)]
+pub enum CraftType {
+    Synthesis,
+    Forge,
+    Cook,
+}
+
+// Common view over synthesis, forge and cook formula tables
+pub struct Formula {
+    pub id: i32,
+    pub item_id: i32,
+    pub item_count: i32,
+    pub consume: &'static HashMap<i32, i32>,
+    // 0 if formula is unlocked from the start
+    pub formula_item_id: i32,
+    proficiency: i32,
+    unlock_level: i32,
+}
+
+#[derive(Default)]
+struct FormulaState {
+    count: i32,
+    last_role_id: i32,
+}
+
+struct Workshop {
+    unlocked: HashSet<i32>,
+    formulas: HashMap<i32, FormulaState>,
+    level: i32,
+    proficiency: i32,
+}
+
+#[derive(Default)]
+pub struct PlayerCrafting {
+    synthesis: Workshop,
+    forge: Workshop,
+    cook: Workshop,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum CraftError {
+    #[error("{0:?} formula with id {1} doesn't exist")]
+    FormulaNotExist(CraftType, i32),
+    #[error("{0:?} formula with id {1} is locked")]
+    FormulaLocked(CraftType, i32),
+    #[error("{0:?} formula with id {1} is already unlocked")]
+    FormulaAlreadyUnlocked(CraftType, i32),
+    #[error("{0:?} formula with id {1} requires level {2}")]
+    LevelLimit(CraftType, i32, i32),
+    #[error("invalid {0:?} count {1}")]
+    InvalidCount(CraftType, i32),
+    #[error("{0:?} is already at max level")]
+    MaxLevel(CraftType),
+    #[error("{0:?} proficiency is not enough for the next level")]
+    ProficiencyNotEnough(CraftType),
+}
+
+impl From<CraftError> for ErrorCode {
+    fn from(err: CraftError) -> Self {
+        use CraftType::{Cook, Forge, Synthesis};
+
+        match err {
+            CraftError::FormulaNotExist(Synthesis, _) => ErrorCode::ErrSynthesisConfigNotFound,
+            CraftError::FormulaNotExist(Forge, _) => ErrorCode::ErrForgeConfigNotFound,
+            CraftError::FormulaNotExist(Cook, _) => ErrorCode::ErrCookingFormulaNotFound,
+            CraftError::FormulaLocked(Synthesis, _) => ErrorCode::ErrSynthesisLocked,
+            CraftError::FormulaLocked(Forge, _) => ErrorCode::ErrForgeLocked,
+            CraftError::FormulaLocked(Cook, _) => ErrorCode::ErrCookingFormulaNotFound,
+            CraftError::FormulaAlreadyUnlocked(Synthesis, _) => {
+                ErrorCode::ErrSynthesisFormulaUnlocked
+            }
+            CraftError::FormulaAlreadyUnlocked(Forge, _) => ErrorCode::ErrForgeFormulaUnlocked,
+            CraftError::FormulaAlreadyUnlocked(Cook, _) => ErrorCode::ErrCookFormulaUnlocked,
+            CraftError::LevelLimit(Synthesis, ..) | CraftError::ProficiencyNotEnough(Synthesis) => {
+                ErrorCode::ErrSynthesisLevelLimit
+            }
+            CraftError::LevelLimit(Forge, ..) | CraftError::ProficiencyNotEnough(Forge) => {
+                ErrorCode::ErrForgeLocked
+            }
+            CraftError::LevelLimit(Cook, ..) | CraftError::ProficiencyNotEnough(Cook) => {
+                ErrorCode::ErrCookingLevelLimt
+            }
+            CraftError::InvalidCount(Synthesis, _) => ErrorCode::ErrSynthesisCountLimit,
+            CraftError::InvalidCount(Forge, _) => ErrorCode::ErrForgeCountLimit,
+            CraftError::InvalidCount(Cook, _) => ErrorCode::ErrCookingCount,
+            CraftError::MaxLevel(Synthesis) => ErrorCode::ErrSynthesisLevelNotFound,
+            CraftError::MaxLevel(Forge) => ErrorCode::ErrForgeConfigNotFound,
+            CraftError::MaxLevel(Cook) => ErrorCode::ErrCookingLevelNotFound,
+        }
+    }
+}
+
+impl From<&'static SynthesisFormulaData> for Formula {
+    fn from(data: &'static SynthesisFormulaData) -> Self {
+        Self {
+            id: data.id,
+            item_id: data.item_id,
+            item_count: data.item_count,
+            consume: &data.consume,
+            formula_item_id: data.formula_item_id,
+            proficiency: data.proficiency,
+            unlock_level: data.unlock_level,
+        }
+    }
+}
+
+impl From<&'static ForgeFormulaData> for Formula {
+    fn from(data: &'static ForgeFormulaData) -> Self {
+        Self {
+            id: data.id,
+            item_id: data.item_id,
+            item_count: 1,
+            consume: &data.consume,
+            formula_item_id: data.formula_item_id,
+            proficiency: 0,
+            unlock_level: 0,
+        }
+    }
+}
+
+impl From<&'static CookFormulaData> for Formula {
+    fn from(data: &'static CookFormulaData) -> Self {
+        Self {
+            id: data.id,
+            item_id: data.item_id,
+            item_count: data.item_count,
+            consume: &data.consume,
+            formula_item_id: data.formula_item_id,
+            proficiency: data.proficiency,
+            unlock_level: data.unlock_level,
+        }
+    }
+}
+
+impl Formula {
+    fn all(craft_type: CraftType) -> Vec<Self> {
+        match craft_type {
+            CraftType::Synthesis => synthesis_formula_data::iter().map(Self::from).collect(),
+            CraftType::Forge => forge_formula_data::iter().map(Self::from).collect(),
+            CraftType::Cook => cook_formula_data::iter().map(Self::from).collect(),
+        }
+    }
+
+    fn find(craft_type: CraftType, id: i32) -> Result<Self, CraftError> {
+        Self::all(craft_type)
+            .into_iter()
+            .find(|formula| formula.id == id)
+            .ok_or(CraftError::FormulaNotExist(craft_type, id))
+    }
+}
+
+impl Default for Workshop {
+    fn default() -> Self {
+        Self {
+            unlocked: HashSet::new(),
+            formulas: HashMap::new(),
+            level: 1,
+            proficiency: 0,
+        }
+    }
+}
+
+impl Workshop {
+    fn load_from_save(data: CraftWorkshopData) -> Self {
+        Self {
+            unlocked: data.unlocked_formula_ids.into_iter().collect(),
+            formulas: data
+                .formulas
+                .into_iter()
+                .map(|(id, formula)| {
+                    (
+                        id,
+                        FormulaState {
+                            count: formula.count,
+                            last_role_id: formula.last_role_id,
+                        },
+                    )
+                })
+                .collect(),
+            level: data.level.max(1),
+            proficiency: data.proficiency,
+        }
+    }
+
+    fn build_save_data(&self) -> CraftWorkshopData {
+        CraftWorkshopData {
+            unlocked_formula_ids: self.unlocked.iter().copied().collect(),
+            formulas: self
+                .formulas
+                .iter()
+                .map(|(&id, formula)| {
+                    (
+                        id,
+                        CraftFormulaSaveData {
+                            count: formula.count,
+                            last_role_id: formula.last_role_id,
+                        },
+                    )
+                })
+                .collect(),
+            level: self.level,
+            proficiency: self.proficiency,
+        }
+    }
+
+    fn is_unlocked(&self, formula: &Formula) -> bool {
+        formula.formula_item_id == 0 || self.unlocked.contains(&formula.id)
+    }
+}
+
+impl PlayerCrafting {
+    pub fn load_from_save(data: PlayerCraftingData) -> Self {
+        Self {
+            synthesis: data
+                .synthesis
+                .map(Workshop::load_from_save)
+                .unwrap_or_default(),
+            forge: data.forge.map(Workshop::load_from_save).unwrap_or_default(),
+            cook: data.cook.map(Workshop::load_from_save).unwrap_or_default(),
+        }
+    }
+
+    pub fn build_save_data(&self) -> PlayerCraftingData {
+        PlayerCraftingData {
+            synthesis: Some(self.synthesis.build_save_data()),
+            forge: Some(self.forge.build_save_data()),
+            cook: Some(self.cook.build_save_data()),
+        }
+    }
+
+    pub fn check_craft(
+        &self,
+        craft_type: CraftType,
+        id: i32,
+        count: i32,
+    ) -> Result<Formula, CraftError> {
+        let formula = Formula::find(craft_type, id)?;
+        if !(1..=MAX_CRAFT_COUNT).contains(&count) {
+            return Err(CraftError::InvalidCount(craft_type, count));
+        }
+
+        let workshop = self.workshop(craft_type);
+        if !workshop.is_unlocked(&formula) {
+            return Err(CraftError::FormulaLocked(craft_type, id));
+        }
+
+        if workshop.level < formula.unlock_level {
+            return Err(CraftError::LevelLimit(craft_type, id, formula.unlock_level));
+        }
+
+        Ok(formula)
+    }
+
+    pub fn on_crafted(
+        &mut self,
+        craft_type: CraftType,
+        formula: &Formula,
+        role_id: i32,
+        count: i32,
+    ) {
+        let workshop = self.workshop_mut(craft_type);
+        workshop.proficiency += formula.proficiency * count;
+
+        let state = workshop.formulas.entry(formula.id).or_default();
+        state.count += count;
+        state.last_role_id = role_id;
+    }
+
+    // Returns formula whose unlock item has to be consumed
+    pub fn check_unlock(&self, craft_type: CraftType, id: i32) -> Result<Formula, CraftError> {
+        let formula = Formula::find(craft_type, id)?;
+        match self.workshop(craft_type).is_unlocked(&formula) {
+            true => Err(CraftError::FormulaAlreadyUnlocked(craft_type, id)),
+            false => Ok(formula),
+        }
+    }
+
+    pub fn unlock(&mut self, craft_type: CraftType, id: i32) {
+        self.workshop_mut(craft_type).unlocked.insert(id);
+    }
+
+    // Raises level by one, returns drop id of the level reward
+    // Returns drop id granted for reaching the next level
+    pub fn check_level_up(&self, craft_type: CraftType) -> Result<i32, CraftError> {
+        let workshop = self.workshop(craft_type);
+        let (proficiency, drop_id) = Self::level_data(craft_type, workshop.level + 1)
+            .ok_or(CraftError::MaxLevel(craft_type))?;
+
+        if workshop.proficiency < proficiency {
+            return Err(CraftError::ProficiencyNotEnough(craft_type));
+        }
+
+        Ok(drop_id)
+    }
+
+    pub fn level_up(&mut self, craft_type: CraftType) {
+        self.workshop_mut(craft_type).level += 1;
+    }
+
+    pub fn build_synthesis_info_list(&self) -> Vec<OneSynthesisInfo> {
+        self.unlocked_formulas(CraftType::Synthesis)
+            .map(|(id, state)| OneSynthesisInfo {
+                id,
+                count: state.map(|s| s.count).unwrap_or_default(),
+                last_role_id: state.map(|s| s.last_role_id).unwrap_or_default(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    pub fn build_synthesis_level_info(&self) -> SynthesisLevelInfo {
+        SynthesisLevelInfo {
+            level: self.synthesis.level,
+            total_proficiency: self.synthesis.proficiency,
+        }
+    }
+
+    pub fn build_forge_info_list(&self) -> Vec<OneForgeInfo> {
+        self.unlocked_formulas(CraftType::Forge)
+            .map(|(id, state)| OneForgeInfo {
+                id,
+                last_role_id: state.map(|s| s.last_role_id).unwrap_or_default(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    pub fn build_cooking_data_list(&self) -> Vec<CookingData> {
+        self.unlocked_formulas(CraftType::Cook)
+            .map(|(id, state)| CookingData {
+                id,
+                cook_count: state.map(|s| s.count).unwrap_or_default(),
+                last_role_id: state.map(|s| s.last_role_id).unwrap_or_default(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    pub fn build_cooker_info(&self) -> CookerInfo {
+        CookerInfo {
+            cooking_level: self.cook.level,
+            total_proficiencies: self.cook.proficiency,
+        }
+    }
+
+    fn unlocked_formulas(
+        &self,
+        craft_type: CraftType,
+    ) -> impl Iterator<Item = (i32, Option<&FormulaState>)> {
+        let workshop = self.workshop(craft_type);
+        Formula::all(craft_type)
+            .into_iter()
+            .filter(|formula| workshop.is_unlocked(formula))
+            .map(|formula| (formula.id, workshop.formulas.get(&formula.id)))
+    }
+
+    // Required total proficiency and reward drop id of the level
+    fn level_data(craft_type: CraftType, level: i32) -> Option<(i32, i32)> {
+        match craft_type {
+            CraftType::Synthesis => synthesis_level_data::iter()
+                .find(|data| data.level == level)
+                .map(|data| (data.proficiency, data.drop_id)),
+            CraftType::Cook => cook_level_data::iter()
+                .find(|data| data.level == level)
+                .map(|data| (data.proficiency, data.drop_id)),
+            CraftType::Forge => None,
+        }
+    }
+
+    fn workshop(&self, craft_type: CraftType) -> &Workshop {
+        match craft_type {
+            CraftType::Synthesis => &self.synthesis,
+            CraftType::Forge => &self.forge,
+            CraftType::Cook => &self.cook,
+        }
+    }
+
+    fn workshop_mut(&mut self, craft_type: CraftType) -> &mut Workshop {
+        match craft_type {
+            CraftType::Synthesis => &mut self.synthesis,
+            CraftType::Forge => &mut self.forge,
+            CraftType::Cook => &mut self.cook,
+        }
+    }
+}
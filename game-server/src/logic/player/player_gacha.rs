@@ -0,0 +1,644 @@
+use std::collections::HashMap;
+
+use common::time_util;
+use rand::Rng;
+use shorekeeper_data::{
+    gacha_data, gacha_pool_data, gacha_rule_data, role_info_data, GachaData, GachaPoolData,
+    GachaRuleData,
+};
+use shorekeeper_protocol::{
+    ErrorCode, GachaConsume, GachaInfo, GachaItem, GachaPoolDetail, GachaPoolInfo, GachaRecordData,
+    GachaStateData, PlayerGachaData,
+};
+
+#[derive(Default, Clone)]
+pub struct GachaState {
+    pub today_times: i32,
+    pub total_times: i32,
+    pub last_gacha_day: i64,
+    pub use_pool_id: i32,
+    pub five_star_pity: i32,
+    pub four_star_pity: i32,
+    pub is_up_guaranteed: bool,
+    pub is_four_star_up_guaranteed: bool,
+}
+
+pub struct GachaRecord {
+    pub gacha_id: i32,
+    pub pool_id: i32,
+    pub item_id: i32,
+    pub quality: i32,
+    pub time: i64,
+}
+
+pub struct GachaPull {
+    pub item_id: i32,
+    pub quality: i32,
+}
+
+// Rolled pulls together with gacha state after them, nothing is stored until committed
+pub struct PreparedPulls {
+    pub pulls: Vec<GachaPull>,
+    state: GachaState,
+}
+
+#[derive(Default)]
+pub struct PlayerGacha {
+    states: HashMap<i32, GachaState>,
+    records: Vec<GachaRecord>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GachaError {
+    #[error("gacha with id {0} doesn't exist")]
+    ConfigNotFound(i32),
+    #[error("gacha pool with id {0} doesn't exist")]
+    PoolConfigNotFound(i32),
+    #[error("gacha rule with id {0} doesn't exist")]
+    RuleNotFound(i32),
+    #[error("gacha pool with id {1} doesn't belong to gacha with id {0}")]
+    PoolNotBelongToGacha(i32, i32),
+    #[error("gacha with id {0} is not open")]
+    NotInOpenTime(i32),
+    #[error("gacha pool with id {0} is not open")]
+    PoolNotInOpenTime(i32),
+    #[error("gacha pool with id {0} has no items")]
+    EmptyPool(i32),
+    #[error("gacha with id {0} doesn't support {1} pulls at once")]
+    TimesNonsupport(i32, i32),
+    #[error("gacha with id {0} reached its daily limit")]
+    DailyTimesLimit(i32),
+    #[error("gacha with id {0} reached its total limit")]
+    TotalTimesLimit(i32),
+}
+
+impl From<GachaError> for ErrorCode {
+    fn from(err: GachaError) -> Self {
+        match err {
+            GachaError::ConfigNotFound(_) => ErrorCode::ErrGachaConfigNotFound,
+            GachaError::PoolConfigNotFound(_) => ErrorCode::ErrGachaPoolConfigNotFound,
+            GachaError::EmptyPool(_) => ErrorCode::ErrGachaPoolConfigNotFound,
+            GachaError::RuleNotFound(_) => ErrorCode::ErrGachaRulesNotFound,
+            GachaError::PoolNotBelongToGacha(..) => ErrorCode::ErrGachaPoolNotBelongToGacha,
+            GachaError::NotInOpenTime(_) => ErrorCode::ErrGachaIsNotInOpenTime,
+            GachaError::PoolNotInOpenTime(_) => ErrorCode::ErrGachaPoolIsNotInOpenTime,
+            GachaError::TimesNonsupport(..) => ErrorCode::ErrGachaTimesNonsupport,
+            GachaError::DailyTimesLimit(_) => ErrorCode::ErrGachaDailyTimesLimit,
+            GachaError::TotalTimesLimit(_) => ErrorCode::ErrGachaTotalTimesLimit,
+        }
+    }
+}
+
+impl PlayerGacha {
+    const MAX_RECORDS: usize = 1000;
+    const FOUR_STAR_UP_RATE: i32 = 5000;
+    const RATE_BASE: i32 = 10000;
+    const SECONDS_PER_DAY: i64 = 86400;
+
+    pub fn load_from_save(data: PlayerGachaData) -> Self {
+        Self {
+            states: data
+                .gacha_states
+                .into_iter()
+                .map(|(gacha_id, state)| {
+                    (
+                        gacha_id,
+                        GachaState {
+                            today_times: state.today_times,
+                            total_times: state.total_times,
+                            last_gacha_day: state.last_gacha_day,
+                            use_pool_id: state.use_pool_id,
+                            five_star_pity: state.five_star_pity,
+                            four_star_pity: state.four_star_pity,
+                            is_up_guaranteed: state.is_up_guaranteed,
+                            is_four_star_up_guaranteed: state.is_four_star_up_guaranteed,
+                        },
+                    )
+                })
+                .collect(),
+            records: data
+                .records
+                .into_iter()
+                .map(|record| GachaRecord {
+                    gacha_id: record.gacha_id,
+                    pool_id: record.pool_id,
+                    item_id: record.item_id,
+                    quality: record.quality,
+                    time: record.time,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn build_save_data(&self) -> PlayerGachaData {
+        PlayerGachaData {
+            gacha_states: self
+                .states
+                .iter()
+                .map(|(&gacha_id, state)| {
+                    (
+                        gacha_id,
+                        GachaStateData {
+                            today_times: state.today_times,
+                            total_times: state.total_times,
+                            last_gacha_day: state.last_gacha_day,
+                            use_pool_id: state.use_pool_id,
+                            five_star_pity: state.five_star_pity,
+                            four_star_pity: state.four_star_pity,
+                            is_up_guaranteed: state.is_up_guaranteed,
+                            is_four_star_up_guaranteed: state.is_four_star_up_guaranteed,
+                        },
+                    )
+                })
+                .collect(),
+            records: self
+                .records
+                .iter()
+                .map(|record| GachaRecordData {
+                    gacha_id: record.gacha_id,
+                    pool_id: record.pool_id,
+                    item_id: record.item_id,
+                    quality: record.quality,
+                    time: record.time,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn build_gacha_infos(&self) -> Vec<GachaInfo> {
+        let now = time_util::unix_timestamp() as i64;
+        let today = now / Self::SECONDS_PER_DAY;
+
+        let mut gachas: Vec<&GachaData> = gacha_data::iter()
+            .filter(|gacha| Self::is_open(gacha.begin_time, gacha.end_time, now))
+            .collect();
+        gachas.sort_by_key(|gacha| gacha.id);
+
+        gachas
+            .into_iter()
+            .map(|gacha| {
+                let state = self.states.get(&gacha.id);
+                GachaInfo {
+                    id: gacha.id,
+                    today_times: state
+                        .filter(|s| s.last_gacha_day == today)
+                        .map(|s| s.today_times)
+                        .unwrap_or_default(),
+                    total_times: state.map(|s| s.total_times).unwrap_or_default(),
+                    item_id: gacha.item_id,
+                    gacha_consumes: gacha
+                        .gacha_times
+                        .iter()
+                        .map(|&times| GachaConsume {
+                            times,
+                            consume: times,
+                        })
+                        .collect(),
+                    use_pool_id: Self::use_pool_id(gacha, state),
+                    pools: gacha
+                        .pool_ids
+                        .iter()
+                        .flat_map(|id| gacha_pool_data::iter().find(|p| p.id == *id))
+                        .filter(|pool| Self::is_open(pool.begin_time, pool.end_time, now))
+                        .map(Self::build_pool_info)
+                        .collect(),
+                    begin_time: gacha.begin_time,
+                    end_time: gacha.end_time,
+                    daily_limit_times: gacha.daily_limit_times,
+                    total_limit_times: gacha.total_limit_times,
+                    resources_id: gacha.resources_id.clone(),
+                }
+            })
+            .collect()
+    }
+
+    pub fn set_use_pool(&mut self, gacha_id: i32, pool_id: i32) -> Result<(), GachaError> {
+        let gacha = Self::get_gacha(gacha_id)?;
+        let pool = Self::get_pool(pool_id)?;
+
+        if !gacha.pool_ids.contains(&pool.id) {
+            return Err(GachaError::PoolNotBelongToGacha(gacha_id, pool_id));
+        }
+
+        if !Self::is_open(
+            pool.begin_time,
+            pool.end_time,
+            time_util::unix_timestamp() as i64,
+        ) {
+            return Err(GachaError::PoolNotInOpenTime(pool_id));
+        }
+
+        self.states.entry(gacha_id).or_default().use_pool_id = pool_id;
+        Ok(())
+    }
+
+    // Validates that requested amount of pulls can be made,
+    // returns gacha config together with currently selected pool and its rule
+    pub fn check_gacha(
+        &self,
+        gacha_id: i32,
+        times: i32,
+    ) -> Result<
+        (
+            &'static GachaData,
+            &'static GachaPoolData,
+            &'static GachaRuleData,
+        ),
+        GachaError,
+    > {
+        let gacha = Self::get_gacha(gacha_id)?;
+        let now = time_util::unix_timestamp() as i64;
+
+        if !Self::is_open(gacha.begin_time, gacha.end_time, now) {
+            return Err(GachaError::NotInOpenTime(gacha_id));
+        }
+
+        if !gacha.gacha_times.contains(&times) {
+            return Err(GachaError::TimesNonsupport(gacha_id, times));
+        }
+
+        let state = self.states.get(&gacha_id);
+        let pool = Self::get_pool(Self::use_pool_id(gacha, state))?;
+        if !Self::is_open(pool.begin_time, pool.end_time, now) {
+            return Err(GachaError::PoolNotInOpenTime(pool.id));
+        }
+
+        // Pulls that miss higher rarities fall back to 3-star items
+        if pool.three_star.is_empty() {
+            return Err(GachaError::EmptyPool(pool.id));
+        }
+
+        let rule = gacha_rule_data::iter()
+            .find(|r| r.id == gacha.rule_id)
+            .ok_or(GachaError::RuleNotFound(gacha.rule_id))?;
+
+        let today_times = state
+            .filter(|s| s.last_gacha_day == now / Self::SECONDS_PER_DAY)
+            .map(|s| s.today_times)
+            .unwrap_or_default();
+        if gacha.daily_limit_times != 0 && today_times + times > gacha.daily_limit_times {
+            return Err(GachaError::DailyTimesLimit(gacha_id));
+        }
+
+        let total_times = state.map(|s| s.total_times).unwrap_or_default();
+        if gacha.total_limit_times != 0 && total_times + times > gacha.total_limit_times {
+            return Err(GachaError::TotalTimesLimit(gacha_id));
+        }
+
+        Ok((gacha, pool, rule))
+    }
+
+    // Rolls pulls on already validated gacha, pity and history are updated by commit_pulls
+    pub fn prepare_pulls(
+        &self,
+        gacha: &GachaData,
+        rule: &GachaRuleData,
+        pool: &GachaPoolData,
+        times: i32,
+    ) -> PreparedPulls {
+        let today = time_util::unix_timestamp() as i64 / Self::SECONDS_PER_DAY;
+
+        let mut state = self.states.get(&gacha.id).cloned().unwrap_or_default();
+        if state.last_gacha_day != today {
+            state.last_gacha_day = today;
+            state.today_times = 0;
+        }
+
+        state.today_times += times;
+        state.total_times += times;
+
+        let mut rng = rand::thread_rng();
+        let pulls = (0..times)
+            .map(|_| Self::roll(&mut rng, rule, pool, &mut state))
+            .collect();
+
+        PreparedPulls { pulls, state }
+    }
+
+    pub fn commit_pulls(
+        &mut self,
+        gacha: &GachaData,
+        pool: &GachaPoolData,
+        prepared: PreparedPulls,
+    ) -> Vec<GachaPull> {
+        let now = time_util::unix_timestamp() as i64;
+        self.states.insert(gacha.id, prepared.state);
+
+        self.records
+            .extend(prepared.pulls.iter().map(|pull| GachaRecord {
+                gacha_id: gacha.id,
+                pool_id: pool.id,
+                item_id: pull.item_id,
+                quality: pull.quality,
+                time: now,
+            }));
+
+        if self.records.len() > Self::MAX_RECORDS {
+            let excess = self.records.len() - Self::MAX_RECORDS;
+            self.records.drain(..excess);
+        }
+
+        prepared.pulls
+    }
+
+    pub fn build_pool_detail(pool_id: i32) -> Result<GachaPoolDetail, GachaError> {
+        let pool = Self::get_pool(pool_id)?;
+
+        let build_items = |up: &[i32], items: &[i32], roles: bool| -> Vec<GachaItem> {
+            up.iter()
+                .map(|&item_id| (item_id, true))
+                .chain(items.iter().map(|&item_id| (item_id, false)))
+                .filter(|&(item_id, _)| Self::is_role(item_id) == roles)
+                .map(|(item_id, is_up)| GachaItem {
+                    item_id,
+                    x_sl2: is_up,
+                })
+                .collect()
+        };
+
+        Ok(GachaPoolDetail {
+            text: pool.description.clone(),
+            five_star_roles: build_items(&pool.up_five_star, &pool.five_star, true),
+            five_star_weapons: build_items(&pool.up_five_star, &pool.five_star, false),
+            four_star_roles: build_items(&pool.up_four_star, &pool.four_star, true),
+            four_star_weapons: build_items(&pool.up_four_star, &pool.four_star, false),
+            // Only 3-star list in the protocol, pools have nothing but weapons at this rarity
+            three_star_roles: build_items(&[], &pool.three_star, false),
+        })
+    }
+
+    #[inline]
+    pub fn is_role(item_id: i32) -> bool {
+        role_info_data::get(item_id).is_some()
+    }
+
+    fn roll(
+        rng: &mut impl Rng,
+        rule: &GachaRuleData,
+        pool: &GachaPoolData,
+        state: &mut GachaState,
+    ) -> GachaPull {
+        state.five_star_pity += 1;
+        state.four_star_pity += 1;
+
+        let mut five_star_rate = rule.five_star_rate;
+        if state.five_star_pity >= rule.soft_pity_start {
+            five_star_rate +=
+                (state.five_star_pity - rule.soft_pity_start + 1) * rule.soft_pity_rate;
+        }
+
+        let has_five_star = !pool.up_five_star.is_empty() || !pool.five_star.is_empty();
+        let has_four_star = !pool.up_four_star.is_empty() || !pool.four_star.is_empty();
+        let roll = rng.gen_range(0..Self::RATE_BASE);
+
+        if has_five_star && (state.five_star_pity >= rule.hard_pity || roll < five_star_rate) {
+            state.five_star_pity = 0;
+            let item_id = Self::pick(
+                rng,
+                &pool.up_five_star,
+                &pool.five_star,
+                rule.up_rate,
+                &mut state.is_up_guaranteed,
+            );
+
+            GachaPull {
+                item_id,
+                quality: 5,
+            }
+        } else if has_four_star
+            && (state.four_star_pity >= rule.four_star_hard_pity
+                || roll < five_star_rate + rule.four_star_rate)
+        {
+            state.four_star_pity = 0;
+            let item_id = Self::pick(
+                rng,
+                &pool.up_four_star,
+                &pool.four_star,
+                Self::FOUR_STAR_UP_RATE,
+                &mut state.is_four_star_up_guaranteed,
+            );
+
+            GachaPull {
+                item_id,
+                quality: 4,
+            }
+        } else {
+            GachaPull {
+                item_id: pool.three_star[rng.gen_range(0..pool.three_star.len())],
+                quality: 3,
+            }
+        }
+    }
+
+    // Picks featured item on won 50/50 or active guarantee, losing sets the guarantee
+    fn pick(
+        rng: &mut impl Rng,
+        up: &[i32],
+        items: &[i32],
+        up_rate: i32,
+        is_up_guaranteed: &mut bool,
+    ) -> i32 {
+        let is_up = !up.is_empty()
+            && (items.is_empty()
+                || *is_up_guaranteed
+                || rng.gen_range(0..Self::RATE_BASE) < up_rate);
+
+        if !up.is_empty() {
+            *is_up_guaranteed = !is_up;
+        }
+
+        let list = if is_up { up } else { items };
+        list[rng.gen_range(0..list.len())]
+    }
+
+    fn build_pool_info(pool: &GachaPoolData) -> GachaPoolInfo {
+        GachaPoolInfo {
+            id: pool.id,
+            begin_time: pool.begin_time,
+            end_time: pool.end_time,
+            title: pool.title.clone(),
+            description: pool.description.clone(),
+            ui_type: pool.ui_type,
+            theme_color: pool.theme_color.clone(),
+            show_id_list: pool.show_id_list.clone(),
+            up_list: pool
+                .up_five_star
+                .iter()
+                .chain(pool.up_four_star.iter())
+                .copied()
+                .collect(),
+            preview_id_list: pool.show_id_list.clone(),
+        }
+    }
+
+    fn use_pool_id(gacha: &GachaData, state: Option<&GachaState>) -> i32 {
+        state
+            .map(|s| s.use_pool_id)
+            .filter(|id| gacha.pool_ids.contains(id))
+            .unwrap_or(gacha.default_pool_id)
+    }
+
+    fn get_gacha(gacha_id: i32) -> Result<&'static GachaData, GachaError> {
+        gacha_data::iter()
+            .find(|g| g.id == gacha_id)
+            .ok_or(GachaError::ConfigNotFound(gacha_id))
+    }
+
+    fn get_pool(pool_id: i32) -> Result<&'static GachaPoolData, GachaError> {
+        gacha_pool_data::iter()
+            .find(|p| p.id == pool_id)
+            .ok_or(GachaError::PoolConfigNotFound(pool_id))
+    }
+
+    #[inline]
+    fn is_open(begin_time: i64, end_time: i64, now: i64) -> bool {
+        (begin_time == 0 || now >= begin_time) && (end_time == 0 || now < end_time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use shorekeeper_data::{GachaPoolData, GachaRuleData};
+
+    use super::{GachaState, PlayerGacha};
+
+    const UP_FIVE_STAR: i32 = 1;
+    const FIVE_STAR: i32 = 2;
+    const UP_FOUR_STAR: i32 = 3;
+    const FOUR_STAR: i32 = 4;
+    const THREE_STAR: i32 = 5;
+
+    // Random rates are disabled, pulls are decided by pity only
+    fn build_rule() -> GachaRuleData {
+        GachaRuleData {
+            id: 1,
+            five_star_rate: 0,
+            four_star_rate: 0,
+            soft_pity_start: 1000,
+            soft_pity_rate: 0,
+            hard_pity: 80,
+            four_star_hard_pity: 10,
+            up_rate: 5000,
+        }
+    }
+
+    fn build_pool() -> GachaPoolData {
+        GachaPoolData {
+            id: 1,
+            gacha_id: 1,
+            title: String::new(),
+            description: String::new(),
+            ui_type: 0,
+            theme_color: String::new(),
+            show_id_list: Vec::new(),
+            up_five_star: vec![UP_FIVE_STAR],
+            five_star: vec![FIVE_STAR],
+            up_four_star: vec![UP_FOUR_STAR],
+            four_star: vec![FOUR_STAR],
+            three_star: vec![THREE_STAR],
+            begin_time: 0,
+            end_time: 0,
+        }
+    }
+
+    fn roll_qualities(
+        rule: &GachaRuleData,
+        pool: &GachaPoolData,
+        state: &mut GachaState,
+        times: usize,
+    ) -> Vec<i32> {
+        let mut rng = StdRng::seed_from_u64(0);
+        (0..times)
+            .map(|_| PlayerGacha::roll(&mut rng, rule, pool, state).quality)
+            .collect()
+    }
+
+    #[test]
+    fn five_star_on_hard_pity() {
+        let mut state = GachaState::default();
+        let qualities = roll_qualities(&build_rule(), &build_pool(), &mut state, 80);
+
+        assert!(qualities[..79].iter().all(|&quality| quality < 5));
+        assert_eq!(qualities[79], 5);
+        assert_eq!(state.five_star_pity, 0);
+    }
+
+    #[test]
+    fn four_star_on_hard_pity() {
+        let mut state = GachaState::default();
+        let qualities = roll_qualities(&build_rule(), &build_pool(), &mut state, 30);
+
+        for (index, quality) in qualities.into_iter().enumerate() {
+            let expected = if (index + 1) % 10 == 0 { 4 } else { 3 };
+            assert_eq!(quality, expected, "pull {}", index + 1);
+        }
+        assert_eq!(state.four_star_pity, 0);
+    }
+
+    #[test]
+    fn pity_is_kept_between_rolls() {
+        let mut state = GachaState {
+            five_star_pity: 79,
+            ..Default::default()
+        };
+
+        let qualities = roll_qualities(&build_rule(), &build_pool(), &mut state, 1);
+        assert_eq!(qualities, vec![5]);
+    }
+
+    #[test]
+    fn soft_pity_raises_five_star_rate() {
+        let rule = GachaRuleData {
+            soft_pity_start: 5,
+            soft_pity_rate: 10000,
+            ..build_rule()
+        };
+
+        let mut state = GachaState::default();
+        let qualities = roll_qualities(&rule, &build_pool(), &mut state, 5);
+
+        assert!(qualities[..4].iter().all(|&quality| quality < 5));
+        assert_eq!(qualities[4], 5);
+    }
+
+    #[test]
+    fn lost_up_roll_guarantees_next_one() {
+        // Every pull is a 5-star and 50/50 is always lost
+        let rule = GachaRuleData {
+            hard_pity: 1,
+            up_rate: 0,
+            ..build_rule()
+        };
+        let pool = build_pool();
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut state = GachaState::default();
+        let items: Vec<i32> = (0..4)
+            .map(|_| PlayerGacha::roll(&mut rng, &rule, &pool, &mut state).item_id)
+            .collect();
+
+        assert_eq!(
+            items,
+            vec![FIVE_STAR, UP_FIVE_STAR, FIVE_STAR, UP_FIVE_STAR]
+        );
+    }
+
+    #[test]
+    fn missing_rarity_falls_back_to_three_star() {
+        let pool = GachaPoolData {
+            up_five_star: Vec::new(),
+            five_star: Vec::new(),
+            ..build_pool()
+        };
+
+        let mut state = GachaState {
+            five_star_pity: 100,
+            ..Default::default()
+        };
+        let qualities = roll_qualities(&build_rule(), &pool, &mut state, 1);
+
+        assert_eq!(qualities, vec![3]);
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::{HashMap, HashSet};
+
+use common::time_util;
+use shorekeeper_data::{guide_group_data, tutorial_data, GuideGroupData, TutorialData};
+use shorekeeper_protocol::{ErrorCode, PlayerGuideData, TutorialInfo, TutorialSaveData};
+
+struct Tutorial {
+    create_time: u32,
+    get_award: bool,
+}
+
+#[derive(Default)]
+pub struct PlayerGuide {
+    finished_groups: HashSet<i32>,
+    // Group triggered by the client which is being played, not saved
+    cur_group: Option<i32>,
+    tutorials: HashMap<i32, Tutorial>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum GuideError {
+    #[error("guide group {0} doesn't exist")]
+    GroupNotExist(i32),
+    #[error("guide group {0} is already finished")]
+    GroupAlreadyFinished(i32),
+    #[error("pre groups of guide group {0} aren't finished")]
+    PreGroupNotFinished(i32),
+    #[error("guide group {0} isn't the triggered one")]
+    GroupNotTriggered(i32),
+    #[error("tutorial with id {0} doesn't exist")]
+    TutorialNotExist(i32),
+    #[error("tutorial with id {0} is already unlocked")]
+    TutorialAlreadyUnlocked(i32),
+    #[error("tutorial with id {0} is not unlocked")]
+    TutorialNotUnlocked(i32),
+    #[error("reward of tutorial {0} is already received")]
+    TutorialAlreadyReceived(i32),
+}
+
+impl From<GuideError> for ErrorCode {
+    fn from(err: GuideError) -> Self {
+        match err {
+            GuideError::GroupNotExist(_) => ErrorCode::GuideConfigNotFind,
+            GuideError::GroupAlreadyFinished(_) => ErrorCode::GuideIsFinish,
+            GuideError::PreGroupNotFinished(_) => ErrorCode::GuidePerIsNotFinish,
+            GuideError::GroupNotTriggered(_) => ErrorCode::GuideGroupIdNoMatch,
+            GuideError::TutorialNotExist(_) => ErrorCode::GuideTutorialConfigNotFind,
+            GuideError::TutorialAlreadyUnlocked(_) => ErrorCode::GuideTutorialIsUnlock,
+            GuideError::TutorialNotUnlocked(_) => ErrorCode::GuideTutorialNotUnlock,
+            GuideError::TutorialAlreadyReceived(_) => ErrorCode::GuideTutorialIsReceive,
+        }
+    }
+}
+
+impl PlayerGuide {
+    pub fn load_from_save(data: PlayerGuideData) -> Self {
+        Self {
+            finished_groups: data.finished_group_ids.into_iter().collect(),
+            cur_group: None,
+            tutorials: data
+                .tutorials
+                .into_iter()
+                .map(|(id, tutorial)| {
+                    (
+                        id,
+                        Tutorial {
+                            create_time: tutorial.create_time,
+                            get_award: tutorial.get_award,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn build_save_data(&self) -> PlayerGuideData {
+        PlayerGuideData {
+            finished_group_ids: self.finished_groups.iter().copied().collect(),
+            tutorials: self
+                .tutorials
+                .iter()
+                .map(|(&id, tutorial)| {
+                    (
+                        id,
+                        TutorialSaveData {
+                            create_time: tutorial.create_time,
+                            get_award: tutorial.get_award,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn finished_group_ids(&self) -> impl Iterator<Item = i32> + '_ {
+        self.finished_groups.iter().copied()
+    }
+
+    pub fn trigger_group(&mut self, group_id: i32) -> Result<(), GuideError> {
+        let data = self.check_group(group_id)?;
+        if !data
+            .pre_group_ids
+            .iter()
+            .all(|id| self.finished_groups.contains(id))
+        {
+            return Err(GuideError::PreGroupNotFinished(group_id));
+        }
+
+        self.cur_group = Some(group_id);
+        Ok(())
+    }
+
+    pub fn finish_group(&mut self, group_id: i32) -> Result<(), GuideError> {
+        self.check_group(group_id)?;
+        if self.cur_group != Some(group_id) {
+            return Err(GuideError::GroupNotTriggered(group_id));
+        }
+
+        self.cur_group = None;
+        self.finished_groups.insert(group_id);
+        Ok(())
+    }
+
+    fn check_group(&self, group_id: i32) -> Result<&'static GuideGroupData, GuideError> {
+        let data = guide_group_data::iter()
+            .find(|data| data.id == group_id)
+            .ok_or(GuideError::GroupNotExist(group_id))?;
+
+        match self.finished_groups.contains(&group_id) {
+            true => Err(GuideError::GroupAlreadyFinished(group_id)),
+            false => Ok(data),
+        }
+    }
+
+    pub fn unlock_tutorial(&mut self, id: i32) -> Result<TutorialInfo, GuideError> {
+        if !tutorial_data::iter().any(|data| data.id == id) {
+            return Err(GuideError::TutorialNotExist(id));
+        }
+
+        if self.tutorials.contains_key(&id) {
+            return Err(GuideError::TutorialAlreadyUnlocked(id));
+        }
+
+        self.tutorials.insert(
+            id,
+            Tutorial {
+                create_time: time_util::unix_timestamp() as u32,
+                get_award: false,
+            },
+        );
+
+        Ok(self.build_tutorial_info(id))
+    }
+
+    pub fn check_receive_tutorial(&self, id: i32) -> Result<&'static TutorialData, GuideError> {
+        let data = tutorial_data::iter()
+            .find(|data| data.id == id)
+            .ok_or(GuideError::TutorialNotExist(id))?;
+
+        match self.tutorials.get(&id) {
+            None => Err(GuideError::TutorialNotUnlocked(id)),
+            Some(tutorial) if tutorial.get_award => Err(GuideError::TutorialAlreadyReceived(id)),
+            Some(_) => Ok(data),
+        }
+    }
+
+    pub fn set_tutorial_received(&mut self, id: i32) {
+        if let Some(tutorial) = self.tutorials.get_mut(&id) {
+            tutorial.get_award = true;
+        }
+    }
+
+    pub fn build_tutorial_list(&self) -> Vec<TutorialInfo> {
+        self.tutorials
+            .keys()
+            .map(|&id| self.build_tutorial_info(id))
+            .collect()
+    }
+
+    fn build_tutorial_info(&self, id: i32) -> TutorialInfo {
+        let tutorial = self.tutorials.get(&id);
+        TutorialInfo {
+            id,
+            create_time: tutorial.map(|t| t.create_time).unwrap_or_default(),
+            get_award: tutorial.is_some_and(|t| t.get_award),
+        }
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::{HashMap, HashSet};
+
+use shorekeeper_data::{
+    instance_dungeon_data, instance_energy_cost_data, level_entity_config_data,
+    InstanceDungeonData, InstanceEnergyCostData,
+};
+use shorekeeper_protocol::{
+    EnterInstanceNotify, ErrorCode, InstDataNotify, InstEnterInfoPb, PlayerInstanceData,
+};
+
+use super::PlayerLocation;
+
+// Shortest time in seconds an instance can be cleared in
+const MIN_CLEAR_TIME: u64 = 5;
+// Allowed difference in seconds between reported fight time and time spent in the instance
+const CLEAR_TIME_TOLERANCE: u64 = 5;
+
+pub struct CurrentInstance {
+    pub id: i32,
+    pub return_location: PlayerLocation,
+    pub enter_time: u64,
+    // 0 if instance has no time limit
+    pub end_time: u64,
+    pub settled: bool,
+    // Level entity ids of instance monsters not killed yet
+    pub monsters_left: HashSet<i64>,
+}
+
+#[derive(Default)]
+pub struct PlayerInstance {
+    challenged_times: HashMap<i32, i32>,
+    cleared_ids: HashSet<i32>,
+    current: Option<CurrentInstance>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum InstanceError {
+    #[error("instance with id {0} doesn't exist")]
+    NotExist(i32),
+    #[error("instance with id {0} can't be entered from entity {1}")]
+    EntranceNotMatch(i32, i32),
+    #[error("player is already in instance {0}")]
+    AlreadyInInstance(i32),
+    #[error("player is not in instance")]
+    NotInInstance,
+    #[error("instance with id {0} can't be left through entity {1}")]
+    ExitNotMatch(i32, i32),
+    #[error("instance with id {0} is already settled")]
+    AlreadySettled(i32),
+    #[error("instance with id {0} can't be cleared in {1} seconds")]
+    ClearTimeInvalid(i32, u64),
+    #[error("instance with id {0} still has {1} monsters alive")]
+    MonstersAlive(i32, usize),
+}
+
+impl From<InstanceError> for ErrorCode {
+    fn from(err: InstanceError) -> Self {
+        match err {
+            InstanceError::NotExist(_) => ErrorCode::ErrFbInstIdNotExist,
+            InstanceError::EntranceNotMatch(..) => ErrorCode::ErrInteractEntranceNotMatch,
+            InstanceError::AlreadyInInstance(_) => ErrorCode::ErrStateCannotEnterInst,
+            InstanceError::NotInInstance => ErrorCode::InvalidRequest,
+            InstanceError::ExitNotMatch(..) => ErrorCode::ErrInteractEntranceNotMatch,
+            InstanceError::AlreadySettled(_) => ErrorCode::ErrInstNotSettleCanNotReChallenge,
+            InstanceError::ClearTimeInvalid(..) => ErrorCode::InvalidRequest,
+            InstanceError::MonstersAlive(..) => ErrorCode::InvalidRequest,
+        }
+    }
+}
+
+impl PlayerInstance {
+    pub fn load_from_save(data: PlayerInstanceData) -> Self {
+        Self {
+            challenged_times: data.challenged_times,
+            cleared_ids: data.cleared_ids.into_iter().collect(),
+            current: (data.cur_instance_id != 0).then(|| CurrentInstance {
+                id: data.cur_instance_id,
+                return_location: data
+                    .return_location
+                    .map(PlayerLocation::load_from_save)
+                    .unwrap_or_default(),
+                enter_time: 0,
+                end_time: 0,
+                settled: false,
+                monsters_left: instance_monsters(data.cur_instance_id),
+            }),
+        }
+    }
+
+    pub fn build_save_data(&self) -> PlayerInstanceData {
+        PlayerInstanceData {
+            challenged_times: self.challenged_times.clone(),
+            cleared_ids: self.cleared_ids.iter().copied().collect(),
+            cur_instance_id: self.current.as_ref().map(|c| c.id).unwrap_or_default(),
+            return_location: self
+                .current
+                .as_ref()
+                .map(|c| c.return_location.build_save_data()),
+        }
+    }
+
+    #[inline]
+    pub fn current(&self) -> Option<&CurrentInstance> {
+        self.current.as_ref()
+    }
+
+    pub fn challenged_times(&self, instance_id: i32) -> i32 {
+        self.challenged_times
+            .get(&instance_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    // Instances are entered only through one of their entrance entities
+    pub fn check_enter(
+        &self,
+        instance_id: i32,
+        pos_entity_id: i32,
+    ) -> Result<&'static InstanceDungeonData, InstanceError> {
+        if let Some(current) = self.current.as_ref() {
+            return Err(InstanceError::AlreadyInInstance(current.id));
+        }
+
+        let data = instance_dungeon_data::iter()
+            .find(|d| d.id == instance_id)
+            .ok_or(InstanceError::NotExist(instance_id))?;
+
+        if !data
+            .entrance_entities
+            .iter()
+            .any(|e| e.entrance_entity_id == pos_entity_id)
+        {
+            return Err(InstanceError::EntranceNotMatch(instance_id, pos_entity_id));
+        }
+
+        Ok(data)
+    }
+
+    pub fn enter(
+        &mut self,
+        data: &InstanceDungeonData,
+        return_location: PlayerLocation,
+        now: u64,
+    ) -> EnterInstanceNotify {
+        *self.challenged_times.entry(data.id).or_default() += 1;
+        self.current = Some(CurrentInstance {
+            id: data.id,
+            return_location,
+            enter_time: now,
+            end_time: match data.limit_time {
+                limit if limit > 0 => now + limit as u64,
+                _ => 0,
+            },
+            settled: false,
+            monsters_left: instance_monsters(data.id),
+        });
+
+        EnterInstanceNotify {
+            enter_infos: vec![self.build_enter_info(data.id)],
+        }
+    }
+
+    pub fn is_cleared(&self, instance_id: i32) -> bool {
+        self.cleared_ids.contains(&instance_id)
+    }
+
+    // Energy cost of rewards for clearing the instance again, spending it grants account exp
+    pub fn energy_cost(instance_id: i32) -> Option<&'static InstanceEnergyCostData> {
+        instance_energy_cost_data::iter().find(|data| data.instance_id == instance_id)
+    }
+
+    // Level of monsters spawned in the instance, 0 if it follows world level
+    pub fn entity_level(&self) -> i32 {
+        self.current
+            .as_ref()
+            .and_then(|c| instance_dungeon_data::iter().find(|d| d.id == c.id))
+            .map(|d| d.entity_level)
+            .unwrap_or_default()
+    }
+
+    pub fn on_monster_killed(&mut self, config_id: i64) {
+        if let Some(current) = self.current.as_mut() {
+            current.monsters_left.remove(&config_id);
+        }
+    }
+
+    // Returns drop ids granted for completing the instance, all of its monsters have to be killed
+    // and reported fight time has to fit into the time spent in the instance
+    pub fn check_settle(
+        &self,
+        instance_id: i32,
+        cost_time: i32,
+        now: u64,
+    ) -> Result<Vec<i32>, InstanceError> {
+        let current = self
+            .current
+            .as_ref()
+            .filter(|c| c.id == instance_id)
+            .ok_or(InstanceError::NotInInstance)?;
+
+        if current.settled {
+            return Err(InstanceError::AlreadySettled(instance_id));
+        }
+
+        if !current.monsters_left.is_empty() {
+            return Err(InstanceError::MonstersAlive(
+                instance_id,
+                current.monsters_left.len(),
+            ));
+        }
+
+        let elapsed = now.saturating_sub(current.enter_time);
+        if elapsed < MIN_CLEAR_TIME
+            || cost_time.max(0) as u64 > elapsed + CLEAR_TIME_TOLERANCE
+            || (current.end_time != 0 && now > current.end_time + CLEAR_TIME_TOLERANCE)
+        {
+            return Err(InstanceError::ClearTimeInvalid(instance_id, elapsed));
+        }
+
+        let data = instance_dungeon_data::iter()
+            .find(|d| d.id == instance_id)
+            .ok_or(InstanceError::NotExist(instance_id))?;
+
+        let mut drop_ids = Vec::with_capacity(2);
+        if !self.is_cleared(instance_id) {
+            drop_ids.push(data.first_reward_id);
+            drop_ids.push(data.reward_id);
+        } else if data.repeat_reward_id != 0 {
+            drop_ids.push(data.repeat_reward_id);
+        } else {
+            drop_ids.push(data.reward_id);
+        }
+
+        drop_ids.retain(|&id| id != 0);
+        Ok(drop_ids)
+    }
+
+    // Marks current run as completed, has to follow a successful check_settle
+    pub fn settle(&mut self, instance_id: i32) {
+        if let Some(current) = self.current.as_mut().filter(|c| c.id == instance_id) {
+            current.settled = true;
+            self.cleared_ids.insert(instance_id);
+        }
+    }
+
+    pub fn check_leave(&self, pos_entity_id: i32) -> Result<(), InstanceError> {
+        let current = self.current.as_ref().ok_or(InstanceError::NotInInstance)?;
+
+        // Leaving through the menu doesn't involve any entity
+        if pos_entity_id == 0 {
+            return Ok(());
+        }
+
+        let data = instance_dungeon_data::iter()
+            .find(|d| d.id == current.id)
+            .ok_or(InstanceError::NotExist(current.id))?;
+
+        if !data.exit_entities.contains(&pos_entity_id) {
+            return Err(InstanceError::ExitNotMatch(current.id, pos_entity_id));
+        }
+
+        Ok(())
+    }
+
+    // Returns location to restore after leaving the instance
+    pub fn leave(&mut self) -> Option<PlayerLocation> {
+        self.current.take().map(|c| c.return_location)
+    }
+
+    pub fn is_timed_out(&self, now: u64) -> bool {
+        self.current
+            .as_ref()
+            .is_some_and(|c| !c.settled && c.end_time != 0 && now >= c.end_time)
+    }
+
+    pub fn build_inst_data_notify(&self) -> InstDataNotify {
+        InstDataNotify {
+            enter_infos: self
+                .challenged_times
+                .keys()
+                .map(|&id| self.build_enter_info(id))
+                .collect(),
+        }
+    }
+
+    fn build_enter_info(&self, instance_id: i32) -> InstEnterInfoPb {
+        InstEnterInfoPb {
+            id: instance_id,
+            challenged_times: self.challenged_times(instance_id),
+        }
+    }
+}
+
+// Monsters placed on the instance map by level data
+fn instance_monsters(instance_id: i32) -> HashSet<i64> {
+    let Some(data) = instance_dungeon_data::iter().find(|d| d.id == instance_id) else {
+        return HashSet::new();
+    };
+
+    level_entity_config_data::iter()
+        .filter(|(_, entity)| {
+            entity.map_id == data.map_config_id && entity.blueprint_type.contains("Monster")
+        })
+        .map(|(&entity_id, _)| entity_id)
+        .collect()
+}
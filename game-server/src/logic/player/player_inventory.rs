@@ -0,0 +1,505 @@
+use std::collections::{HashMap, HashSet};
+
+use shorekeeper_data::{item_info_data, weapon_conf_data};
+use shorekeeper_protocol::{
+    AddCountItemInfo, ErrorCode, NormalItem, PhantomItem, PlayerInventoryData, WeaponItem,
+};
+
+use super::phantom::{PhantomInstance, PropBonus};
+use super::weapon::WeaponInstance;
+
+pub struct ItemUsage {
+    pub id: i32,
+    pub quantity: i32,
+}
+
+// Result of a successful inventory operation, used to build change notifies
+#[derive(Default)]
+pub struct InventoryChange {
+    pub normal_items: HashSet<i32>,
+    pub added_weapons: Vec<i32>,
+    pub removed_weapons: Vec<i32>,
+    pub added_phantoms: Vec<i32>,
+    pub removed_phantoms: Vec<i32>,
+    pub obtained: Vec<AddCountItemInfo>,
+}
+
+#[derive(Default)]
+pub struct PlayerInventory {
+    items: HashMap<i32, i32>,
+    weapons: HashMap<i32, WeaponInstance>,
+    phantoms: HashMap<i32, PhantomInstance>,
+    cur_incr_id: i32,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum InventoryError {
+    #[error("item with id {0} doesn't exist in item data")]
+    InvalidItemId(i32),
+    #[error("item with id {0} doesn't exist in inventory")]
+    ItemNotFound(i32),
+    #[error("item with id {0} has quantity {1}, but {2} requested")]
+    ItemNotEnough(i32, i32, i32),
+    #[error("invalid quantity {1} for item with id {0}")]
+    InvalidQuantity(i32, i32),
+    #[error("item with id {0} exceeds its capacity")]
+    CapacityExceeded(i32),
+    #[error("item with incr_id {0} was specified more than once")]
+    DuplicateItem(i32),
+    #[error("item with incr_id {0} is locked")]
+    ItemLocked(i32),
+    #[error("item with incr_id {0} is equipped")]
+    ItemEquipped(i32),
+    #[error("item with id {0} can't be destroyed")]
+    NotDestructible(i32),
+}
+
+impl From<InventoryError> for ErrorCode {
+    fn from(err: InventoryError) -> Self {
+        match err {
+            InventoryError::InvalidItemId(_) => ErrorCode::ErrItemIdInvaild,
+            InventoryError::ItemNotFound(_) => ErrorCode::ErrItemNotFound,
+            InventoryError::ItemNotEnough(..) => ErrorCode::ErrItemNotEnough,
+            InventoryError::InvalidQuantity(..) => ErrorCode::ErrItemInvalidParams,
+            InventoryError::CapacityExceeded(_) => ErrorCode::ErrPkgCapacityNotEnough,
+            InventoryError::DuplicateItem(_) => ErrorCode::ErrItemInvalidParams,
+            InventoryError::ItemLocked(_) => ErrorCode::ErrWeaponLocked,
+            InventoryError::ItemEquipped(_) => ErrorCode::ErrWeaponEquiped,
+            InventoryError::NotDestructible(_) => ErrorCode::ErrItemCanNotDestroy,
+        }
+    }
+}
+
+impl PlayerInventory {
+    pub const SHELL_CREDIT_ID: i32 = 2;
+    const FUNC_VALUE_LOCKED: i32 = 1;
+    // Bag capacity of items that are stored as separate instances
+    const WEAPON_CAPACITY: usize = 2000;
+    const PHANTOM_CAPACITY: usize = 3000;
+
+    pub fn load_from_save(data: PlayerInventoryData) -> Self {
+        Self {
+            items: data.items,
+            weapons: data
+                .weapons
+                .into_iter()
+                .map(|(incr_id, weapon)| (incr_id, WeaponInstance::load_from_save(weapon)))
+                .collect(),
+            phantoms: data
+                .phantoms
+                .into_iter()
+                .map(|(incr_id, phantom)| (incr_id, PhantomInstance::load_from_save(phantom)))
+                .collect(),
+            cur_incr_id: data.cur_incr_id,
+        }
+    }
+
+    pub fn build_save_data(&self) -> PlayerInventoryData {
+        PlayerInventoryData {
+            items: self.items.clone(),
+            weapons: self
+                .weapons
+                .iter()
+                .map(|(&incr_id, weapon)| (incr_id, weapon.build_save_data()))
+                .collect(),
+            phantoms: self
+                .phantoms
+                .iter()
+                .map(|(&incr_id, phantom)| (incr_id, phantom.build_save_data()))
+                .collect(),
+            cur_incr_id: self.cur_incr_id,
+        }
+    }
+
+    pub fn get_item_count(&self, id: i32) -> i32 {
+        self.items.get(&id).copied().unwrap_or_default()
+    }
+
+    pub fn get_weapon(&self, incr_id: i32) -> Option<&WeaponInstance> {
+        self.weapons.get(&incr_id)
+    }
+
+    pub fn get_weapon_mut(&mut self, incr_id: i32) -> Option<&mut WeaponInstance> {
+        self.weapons.get_mut(&incr_id)
+    }
+
+    pub fn get_role_weapon(&self, role_id: i32) -> Option<&WeaponInstance> {
+        self.weapons.values().find(|w| w.role_id == role_id)
+    }
+
+    // Creates weapon instance that is already equipped by role, returns its incr_id
+    pub fn add_role_weapon(&mut self, id: i32, role_id: i32) -> i32 {
+        self.add_weapon(id, role_id)
+    }
+
+    pub fn get_phantom(&self, incr_id: i32) -> Option<&PhantomInstance> {
+        self.phantoms.get(&incr_id)
+    }
+
+    pub fn get_phantom_mut(&mut self, incr_id: i32) -> Option<&mut PhantomInstance> {
+        self.phantoms.get_mut(&incr_id)
+    }
+
+    // Attribute bonuses of equipped phantoms including set bonuses
+    pub fn build_phantom_bonuses(&self, incr_ids: &[i32]) -> Vec<PropBonus> {
+        let phantoms = incr_ids
+            .iter()
+            .filter_map(|incr_id| self.phantoms.get(incr_id))
+            .collect::<Vec<_>>();
+
+        phantoms
+            .iter()
+            .flat_map(|phantom| phantom.build_prop_bonuses())
+            .chain(PhantomInstance::build_fetter_bonuses(
+                phantoms.iter().copied(),
+            ))
+            .collect()
+    }
+
+    // Validates that all items fit into the inventory, used to check rewards before paying for them
+    pub fn check_add(&self, usages: &[ItemUsage]) -> Result<(), InventoryError> {
+        let mut normal_items: HashMap<i32, i32> = HashMap::new();
+        let (mut weapon_count, mut phantom_count) = (self.weapons.len(), self.phantoms.len());
+        for usage in usages.iter() {
+            if usage.quantity <= 0 {
+                return Err(InventoryError::InvalidQuantity(usage.id, usage.quantity));
+            }
+
+            if Self::is_weapon(usage.id) {
+                weapon_count += usage.quantity as usize;
+                if weapon_count > Self::WEAPON_CAPACITY {
+                    return Err(InventoryError::CapacityExceeded(usage.id));
+                }
+            } else if PhantomInstance::is_phantom(usage.id) {
+                phantom_count += usage.quantity as usize;
+                if phantom_count > Self::PHANTOM_CAPACITY {
+                    return Err(InventoryError::CapacityExceeded(usage.id));
+                }
+            } else if item_info_data::get(usage.id).is_some() {
+                let quantity = normal_items.entry(usage.id).or_default();
+                *quantity = quantity
+                    .checked_add(usage.quantity)
+                    .ok_or(InventoryError::CapacityExceeded(usage.id))?;
+            } else {
+                return Err(InventoryError::InvalidItemId(usage.id));
+            }
+        }
+
+        for (&id, &quantity) in normal_items.iter() {
+            self.get_item_count(id)
+                .checked_add(quantity)
+                .ok_or(InventoryError::CapacityExceeded(id))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn add_items(&mut self, usages: &[ItemUsage]) -> Result<InventoryChange, InventoryError> {
+        self.check_add(usages)?;
+
+        let mut change = InventoryChange::default();
+        for usage in usages.iter() {
+            if Self::is_weapon(usage.id) {
+                for _ in 0..usage.quantity {
+                    let incr_id = self.add_weapon(usage.id, 0);
+                    change.added_weapons.push(incr_id);
+                    change.obtained.push(AddCountItemInfo {
+                        id: usage.id,
+                        count: 1,
+                        incr_id,
+                    });
+                }
+            } else if PhantomInstance::is_phantom(usage.id) {
+                for _ in 0..usage.quantity {
+                    let incr_id = self.next_incr_id();
+                    self.phantoms
+                        .insert(incr_id, PhantomInstance::new(usage.id, incr_id));
+                    change.added_phantoms.push(incr_id);
+                    change.obtained.push(AddCountItemInfo {
+                        id: usage.id,
+                        count: 1,
+                        incr_id,
+                    });
+                }
+            } else {
+                *self.items.entry(usage.id).or_default() += usage.quantity;
+                change.normal_items.insert(usage.id);
+                change.obtained.push(AddCountItemInfo {
+                    id: usage.id,
+                    count: usage.quantity,
+                    incr_id: 0,
+                });
+            }
+        }
+
+        Ok(change)
+    }
+
+    pub fn consume_items(
+        &mut self,
+        usages: &[ItemUsage],
+    ) -> Result<InventoryChange, InventoryError> {
+        let required = self.check_consume(usages)?;
+
+        let mut change = InventoryChange::default();
+        self.apply_consume(required, &mut change);
+        Ok(change)
+    }
+
+    // Destroys stackable items, weapons and phantoms in one go,
+    // nothing is removed if any of them fails the checks
+    pub fn destruct_items(
+        &mut self,
+        usages: &[ItemUsage],
+        weapon_incr_ids: &[i32],
+        phantom_incr_ids: &[i32],
+    ) -> Result<InventoryChange, InventoryError> {
+        let required = self.check_consume(usages)?;
+
+        Self::check_unique(weapon_incr_ids.iter().chain(phantom_incr_ids.iter()))?;
+        for incr_id in weapon_incr_ids.iter() {
+            let weapon = self.check_weapon_removable(*incr_id)?;
+            if !weapon.conf().is_some_and(|w| w.destructible) {
+                return Err(InventoryError::NotDestructible(weapon.id));
+            }
+        }
+
+        for incr_id in phantom_incr_ids.iter() {
+            self.check_phantom_removable(*incr_id)?;
+        }
+
+        let mut change = InventoryChange::default();
+        self.apply_consume(required, &mut change);
+
+        for incr_id in weapon_incr_ids.iter() {
+            self.weapons.remove(incr_id);
+            change.removed_weapons.push(*incr_id);
+        }
+
+        for incr_id in phantom_incr_ids.iter() {
+            self.phantoms.remove(incr_id);
+            change.removed_phantoms.push(*incr_id);
+        }
+
+        Ok(change)
+    }
+
+    // Consumes stackable items together with weapons used as upgrade material,
+    // nothing is removed if any of them is missing, locked or equipped
+    pub fn consume_with_weapons(
+        &mut self,
+        usages: &[ItemUsage],
+        weapon_incr_ids: &[i32],
+    ) -> Result<InventoryChange, InventoryError> {
+        let required = self.check_consume(usages)?;
+
+        Self::check_unique(weapon_incr_ids.iter())?;
+        for incr_id in weapon_incr_ids.iter() {
+            self.check_weapon_removable(*incr_id)?;
+        }
+
+        let mut change = InventoryChange::default();
+        self.apply_consume(required, &mut change);
+
+        for incr_id in weapon_incr_ids.iter() {
+            self.weapons.remove(incr_id);
+            change.removed_weapons.push(*incr_id);
+        }
+
+        Ok(change)
+    }
+
+    // Consumes stackable items together with phantoms used as upgrade material,
+    // nothing is removed if any of them is missing, locked or equipped
+    pub fn consume_with_phantoms(
+        &mut self,
+        usages: &[ItemUsage],
+        phantom_incr_ids: &[i32],
+    ) -> Result<InventoryChange, InventoryError> {
+        let required = self.check_consume(usages)?;
+
+        Self::check_unique(phantom_incr_ids.iter())?;
+        for incr_id in phantom_incr_ids.iter() {
+            self.check_phantom_removable(*incr_id)?;
+        }
+
+        let mut change = InventoryChange::default();
+        self.apply_consume(required, &mut change);
+
+        for incr_id in phantom_incr_ids.iter() {
+            self.phantoms.remove(incr_id);
+            change.removed_phantoms.push(*incr_id);
+        }
+
+        Ok(change)
+    }
+
+    // Toggles lock state of weapon or phantom, returns new func_value
+    pub fn toggle_lock(&mut self, item_id: i32, incr_id: i32) -> Result<i32, InventoryError> {
+        let func_value = if let Some(weapon) =
+            self.weapons.get_mut(&incr_id).filter(|w| w.id == item_id)
+        {
+            &mut weapon.func_value
+        } else if let Some(phantom) = self.phantoms.get_mut(&incr_id).filter(|p| p.id == item_id) {
+            &mut phantom.func_value
+        } else {
+            return Err(InventoryError::ItemNotFound(item_id));
+        };
+
+        *func_value ^= Self::FUNC_VALUE_LOCKED;
+        Ok(*func_value)
+    }
+
+    pub fn build_normal_item_list(&self) -> Vec<NormalItem> {
+        self.items
+            .iter()
+            .map(|(&id, &count)| NormalItem {
+                id,
+                count,
+                expire_time: 0,
+            })
+            .collect()
+    }
+
+    pub fn build_normal_items(&self, ids: &HashSet<i32>) -> Vec<NormalItem> {
+        ids.iter()
+            .map(|&id| NormalItem {
+                id,
+                count: self.get_item_count(id),
+                expire_time: 0,
+            })
+            .collect()
+    }
+
+    pub fn build_weapon_item_list(&self) -> Vec<WeaponItem> {
+        self.weapons
+            .values()
+            .map(WeaponInstance::to_protobuf)
+            .collect()
+    }
+
+    pub fn build_weapon_items(&self, incr_ids: &[i32]) -> Vec<WeaponItem> {
+        incr_ids
+            .iter()
+            .flat_map(|incr_id| self.weapons.get(incr_id))
+            .map(WeaponInstance::to_protobuf)
+            .collect()
+    }
+
+    pub fn build_phantom_item_list(&self) -> Vec<PhantomItem> {
+        self.phantoms
+            .values()
+            .map(PhantomInstance::to_protobuf)
+            .collect()
+    }
+
+    pub fn build_phantom_items(&self, incr_ids: &[i32]) -> Vec<PhantomItem> {
+        incr_ids
+            .iter()
+            .flat_map(|incr_id| self.phantoms.get(incr_id))
+            .map(PhantomInstance::to_protobuf)
+            .collect()
+    }
+
+    fn add_weapon(&mut self, id: i32, role_id: i32) -> i32 {
+        let incr_id = self.next_incr_id();
+        self.weapons
+            .insert(incr_id, WeaponInstance::new(id, incr_id, role_id));
+
+        incr_id
+    }
+
+    fn next_incr_id(&mut self) -> i32 {
+        self.cur_incr_id += 1;
+        self.cur_incr_id
+    }
+
+    fn check_consume(&self, usages: &[ItemUsage]) -> Result<HashMap<i32, i32>, InventoryError> {
+        let mut required: HashMap<i32, i32> = HashMap::new();
+        for usage in usages.iter() {
+            if usage.quantity <= 0 {
+                return Err(InventoryError::InvalidQuantity(usage.id, usage.quantity));
+            }
+
+            let quantity = required.entry(usage.id).or_default();
+            *quantity = quantity
+                .checked_add(usage.quantity)
+                .ok_or(InventoryError::InvalidQuantity(usage.id, usage.quantity))?;
+        }
+
+        for (&id, &quantity) in required.iter() {
+            match self.items.get(&id) {
+                None => return Err(InventoryError::ItemNotFound(id)),
+                Some(&count) if count < quantity => {
+                    return Err(InventoryError::ItemNotEnough(id, count, quantity))
+                }
+                _ => (),
+            }
+        }
+
+        Ok(required)
+    }
+
+    fn apply_consume(&mut self, required: HashMap<i32, i32>, change: &mut InventoryChange) {
+        for (id, quantity) in required {
+            if let Some(count) = self.items.get_mut(&id) {
+                *count -= quantity;
+                if *count == 0 {
+                    self.items.remove(&id);
+                }
+            }
+
+            change.normal_items.insert(id);
+        }
+    }
+
+    fn check_weapon_removable(&self, incr_id: i32) -> Result<&WeaponInstance, InventoryError> {
+        let weapon = self
+            .weapons
+            .get(&incr_id)
+            .ok_or(InventoryError::ItemNotFound(incr_id))?;
+
+        if weapon.func_value & Self::FUNC_VALUE_LOCKED != 0 {
+            return Err(InventoryError::ItemLocked(incr_id));
+        }
+
+        if weapon.role_id != 0 {
+            return Err(InventoryError::ItemEquipped(incr_id));
+        }
+
+        Ok(weapon)
+    }
+
+    fn check_phantom_removable(&self, incr_id: i32) -> Result<&PhantomInstance, InventoryError> {
+        let phantom = self
+            .phantoms
+            .get(&incr_id)
+            .ok_or(InventoryError::ItemNotFound(incr_id))?;
+
+        if phantom.func_value & Self::FUNC_VALUE_LOCKED != 0 {
+            return Err(InventoryError::ItemLocked(incr_id));
+        }
+
+        if phantom.role_id != 0 {
+            return Err(InventoryError::ItemEquipped(incr_id));
+        }
+
+        Ok(phantom)
+    }
+
+    fn check_unique<'a>(incr_ids: impl Iterator<Item = &'a i32>) -> Result<(), InventoryError> {
+        let mut unique = HashSet::new();
+        for incr_id in incr_ids {
+            if !unique.insert(*incr_id) {
+                return Err(InventoryError::DuplicateItem(*incr_id));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn is_weapon(id: i32) -> bool {
+        weapon_conf_data::get(id).is_some()
+    }
+}
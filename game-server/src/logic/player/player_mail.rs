@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+use common::time_util;
+use shorekeeper_protocol::{ErrorCode, MailInfosNotify, PbMailAttachment, PbMailInfo};
+
+use super::ItemUsage;
+
+pub struct Mail {
+    pub id: i64,
+    pub title: String,
+    pub content: String,
+    pub sender: String,
+    pub level: i32,
+    pub attachments: Vec<ItemUsage>,
+    pub received_time: i64,
+    pub read_time: i64,
+    pub expire_time: i64,
+    pub is_claimed: bool,
+}
+
+// Mailbox is persisted in t_player_mail, changes are written through mail_task
+// except claims, which are saved together with the player data
+#[derive(Default)]
+pub struct PlayerMail {
+    mails: HashMap<i64, Mail>,
+    unsaved_claims: Vec<i64>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum MailError {
+    #[error("mail with id {0} doesn't exist")]
+    NotExist(String),
+    #[error("mail with id {0} has no attachments")]
+    NoAttachment(i64),
+    #[error("attachments of mail with id {0} were already claimed")]
+    AttachmentIsGet(i64),
+    #[error("attachments of mail with id {0} were not claimed yet")]
+    AttachmentNotGet(i64),
+}
+
+impl From<MailError> for ErrorCode {
+    fn from(err: MailError) -> Self {
+        match err {
+            MailError::NotExist(_) => ErrorCode::ErrMailNotExist,
+            MailError::NoAttachment(_) => ErrorCode::ErrMailNoAttachment,
+            MailError::AttachmentIsGet(_) => ErrorCode::ErrMailAttachmentIsGet,
+            MailError::AttachmentNotGet(_) => ErrorCode::ErrMailAttachmentNotGet,
+        }
+    }
+}
+
+impl Mail {
+    pub const STATE_UNREAD: i32 = 0;
+    pub const STATE_READ: i32 = 1;
+    pub const STATE_CLAIMED: i32 = 2;
+
+    pub fn state(&self) -> i32 {
+        if self.is_claimed {
+            Self::STATE_CLAIMED
+        } else if self.read_time != 0 {
+            Self::STATE_READ
+        } else {
+            Self::STATE_UNREAD
+        }
+    }
+
+    #[inline]
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expire_time <= now
+    }
+
+    pub fn to_protobuf(&self) -> PbMailInfo {
+        let valid_time = (self.expire_time - time_util::unix_timestamp() as i64).max(0) as i32;
+
+        PbMailInfo {
+            id: self.id.to_string(),
+            received_time: self.received_time,
+            read_time: self.read_time,
+            state: self.state(),
+            level: self.level,
+            title: self.title.clone(),
+            content: self.content.clone(),
+            sender: self.sender.clone(),
+            valid_time,
+            read_valid_time: valid_time,
+            attachments: self
+                .attachments
+                .iter()
+                .map(|item| PbMailAttachment {
+                    id: item.id,
+                    count: item.quantity,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+impl PlayerMail {
+    pub fn new(mails: Vec<Mail>) -> Self {
+        Self {
+            mails: mails.into_iter().map(|mail| (mail.id, mail)).collect(),
+            unsaved_claims: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, mail: Mail) -> &Mail {
+        let id = mail.id;
+        self.mails.insert(id, mail);
+        self.mails.get(&id).unwrap()
+    }
+
+    pub fn get(&self, id: &str) -> Result<&Mail, MailError> {
+        let now = time_util::unix_timestamp() as i64;
+        id.parse::<i64>()
+            .ok()
+            .and_then(|id| self.mails.get(&id))
+            .filter(|mail| !mail.is_expired(now))
+            .ok_or_else(|| MailError::NotExist(id.to_string()))
+    }
+
+    // Marks mail as read, returns true if it wasn't read before
+    pub fn read(&mut self, id: i64) -> bool {
+        let mail = self.mails.get_mut(&id).unwrap();
+        if mail.read_time != 0 {
+            return false;
+        }
+
+        mail.read_time = time_util::unix_timestamp() as i64;
+        true
+    }
+
+    pub fn check_claim(&self, id: &str) -> Result<&Mail, MailError> {
+        let mail = self.get(id)?;
+        if mail.attachments.is_empty() {
+            return Err(MailError::NoAttachment(mail.id));
+        }
+
+        if mail.is_claimed {
+            return Err(MailError::AttachmentIsGet(mail.id));
+        }
+
+        Ok(mail)
+    }
+
+    pub fn set_claimed(&mut self, id: i64) {
+        self.mails.get_mut(&id).unwrap().is_claimed = true;
+        self.unsaved_claims.push(id);
+    }
+
+    pub fn take_unsaved_claims(&mut self) -> Vec<i64> {
+        std::mem::take(&mut self.unsaved_claims)
+    }
+
+    // Mails with unclaimed attachments can't be deleted
+    pub fn check_delete(&self, id: &str) -> Result<&Mail, MailError> {
+        let mail = self.get(id)?;
+        if !mail.attachments.is_empty() && !mail.is_claimed {
+            return Err(MailError::AttachmentNotGet(mail.id));
+        }
+
+        Ok(mail)
+    }
+
+    pub fn remove(&mut self, id: i64) {
+        self.mails.remove(&id);
+    }
+
+    pub fn build_mail_infos_notify(&self) -> MailInfosNotify {
+        let now = time_util::unix_timestamp() as i64;
+        let mut mails: Vec<&Mail> = self
+            .mails
+            .values()
+            .filter(|mail| !mail.is_expired(now))
+            .collect();
+        mails.sort_by_key(|mail| mail.received_time);
+
+        MailInfosNotify {
+            mail_infos: mails.into_iter().map(Mail::to_protobuf).collect(),
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::{BTreeMap, HashSet};
+
+use shorekeeper_protocol::{
+    ErrorCode, MapMarkData, MarkPointInfo, MarkPointRequestInfo, PlayerMapData,
+};
+
+use crate::logic::math::Vector3f;
+
+const MAX_MARKS: usize = 100;
+const MARK_TYPE_CUSTOM: i32 = 1;
+// Area is re-evaluated once player moves further than this (in meters)
+const AREA_CHECK_DISTANCE: f32 = 10.0;
+
+struct MapMark {
+    config_id: i32,
+    mark_type: i32,
+    map_id: i32,
+    position: Vector3f,
+}
+
+#[derive(Default)]
+pub struct PlayerMap {
+    marks: BTreeMap<i32, MapMark>,
+    next_mark_id: i32,
+    // Only a single mark can be traced at a time, tracing a new one replaces the old
+    traced_mark_id: Option<i32>,
+    unlocked_areas: HashSet<i32>,
+    // Runtime
+    last_area_check_position: Option<Vector3f>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum MapMarkError {
+    #[error("map mark limit of {MAX_MARKS} reached")]
+    NumLimit,
+    #[error("map mark with id {0} doesn't exist")]
+    NotExist(i32),
+    #[error("map mark with id {0} is not a custom mark")]
+    NotCustom(i32),
+    #[error("map mark with id {0} is not traced")]
+    NotTraced(i32),
+}
+
+impl From<MapMarkError> for ErrorCode {
+    fn from(err: MapMarkError) -> Self {
+        match err {
+            MapMarkError::NumLimit => ErrorCode::ErrMapMarkNumLimit,
+            MapMarkError::NotExist(_) => ErrorCode::ErrMarkIdNotExists,
+            MapMarkError::NotCustom(_) => ErrorCode::ErrMapMarkTypeNotCustom,
+            // Client has no dedicated error code for it
+            MapMarkError::NotTraced(_) => ErrorCode::InvalidRequest,
+        }
+    }
+}
+
+impl PlayerMap {
+    pub fn load_from_save(data: PlayerMapData) -> Self {
+        let traced_mark_id = data
+            .marks
+            .iter()
+            .find(|mark| mark.is_trace)
+            .map(|mark| mark.mark_id);
+
+        Self {
+            marks: data
+                .marks
+                .into_iter()
+                .map(|mark| {
+                    (
+                        mark.mark_id,
+                        MapMark {
+                            config_id: mark.config_id,
+                            mark_type: mark.mark_type,
+                            map_id: mark.map_id,
+                            position: Vector3f::from_save(mark.position.unwrap_or_default()),
+                        },
+                    )
+                })
+                .collect(),
+            next_mark_id: data.next_mark_id,
+            traced_mark_id,
+            unlocked_areas: data.unlocked_area_ids.into_iter().collect(),
+            last_area_check_position: None,
+        }
+    }
+
+    pub fn build_save_data(&self) -> PlayerMapData {
+        PlayerMapData {
+            marks: self
+                .marks
+                .iter()
+                .map(|(&mark_id, mark)| MapMarkData {
+                    mark_id,
+                    config_id: mark.config_id,
+                    mark_type: mark.mark_type,
+                    map_id: mark.map_id,
+                    position: Some(mark.position.save_data()),
+                    is_trace: self.traced_mark_id == Some(mark_id),
+                })
+                .collect(),
+            next_mark_id: self.next_mark_id,
+            unlocked_area_ids: self.unlocked_areas.iter().copied().collect(),
+        }
+    }
+
+    pub fn add_mark(
+        &mut self,
+        request: &MarkPointRequestInfo,
+    ) -> Result<MarkPointInfo, MapMarkError> {
+        if self.marks.len() >= MAX_MARKS {
+            return Err(MapMarkError::NumLimit);
+        }
+
+        self.next_mark_id += 1;
+        let mark_id = self.next_mark_id;
+
+        let mark = MapMark {
+            config_id: request.config_id,
+            mark_type: request.mark_type,
+            map_id: request.map_id,
+            position: Vector3f {
+                x: request.pos_x,
+                y: request.pos_y,
+                z: request.pos_z,
+            },
+        };
+
+        if request.is_trace != 0 {
+            self.traced_mark_id = Some(mark_id);
+        }
+
+        let info = self.build_mark_info(mark_id, &mark);
+        self.marks.insert(mark_id, mark);
+        Ok(info)
+    }
+
+    // Returns ids of marks that were actually removed
+    pub fn remove_marks(&mut self, mark_ids: &[i32]) -> Vec<i32> {
+        let removed: Vec<i32> = mark_ids
+            .iter()
+            .copied()
+            .filter(|mark_id| self.marks.remove(mark_id).is_some())
+            .collect();
+
+        if self
+            .traced_mark_id
+            .is_some_and(|mark_id| removed.contains(&mark_id))
+        {
+            self.traced_mark_id = None;
+        }
+
+        removed
+    }
+
+    pub fn replace_mark(&mut self, mark_id: i32, config_id: i32) -> Result<(), MapMarkError> {
+        let mark = self
+            .marks
+            .get_mut(&mark_id)
+            .ok_or(MapMarkError::NotExist(mark_id))?;
+
+        if mark.mark_type != MARK_TYPE_CUSTOM {
+            return Err(MapMarkError::NotCustom(mark_id));
+        }
+
+        mark.config_id = config_id;
+        Ok(())
+    }
+
+    // Returns id of the previously traced mark if it was replaced
+    pub fn trace(&mut self, mark_id: i32) -> Result<Option<i32>, MapMarkError> {
+        if !self.marks.contains_key(&mark_id) {
+            return Err(MapMarkError::NotExist(mark_id));
+        }
+
+        Ok(self
+            .traced_mark_id
+            .replace(mark_id)
+            .filter(|&previous| previous != mark_id))
+    }
+
+    pub fn cancel_trace(&mut self, mark_id: i32) -> Result<(), MapMarkError> {
+        if !self.marks.contains_key(&mark_id) {
+            return Err(MapMarkError::NotExist(mark_id));
+        }
+
+        match self.traced_mark_id {
+            Some(traced) if traced == mark_id => {
+                self.traced_mark_id = None;
+                Ok(())
+            }
+            _ => Err(MapMarkError::NotTraced(mark_id)),
+        }
+    }
+
+    pub fn traced_mark_ids(&self) -> Vec<i32> {
+        self.traced_mark_id.into_iter().collect()
+    }
+
+    pub fn build_mark_info_list(&self) -> Vec<MarkPointInfo> {
+        self.marks
+            .iter()
+            .map(|(&mark_id, mark)| self.build_mark_info(mark_id, mark))
+            .collect()
+    }
+
+    fn build_mark_info(&self, mark_id: i32, mark: &MapMark) -> MarkPointInfo {
+        MarkPointInfo {
+            pos_x: mark.position.x,
+            pos_y: mark.position.y,
+            pos_z: mark.position.z,
+            config_id: mark.config_id,
+            mark_id,
+            is_trace: (self.traced_mark_id == Some(mark_id)) as i32,
+            mark_type: mark.mark_type,
+            map_id: mark.map_id,
+            is_server_disable: false,
+        }
+    }
+
+    // Returns true if player moved far enough since the last area check
+    pub fn should_check_area(&mut self, position: &Vector3f) -> bool {
+        let moved_enough = self.last_area_check_position.as_ref().is_none_or(|last| {
+            (last.x - position.x).powi(2) + (last.y - position.y).powi(2)
+                >= AREA_CHECK_DISTANCE.powi(2)
+        });
+
+        if moved_enough {
+            self.last_area_check_position = Some(position.clone());
+        }
+
+        moved_enough
+    }
+
+    // Returns true if area was newly unlocked
+    pub fn unlock_area(&mut self, area_id: i32) -> bool {
+        self.unlocked_areas.insert(area_id)
+    }
+
+    pub fn unlocked_area_ids(&self) -> Vec<i32> {
+        self.unlocked_areas.iter().copied().collect()
+    }
+}
@@ -0,0 +1,277 @@
+use std::collections::{HashMap, HashSet};
+
+use shorekeeper_data::{quest_data, QuestData, QuestNodeData};
+use shorekeeper_protocol::{
+    ErrorCode, PlayerQuestData, QuestInfo, QuestListNotify, QuestProgressData,
+    QuestReadyListNotify, QuestState,
+};
+
+// QuestState_hTs is sent for quests that are ready to be accepted,
+// QuestState_zfs for quests in progress
+const QUEST_STATE_READY: QuestState = QuestState::HTs;
+const QUEST_STATE_PROGRESS: QuestState = QuestState::Zfs;
+
+struct QuestProgress {
+    state: QuestState,
+    active_nodes: HashSet<i32>,
+    finished_nodes: HashSet<i32>,
+}
+
+#[derive(Default)]
+pub struct PlayerQuest {
+    quests: HashMap<i32, QuestProgress>,
+    trace_quest_id: i32,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum QuestError {
+    #[error("quest with id {0} doesn't exist")]
+    NotExist(i32),
+    #[error("quest with id {0} is already accepted")]
+    AlreadyAccepted(i32),
+    #[error("quest with id {0} can't be accepted yet")]
+    CanNotAccept(i32),
+    #[error("quest with id {0} is not in progress")]
+    NotInProgress(i32),
+    #[error("quest with id {0} doesn't have node {1}")]
+    NodeNotFound(i32, i32),
+    #[error("node {1} of quest {0} is not active")]
+    NodeNotActive(i32, i32),
+    #[error("quest with id {0} is not finished")]
+    NotFinished(i32),
+    #[error("quest with id {0} is chained too deep")]
+    ChainTooDeep(i32),
+}
+
+impl From<QuestError> for ErrorCode {
+    fn from(err: QuestError) -> Self {
+        match err {
+            QuestError::NotExist(_) => ErrorCode::ErrQuestErrTaskId,
+            QuestError::AlreadyAccepted(_) => ErrorCode::ErrQuestAccepted,
+            QuestError::CanNotAccept(_) => ErrorCode::ErrQuestCanNotAccept,
+            QuestError::NotInProgress(_) => ErrorCode::ErrQuestNotProgress,
+            QuestError::NodeNotFound(..) => ErrorCode::ErrQuestNodeNotFound,
+            QuestError::NodeNotActive(..) => ErrorCode::ErrQuestNodeNotActive,
+            QuestError::NotFinished(_) => ErrorCode::ErrQuestNotFinish,
+            // Client has no dedicated error code
+            QuestError::ChainTooDeep(_) => ErrorCode::InvalidRequest,
+        }
+    }
+}
+
+impl PlayerQuest {
+    pub fn load_from_save(data: PlayerQuestData) -> Self {
+        Self {
+            quests: data
+                .quests
+                .into_iter()
+                .map(|(id, progress)| {
+                    (
+                        id,
+                        QuestProgress {
+                            state: QuestState::try_from(progress.status).unwrap_or_default(),
+                            active_nodes: progress.active_node_ids.into_iter().collect(),
+                            finished_nodes: progress.finished_node_ids.into_iter().collect(),
+                        },
+                    )
+                })
+                .collect(),
+            trace_quest_id: data.trace_quest_id,
+        }
+    }
+
+    pub fn build_save_data(&self) -> PlayerQuestData {
+        PlayerQuestData {
+            quests: self
+                .quests
+                .iter()
+                .map(|(&id, progress)| {
+                    (
+                        id,
+                        QuestProgressData {
+                            status: progress.state.into(),
+                            active_node_ids: progress.active_nodes.iter().copied().collect(),
+                            finished_node_ids: progress.finished_nodes.iter().copied().collect(),
+                        },
+                    )
+                })
+                .collect(),
+            trace_quest_id: self.trace_quest_id,
+        }
+    }
+
+    pub fn state(&self, quest_id: i32) -> QuestState {
+        self.quests
+            .get(&quest_id)
+            .map(|progress| progress.state)
+            .unwrap_or(QuestState::InActive)
+    }
+
+    #[inline]
+    pub fn is_finished(&self, quest_id: i32) -> bool {
+        self.state(quest_id) == QuestState::Finish
+    }
+
+    #[inline]
+    pub fn trace_quest_id(&self) -> i32 {
+        self.trace_quest_id
+    }
+
+    pub fn check_accept(
+        &self,
+        quest_id: i32,
+        player_level: i32,
+    ) -> Result<&'static QuestData, QuestError> {
+        let data = quest_data::iter()
+            .find(|d| d.id == quest_id)
+            .ok_or(QuestError::NotExist(quest_id))?;
+
+        match self.state(quest_id) {
+            QuestState::InActive => (),
+            QUEST_STATE_READY => (),
+            _ => return Err(QuestError::AlreadyAccepted(quest_id)),
+        }
+
+        if !self.can_accept(data, player_level) {
+            return Err(QuestError::CanNotAccept(quest_id));
+        }
+
+        Ok(data)
+    }
+
+    // Activates root node of the quest tree
+    pub fn accept(&mut self, data: &QuestData) {
+        self.quests.insert(
+            data.id,
+            QuestProgress {
+                state: QUEST_STATE_PROGRESS,
+                active_nodes: data.nodes.first().map(|node| node.id).into_iter().collect(),
+                finished_nodes: HashSet::new(),
+            },
+        );
+    }
+
+    // Finishes node and activates its children, returns finished node data
+    pub fn finish_node(
+        &mut self,
+        quest_id: i32,
+        node_id: i32,
+    ) -> Result<&'static QuestNodeData, QuestError> {
+        let progress = self
+            .quests
+            .get_mut(&quest_id)
+            .filter(|progress| progress.state == QUEST_STATE_PROGRESS)
+            .ok_or(QuestError::NotInProgress(quest_id))?;
+
+        let data = quest_data::iter()
+            .find(|d| d.id == quest_id)
+            .ok_or(QuestError::NotExist(quest_id))?;
+
+        let node = data
+            .nodes
+            .iter()
+            .find(|node| node.id == node_id)
+            .ok_or(QuestError::NodeNotFound(quest_id, node_id))?;
+
+        if !progress.active_nodes.remove(&node_id) {
+            return Err(QuestError::NodeNotActive(quest_id, node_id));
+        }
+
+        progress.finished_nodes.insert(node_id);
+        progress.active_nodes.extend(
+            node.next_node_ids
+                .iter()
+                .filter(|id| !progress.finished_nodes.contains(id)),
+        );
+
+        Ok(node)
+    }
+
+    // Quest is complete once there are no more active nodes in the tree
+    pub fn is_tree_complete(&self, quest_id: i32) -> bool {
+        self.quests.get(&quest_id).is_some_and(|progress| {
+            progress.state == QUEST_STATE_PROGRESS && progress.active_nodes.is_empty()
+        })
+    }
+
+    pub fn finish(&mut self, quest_id: i32) {
+        if let Some(progress) = self.quests.get_mut(&quest_id) {
+            progress.state = QuestState::Finish;
+            progress.active_nodes.clear();
+        }
+
+        if self.trace_quest_id == quest_id {
+            self.trace_quest_id = 0;
+        }
+    }
+
+    pub fn check_finished(&self, quest_id: i32) -> Result<(), QuestError> {
+        match self.is_finished(quest_id) {
+            true => Ok(()),
+            false => Err(QuestError::NotFinished(quest_id)),
+        }
+    }
+
+    pub fn trace(&mut self, quest_id: i32) -> Result<(), QuestError> {
+        if self.state(quest_id) != QUEST_STATE_PROGRESS {
+            return Err(QuestError::NotInProgress(quest_id));
+        }
+
+        self.trace_quest_id = quest_id;
+        Ok(())
+    }
+
+    // Marks every quest whose requirements are met as ready, returns newly ready quests
+    pub fn update_ready(&mut self, player_level: i32) -> Vec<&'static QuestData> {
+        let ready = quest_data::iter()
+            .filter(|data| self.state(data.id) == QuestState::InActive)
+            .filter(|data| self.can_accept(data, player_level))
+            .collect::<Vec<_>>();
+
+        for data in ready.iter() {
+            self.quests.insert(
+                data.id,
+                QuestProgress {
+                    state: QUEST_STATE_READY,
+                    active_nodes: HashSet::new(),
+                    finished_nodes: HashSet::new(),
+                },
+            );
+        }
+
+        ready
+    }
+
+    pub fn build_quest_info(&self, quest_id: i32) -> QuestInfo {
+        QuestInfo {
+            quest_id,
+            status: self.state(quest_id).into(),
+        }
+    }
+
+    pub fn build_quest_list_notify(&self) -> QuestListNotify {
+        QuestListNotify {
+            quests: self
+                .quests
+                .keys()
+                .map(|&id| self.build_quest_info(id))
+                .collect(),
+        }
+    }
+
+    pub fn build_ready_list_notify(&self) -> QuestReadyListNotify {
+        QuestReadyListNotify {
+            quest_id: self
+                .quests
+                .iter()
+                .filter(|(_, progress)| progress.state == QUEST_STATE_READY)
+                .map(|(&id, _)| id)
+                .collect(),
+        }
+    }
+
+    fn can_accept(&self, data: &QuestData, player_level: i32) -> bool {
+        player_level >= data.accept_level
+            && data.pre_quest_ids.iter().all(|&id| self.is_finished(id))
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+
+use shorekeeper_data::{energy_data, energy_item_data, EnergyData};
+use shorekeeper_protocol::{EnergyInfo, EnergySaveData, ErrorCode, PlayerResourceData};
+
+#[derive(Clone, Copy)]
+struct EnergyState {
+    count: i32,
+    // Regeneration is paused while energy is full, the timer restarts on consumption
+    last_renew_time: i64,
+}
+
+// Time regenerated resources, values are regenerated lazily from stored timestamps
+#[derive(Default)]
+pub struct PlayerResource {
+    energies: HashMap<i32, EnergyState>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ResourceError {
+    #[error("energy with type {0} doesn't exist")]
+    NotExist(i32),
+    #[error("invalid count {1} for energy with type {0}")]
+    InvalidCount(i32, i32),
+    #[error("energy with type {0} has count {1}, but {2} requested")]
+    NotEnough(i32, i32, i32),
+    #[error("energy with type {0} would exceed its storage limit")]
+    Overflow(i32),
+}
+
+impl From<ResourceError> for ErrorCode {
+    fn from(err: ResourceError) -> Self {
+        match err {
+            ResourceError::NotExist(_) => ErrorCode::ErrItemInvalidParams,
+            ResourceError::InvalidCount(..) => ErrorCode::ErrItemInvalidParams,
+            ResourceError::NotEnough(..) => ErrorCode::ErrItemNotEnough,
+            ResourceError::Overflow(_) => ErrorCode::ErrEnergyMaxCharge,
+        }
+    }
+}
+
+impl PlayerResource {
+    pub fn load_from_save(data: PlayerResourceData) -> Self {
+        Self {
+            energies: data
+                .energies
+                .into_iter()
+                .map(|(energy_type, energy)| {
+                    (
+                        energy_type,
+                        EnergyState {
+                            count: energy.count,
+                            last_renew_time: energy.last_renew_time,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn build_save_data(&self) -> PlayerResourceData {
+        PlayerResourceData {
+            energies: self
+                .energies
+                .iter()
+                .map(|(&energy_type, energy)| {
+                    (
+                        energy_type,
+                        EnergySaveData {
+                            count: energy.count,
+                            last_renew_time: energy.last_renew_time,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn check_consume(
+        &self,
+        energy_type: i32,
+        count: i32,
+        now: i64,
+    ) -> Result<(), ResourceError> {
+        let conf = Self::conf(energy_type).ok_or(ResourceError::NotExist(energy_type))?;
+        if count < 0 {
+            return Err(ResourceError::InvalidCount(energy_type, count));
+        }
+
+        let state = self.current(conf, now);
+        match state.count < count {
+            true => Err(ResourceError::NotEnough(energy_type, state.count, count)),
+            false => Ok(()),
+        }
+    }
+
+    // Returns energy left after consumption
+    pub fn consume(
+        &mut self,
+        energy_type: i32,
+        count: i32,
+        now: i64,
+    ) -> Result<i32, ResourceError> {
+        self.check_consume(energy_type, count, now)?;
+
+        let conf = Self::conf(energy_type).ok_or(ResourceError::NotExist(energy_type))?;
+        let mut state = self.current(conf, now);
+        state.count -= count;
+        self.energies.insert(energy_type, state);
+        Ok(state.count)
+    }
+
+    // Gives back energy consumed by a failed operation, regular cap is ignored
+    pub fn refund(&mut self, energy_type: i32, count: i32, now: i64) {
+        let Some(conf) = Self::conf(energy_type) else {
+            return;
+        };
+
+        let mut state = self.current(conf, now);
+        state.count = state
+            .count
+            .saturating_add(count.max(0))
+            .min(conf.overflow_max.max(conf.max_count));
+        self.energies.insert(energy_type, state);
+    }
+
+    // Adds energy from refill items, may exceed regular cap up to storage limit
+    pub fn check_add(&self, energy_type: i32, count: i32, now: i64) -> Result<(), ResourceError> {
+        let conf = Self::conf(energy_type).ok_or(ResourceError::NotExist(energy_type))?;
+        if count <= 0 {
+            return Err(ResourceError::InvalidCount(energy_type, count));
+        }
+
+        let state = self.current(conf, now);
+        match state.count.saturating_add(count) > conf.overflow_max.max(conf.max_count) {
+            true => Err(ResourceError::Overflow(energy_type)),
+            false => Ok(()),
+        }
+    }
+
+    pub fn add(&mut self, energy_type: i32, count: i32, now: i64) -> Result<i32, ResourceError> {
+        self.check_add(energy_type, count, now)?;
+
+        let conf = Self::conf(energy_type).ok_or(ResourceError::NotExist(energy_type))?;
+        let mut state = self.current(conf, now);
+        state.count += count;
+        self.energies.insert(energy_type, state);
+        Ok(state.count)
+    }
+
+    // Energy type and amount restored by using the item, None if item doesn't refill energy
+    pub fn refill_of(item_id: i32, item_count: i32) -> Option<(i32, i32)> {
+        energy_item_data::iter()
+            .find(|data| data.item_id == item_id)
+            .map(|data| (data.energy_type, data.count.saturating_mul(item_count)))
+    }
+
+    pub fn build_energy_info(&self, energy_type: i32, now: i64) -> EnergyInfo {
+        let state = Self::conf(energy_type)
+            .map(|conf| self.current(conf, now))
+            .unwrap_or(EnergyState {
+                count: 0,
+                last_renew_time: now,
+            });
+
+        EnergyInfo {
+            energy_count: state.count,
+            last_renew_energy_time: state.last_renew_time as i32,
+            energy_type,
+        }
+    }
+
+    // Returns all energies if no types are specified
+    pub fn build_energy_info_list(&self, energy_types: &[i32], now: i64) -> Vec<EnergyInfo> {
+        match energy_types.is_empty() {
+            true => energy_data::iter()
+                .map(|conf| self.build_energy_info(conf.energy_type, now))
+                .collect(),
+            false => energy_types
+                .iter()
+                .filter(|&&energy_type| Self::conf(energy_type).is_some())
+                .map(|&energy_type| self.build_energy_info(energy_type, now))
+                .collect(),
+        }
+    }
+
+    fn conf(energy_type: i32) -> Option<&'static EnergyData> {
+        energy_data::iter().find(|data| data.energy_type == energy_type)
+    }
+
+    // Energy state with regeneration applied up to now, untouched energy starts full
+    fn current(&self, conf: &EnergyData, now: i64) -> EnergyState {
+        let Some(&state) = self.energies.get(&conf.energy_type) else {
+            return EnergyState {
+                count: conf.max_count,
+                last_renew_time: now,
+            };
+        };
+
+        if state.count >= conf.max_count || conf.renew_interval <= 0 {
+            return EnergyState {
+                count: state.count,
+                last_renew_time: now,
+            };
+        }
+
+        let interval = conf.renew_interval as i64;
+        let ticks = (now - state.last_renew_time).max(0) / interval;
+        let count = (state.count as i64 + ticks * conf.renew_count as i64)
+            .min(conf.max_count as i64) as i32;
+
+        match count >= conf.max_count {
+            true => EnergyState {
+                count,
+                last_renew_time: now,
+            },
+            false => EnergyState {
+                count,
+                last_renew_time: state.last_renew_time + ticks * interval,
+            },
+        }
+    }
+}
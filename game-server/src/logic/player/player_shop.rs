@@ -0,0 +1,333 @@
+use std::collections::{BTreeSet, HashMap};
+
+use common::time_util;
+use shorekeeper_data::{pay_shop_item_data, shop_item_data, PayShopItemData, ShopItemData};
+use shorekeeper_protocol::{
+    ErrorCode, PayShopInfo, PayShopItem, PayShopItemType, PayShopPrice, PayShopUpdateType,
+    PlayerShopData, ShopInfo, ShopItemInfoNew, ShopPurchaseData, ShoppMoneyInfo,
+};
+
+#[derive(Default, Clone, Copy)]
+struct Purchase {
+    bought_count: i32,
+    // 0 if purchase limit never resets
+    reset_time: i64,
+}
+
+#[derive(Default)]
+pub struct PlayerShop {
+    shop_items: HashMap<i32, Purchase>,
+    pay_shop_items: HashMap<i32, Purchase>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ShopError {
+    #[error("shop with id {0} doesn't exist")]
+    ShopNotExist(i32),
+    #[error("item with id {1} doesn't exist in shop {0}")]
+    ItemNotExist(i32, i32),
+    #[error("shop item {0} can't be bought with money {1}")]
+    MoneyMismatch(i32, i32),
+    #[error("invalid buy count {1} for shop item {0}")]
+    InvalidCount(i32, i32),
+    #[error("shop item {0} reached its purchase limit")]
+    LimitReached(i32),
+    #[error("pay shop item with id {0} doesn't exist")]
+    PayItemNotExist(i32),
+    #[error("pay shop item {0} can only be bought directly")]
+    PayItemIsDirect(i32),
+    #[error("pay shop item {0} can't be bought directly")]
+    PayItemIsNotDirect(i32),
+    #[error("invalid buy count {1} for pay shop item {0}")]
+    PayInvalidCount(i32, i32),
+    #[error("pay shop item {0} reached its purchase limit")]
+    PayLimitReached(i32),
+}
+
+impl From<ShopError> for ErrorCode {
+    fn from(err: ShopError) -> Self {
+        match err {
+            ShopError::ShopNotExist(_) => ErrorCode::ErrShopIdNotExit,
+            ShopError::ItemNotExist(..) => ErrorCode::ErrShopIlligalParam,
+            ShopError::MoneyMismatch(..) => ErrorCode::ErrShopMoneyId,
+            ShopError::InvalidCount(..) => ErrorCode::ErrShopIllegalBuyCount,
+            ShopError::LimitReached(_) => ErrorCode::ErrShopNumLimit,
+            ShopError::PayItemNotExist(_) => ErrorCode::ErrPayShopGoodsNotExists,
+            ShopError::PayItemIsDirect(_) => ErrorCode::ErrPayShopIsDirect,
+            ShopError::PayItemIsNotDirect(_) => ErrorCode::ErrPayShopIsNotDirect,
+            ShopError::PayInvalidCount(..) => ErrorCode::ErrPayShopIllegalBuyCount,
+            ShopError::PayLimitReached(_) => ErrorCode::ErrPayShopGoodsBuyLimit,
+        }
+    }
+}
+
+impl Purchase {
+    fn load_from_save(data: ShopPurchaseData) -> Self {
+        Self {
+            bought_count: data.bought_count,
+            reset_time: data.reset_time,
+        }
+    }
+
+    fn build_save_data(&self) -> ShopPurchaseData {
+        ShopPurchaseData {
+            bought_count: self.bought_count,
+            reset_time: self.reset_time,
+        }
+    }
+
+    fn bought_count(&self, now: i64) -> i32 {
+        match self.reset_time != 0 && now >= self.reset_time {
+            true => 0,
+            false => self.bought_count,
+        }
+    }
+
+    fn add(&mut self, count: i32, update_type: i32, now: i64) -> i32 {
+        self.bought_count = self.bought_count(now) + count;
+        self.reset_time = next_reset_time(update_type, now);
+        self.bought_count
+    }
+}
+
+impl PlayerShop {
+    // Upper bound of items bought by a single request
+    const MAX_BUY_COUNT: i32 = 999;
+
+    pub fn load_from_save(data: PlayerShopData) -> Self {
+        Self {
+            shop_items: data
+                .shop_items
+                .into_iter()
+                .map(|(id, purchase)| (id, Purchase::load_from_save(purchase)))
+                .collect(),
+            pay_shop_items: data
+                .pay_shop_items
+                .into_iter()
+                .map(|(id, purchase)| (id, Purchase::load_from_save(purchase)))
+                .collect(),
+        }
+    }
+
+    pub fn build_save_data(&self) -> PlayerShopData {
+        PlayerShopData {
+            shop_items: self
+                .shop_items
+                .iter()
+                .map(|(&id, purchase)| (id, purchase.build_save_data()))
+                .collect(),
+            pay_shop_items: self
+                .pay_shop_items
+                .iter()
+                .map(|(&id, purchase)| (id, purchase.build_save_data()))
+                .collect(),
+        }
+    }
+
+    pub fn check_buy(
+        &self,
+        shop_id: i32,
+        id: i32,
+        money_id: i32,
+        num: i32,
+        now: i64,
+    ) -> Result<&'static ShopItemData, ShopError> {
+        if !shop_item_data::iter().any(|d| d.shop_id == shop_id) {
+            return Err(ShopError::ShopNotExist(shop_id));
+        }
+
+        let data = shop_item_data::iter()
+            .find(|d| d.shop_id == shop_id && d.id == id)
+            .ok_or(ShopError::ItemNotExist(shop_id, id))?;
+
+        if data.money_id != money_id {
+            return Err(ShopError::MoneyMismatch(id, money_id));
+        }
+
+        if num <= 0 || num > Self::MAX_BUY_COUNT {
+            return Err(ShopError::InvalidCount(id, num));
+        }
+
+        let bought_count = self.bought_count(id, now);
+        if data.limit_num != 0 && bought_count + num > data.limit_num {
+            return Err(ShopError::LimitReached(id));
+        }
+
+        Ok(data)
+    }
+
+    // Returns total bought count of the item in current refresh window
+    pub fn on_bought(&mut self, data: &ShopItemData, num: i32, now: i64) -> i32 {
+        self.shop_items
+            .entry(data.id)
+            .or_default()
+            .add(num, data.update_type, now)
+    }
+
+    pub fn check_pay_buy(
+        &self,
+        id: i32,
+        count: i32,
+        direct: bool,
+        now: i64,
+    ) -> Result<&'static PayShopItemData, ShopError> {
+        let data = pay_shop_item_data::iter()
+            .find(|d| d.id == id)
+            .ok_or(ShopError::PayItemNotExist(id))?;
+
+        match (Self::is_direct(data), direct) {
+            (true, false) => return Err(ShopError::PayItemIsDirect(id)),
+            (false, true) => return Err(ShopError::PayItemIsNotDirect(id)),
+            _ => (),
+        }
+
+        if count <= 0 || count > Self::MAX_BUY_COUNT {
+            return Err(ShopError::PayInvalidCount(id, count));
+        }
+
+        let bought_count = self.pay_bought_count(id, now);
+        if data.buy_limit != 0 && bought_count + count > data.buy_limit {
+            return Err(ShopError::PayLimitReached(id));
+        }
+
+        Ok(data)
+    }
+
+    pub fn on_pay_bought(&mut self, data: &PayShopItemData, count: i32, now: i64) -> i32 {
+        self.pay_shop_items
+            .entry(data.id)
+            .or_default()
+            .add(count, data.update_type, now)
+    }
+
+    pub fn build_shop_info_list(&self, now: i64) -> Vec<ShopInfo> {
+        shop_item_data::iter()
+            .map(|d| d.shop_id)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter_map(|shop_id| self.build_shop_info(shop_id, now))
+            .collect()
+    }
+
+    pub fn build_shop_info(&self, shop_id: i32, now: i64) -> Option<ShopInfo> {
+        let items = shop_item_data::iter()
+            .filter(|d| d.shop_id == shop_id)
+            .collect::<Vec<_>>();
+
+        if items.is_empty() {
+            return None;
+        }
+
+        Some(ShopInfo {
+            shop_id,
+            update_time: next_shop_update_time(items.iter().map(|d| d.update_type), now) as u32,
+            item_info_list: items
+                .into_iter()
+                .map(|d| ShopItemInfoNew {
+                    id: d.id,
+                    bought_count: self.bought_count(d.id, now),
+                    item_id: d.item_id,
+                    item_num: d.item_num,
+                    money_list: vec![ShoppMoneyInfo {
+                        money_id: d.money_id,
+                        money_num: d.price,
+                    }],
+                    limit_num: d.limit_num,
+                    original_money_list: vec![ShoppMoneyInfo {
+                        money_id: d.money_id,
+                        money_num: d.original_price,
+                    }],
+                    ..Default::default()
+                })
+                .collect(),
+        })
+    }
+
+    pub fn build_pay_shop_info_list(&self, now: i64) -> Vec<PayShopInfo> {
+        pay_shop_item_data::iter()
+            .map(|d| d.shop_id)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter_map(|shop_id| self.build_pay_shop_info(shop_id, now))
+            .collect()
+    }
+
+    pub fn build_pay_shop_info(&self, shop_id: i32, now: i64) -> Option<PayShopInfo> {
+        let items = pay_shop_item_data::iter()
+            .filter(|d| d.shop_id == shop_id)
+            .collect::<Vec<_>>();
+
+        if items.is_empty() {
+            return None;
+        }
+
+        Some(PayShopInfo {
+            id: shop_id,
+            update_time: next_shop_update_time(items.iter().map(|d| d.update_type), now),
+            items: items
+                .into_iter()
+                .map(|d| self.build_pay_shop_item(d, now))
+                .collect(),
+        })
+    }
+
+    pub fn build_pay_shop_item(&self, data: &PayShopItemData, now: i64) -> PayShopItem {
+        let bought_count = self.pay_bought_count(data.id, now);
+        PayShopItem {
+            id: data.id,
+            item_id: data.item_id,
+            item_count: data.item_count,
+            buy_limit: data.buy_limit,
+            bought_count,
+            price: Some(PayShopPrice {
+                id: data.price_id,
+                count: data.price_count,
+                promotion_count: data.price_count,
+            }),
+            update_type: data.update_type,
+            update_time: next_reset_time(data.update_type, now),
+            shop_item_type: data.shop_item_type,
+            can_buy_goods: data.buy_limit == 0 || bought_count < data.buy_limit,
+            ..Default::default()
+        }
+    }
+
+    // Direct items are paid through payment provider instead of in-game currency
+    #[inline]
+    pub fn is_direct(data: &PayShopItemData) -> bool {
+        data.shop_item_type == PayShopItemType::Direct as i32
+    }
+
+    fn bought_count(&self, id: i32, now: i64) -> i32 {
+        self.shop_items
+            .get(&id)
+            .map(|purchase| purchase.bought_count(now))
+            .unwrap_or_default()
+    }
+
+    fn pay_bought_count(&self, id: i32, now: i64) -> i32 {
+        self.pay_shop_items
+            .get(&id)
+            .map(|purchase| purchase.bought_count(now))
+            .unwrap_or_default()
+    }
+}
+
+// Time when purchase limit of the given update type resets, 0 if it never does
+fn next_reset_time(update_type: i32, now: i64) -> i64 {
+    let now = now as u64;
+    match PayShopUpdateType::try_from(update_type) {
+        Ok(PayShopUpdateType::Daily) => time_util::next_daily_reset(now) as i64,
+        Ok(PayShopUpdateType::Weekly) => time_util::next_weekly_reset(now) as i64,
+        _ => 0,
+    }
+}
+
+// Nearest reset time among items of the shop
+fn next_shop_update_time(update_types: impl Iterator<Item = i32>, now: i64) -> i64 {
+    update_types
+        .map(|update_type| next_reset_time(update_type, now))
+        .filter(|&time| time != 0)
+        .min()
+        .unwrap_or_default()
+}
@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+
+use shorekeeper_protocol::{ErrorCode, FriendApply, FriendInfo, PlayerBasicData, PlayerDetails};
+
+// Snapshot of another player's public info, taken from their last saved data
+#[derive(Clone, Default)]
+pub struct SocialPlayer {
+    pub player_id: i32,
+    pub name: String,
+    pub level: i32,
+    pub head_photo: i32,
+    pub head_frame: i32,
+    pub is_online: bool,
+}
+
+#[derive(Clone)]
+pub struct Friend {
+    pub info: SocialPlayer,
+    pub remark: String,
+}
+
+#[derive(Clone)]
+pub struct FriendApplication {
+    pub info: SocialPlayer,
+    pub create_time: i64,
+}
+
+// Changes caused by other players, routed to logic thread of the affected player
+pub enum SocialEvent {
+    ApplyReceived(FriendApplication),
+    FriendAdded(Friend),
+    FriendDeleted(i32),
+    PlayerInfo(SocialPlayer),
+    // Request was accepted locally, but the other side turned it down
+    RequestFailed(SocialError),
+}
+
+// Friend list, incoming applications and block list, persisted in Postgres via social_task
+#[derive(Default)]
+pub struct PlayerSocial {
+    friends: HashMap<i32, Friend>,
+    applications: HashMap<i32, FriendApplication>,
+    blocks: HashMap<i32, SocialPlayer>,
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy)]
+pub enum SocialError {
+    #[error("player can't apply to themselves")]
+    ApplyToSelf,
+    #[error("player with id {0} doesn't exist")]
+    PlayerNotExist(i32),
+    #[error("player is blocked by player with id {0}")]
+    BlockedByTarget(i32),
+    #[error("friend list of player with id {0} is full")]
+    TargetFriendListFull(i32),
+    #[error("player with id {0} is already a friend")]
+    AlreadyFriend(i32),
+    #[error("player with id {0} is not a friend")]
+    NotFriend(i32),
+    #[error("friend list is full")]
+    FriendListFull,
+    #[error("friend application from player with id {0} doesn't exist")]
+    ApplyNotExists(i32),
+    #[error("player with id {0} is blocked")]
+    Blocked(i32),
+    #[error("player with id {0} is not blocked")]
+    NotBlocked(i32),
+    #[error("block list is full")]
+    BlockListFull,
+    #[error("remark length {0} exceeds the limit")]
+    RemarkLengthLimit(usize),
+}
+
+impl From<SocialError> for ErrorCode {
+    fn from(err: SocialError) -> Self {
+        match err {
+            SocialError::ApplyToSelf => ErrorCode::ErrCanNotFriendApplySendToSelf,
+            SocialError::PlayerNotExist(_) => ErrorCode::ErrorInvitePlayerNotExist,
+            SocialError::BlockedByTarget(_) => ErrorCode::ErrYouAreBlocked,
+            SocialError::TargetFriendListFull(_) => ErrorCode::ErrInitiatorFriendListCountMax,
+            SocialError::AlreadyFriend(_) => ErrorCode::ErrAlreadyOnFriendList,
+            SocialError::NotFriend(_) => ErrorCode::ErrNotOnFriendList,
+            SocialError::FriendListFull => ErrorCode::ErrFriendListCountMax,
+            SocialError::ApplyNotExists(_) => ErrorCode::ErrFriendApplyNotExists,
+            SocialError::Blocked(_) => ErrorCode::ErrIsBlockedPlayer,
+            SocialError::NotBlocked(_) => ErrorCode::ErrIsNotBlockedPlayer,
+            SocialError::BlockListFull => ErrorCode::ErrBlockListCountMax,
+            SocialError::RemarkLengthLimit(_) => ErrorCode::ErrFriendRemarkLengthLimit,
+        }
+    }
+}
+
+impl SocialPlayer {
+    pub fn from_basic_data(data: &PlayerBasicData, is_online: bool) -> Self {
+        Self {
+            player_id: data.id,
+            name: data.name.clone(),
+            level: data.level,
+            head_photo: data.head_photo,
+            head_frame: data.head_frame,
+            is_online,
+        }
+    }
+
+    pub fn to_protobuf(&self) -> PlayerDetails {
+        PlayerDetails {
+            player_id: self.player_id,
+            name: self.name.clone(),
+            level: self.level,
+            head_id: self.head_photo,
+            head_frame_id: self.head_frame,
+            is_online: self.is_online,
+            ..Default::default()
+        }
+    }
+}
+
+impl Friend {
+    pub fn to_protobuf(&self) -> FriendInfo {
+        FriendInfo {
+            info: Some(self.info.to_protobuf()),
+            remark: self.remark.clone(),
+        }
+    }
+}
+
+impl FriendApplication {
+    pub fn to_protobuf(&self) -> FriendApply {
+        FriendApply {
+            info: Some(self.info.to_protobuf()),
+            created_time: self.create_time,
+        }
+    }
+}
+
+impl PlayerSocial {
+    pub const MAX_FRIENDS: usize = 100;
+    pub const MAX_BLOCKS: usize = 50;
+    pub const MAX_REMARK_LENGTH: usize = 32;
+
+    pub fn new(
+        friends: Vec<Friend>,
+        applications: Vec<FriendApplication>,
+        blocks: Vec<SocialPlayer>,
+    ) -> Self {
+        Self {
+            friends: friends
+                .into_iter()
+                .map(|friend| (friend.info.player_id, friend))
+                .collect(),
+            applications: applications
+                .into_iter()
+                .map(|apply| (apply.info.player_id, apply))
+                .collect(),
+            blocks: blocks
+                .into_iter()
+                .map(|info| (info.player_id, info))
+                .collect(),
+        }
+    }
+
+    pub fn friends(&self) -> impl Iterator<Item = &Friend> {
+        self.friends.values()
+    }
+
+    pub fn applications(&self) -> impl Iterator<Item = &FriendApplication> {
+        self.applications.values()
+    }
+
+    pub fn blocks(&self) -> impl Iterator<Item = &SocialPlayer> {
+        self.blocks.values()
+    }
+
+    #[inline]
+    pub fn is_friend(&self, player_id: i32) -> bool {
+        self.friends.contains_key(&player_id)
+    }
+
+    #[inline]
+    pub fn is_blocked(&self, player_id: i32) -> bool {
+        self.blocks.contains_key(&player_id)
+    }
+
+    pub fn check_apply(&self, player_id: i32, target_id: i32) -> Result<(), SocialError> {
+        if player_id == target_id {
+            return Err(SocialError::ApplyToSelf);
+        }
+
+        if self.friends.contains_key(&target_id) {
+            return Err(SocialError::AlreadyFriend(target_id));
+        }
+
+        if self.blocks.contains_key(&target_id) {
+            return Err(SocialError::Blocked(target_id));
+        }
+
+        self.check_friend_capacity()
+    }
+
+    pub fn check_handle_apply(&self, applicant_id: i32, approve: bool) -> Result<(), SocialError> {
+        if !self.applications.contains_key(&applicant_id) {
+            return Err(SocialError::ApplyNotExists(applicant_id));
+        }
+
+        match approve {
+            true => self.check_friend_capacity(),
+            false => Ok(()),
+        }
+    }
+
+    // Removes application, approved sender is added as a friend by FriendAdded event
+    // once social_task has checked their side
+    pub fn handle_apply(&mut self, applicant_id: i32, approve: bool) -> Result<(), SocialError> {
+        self.check_handle_apply(applicant_id, approve)?;
+        self.applications.remove(&applicant_id);
+        Ok(())
+    }
+
+    pub fn delete_friend(&mut self, friend_id: i32) -> Result<(), SocialError> {
+        self.friends
+            .remove(&friend_id)
+            .map(|_| ())
+            .ok_or(SocialError::NotFriend(friend_id))
+    }
+
+    pub fn set_remark(&mut self, friend_id: i32, remark: &str) -> Result<(), SocialError> {
+        let length = remark.chars().count();
+        if length > Self::MAX_REMARK_LENGTH {
+            return Err(SocialError::RemarkLengthLimit(length));
+        }
+
+        let friend = self
+            .friends
+            .get_mut(&friend_id)
+            .ok_or(SocialError::NotFriend(friend_id))?;
+
+        friend.remark = remark.to_string();
+        Ok(())
+    }
+
+    // Blocking removes the player from friends and applications,
+    // returns whatever is known about them locally
+    pub fn block(&mut self, player_id: i32, target_id: i32) -> Result<SocialPlayer, SocialError> {
+        if player_id == target_id {
+            return Err(SocialError::ApplyToSelf);
+        }
+
+        if self.blocks.contains_key(&target_id) {
+            return Err(SocialError::Blocked(target_id));
+        }
+
+        if self.blocks.len() >= Self::MAX_BLOCKS {
+            return Err(SocialError::BlockListFull);
+        }
+
+        let info = self
+            .friends
+            .remove(&target_id)
+            .map(|friend| friend.info)
+            .or_else(|| self.applications.remove(&target_id).map(|apply| apply.info))
+            .unwrap_or_else(|| SocialPlayer {
+                player_id: target_id,
+                ..Default::default()
+            });
+
+        self.blocks.insert(target_id, info.clone());
+        Ok(info)
+    }
+
+    pub fn unblock(&mut self, target_id: i32) -> Result<(), SocialError> {
+        self.blocks
+            .remove(&target_id)
+            .map(|_| ())
+            .ok_or(SocialError::NotBlocked(target_id))
+    }
+
+    // Applies change made by other player, returns false if it was discarded
+    pub fn apply_event(&mut self, event: &SocialEvent) -> bool {
+        match event {
+            SocialEvent::ApplyReceived(apply) => {
+                let id = apply.info.player_id;
+                if self.blocks.contains_key(&id) || self.friends.contains_key(&id) {
+                    return false;
+                }
+
+                self.applications.insert(id, apply.clone());
+            }
+            SocialEvent::FriendAdded(friend) => {
+                let id = friend.info.player_id;
+                self.applications.remove(&id);
+                self.friends.insert(id, friend.clone());
+            }
+            SocialEvent::FriendDeleted(id) => {
+                self.applications.remove(id);
+                return self.friends.remove(id).is_some();
+            }
+            SocialEvent::PlayerInfo(info) => {
+                let id = info.player_id;
+                if let Some(friend) = self.friends.get_mut(&id) {
+                    friend.info = info.clone();
+                }
+
+                if let Some(apply) = self.applications.get_mut(&id) {
+                    apply.info = info.clone();
+                }
+
+                if let Some(blocked) = self.blocks.get_mut(&id) {
+                    *blocked = info.clone();
+                }
+            }
+            SocialEvent::RequestFailed(_) => (),
+        }
+
+        true
+    }
+
+    fn check_friend_capacity(&self) -> Result<(), SocialError> {
+        if self.friends.len() >= Self::MAX_FRIENDS {
+            return Err(SocialError::FriendListFull);
+        }
+
+        Ok(())
+    }
+}
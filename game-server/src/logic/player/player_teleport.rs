@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+
+use common::time_util;
+use shorekeeper_protocol::{ErrorCode, PlayerTeleportData, TeleportUpdateNotify};
+
+use crate::logic::math::Vector3f;
+use crate::logic::utils::teleport_util::{self, Teleporter};
+
+// Teleporters are unlocked by walking up to them, distance is in meters
+const UNLOCK_DISTANCE: f32 = 30.0;
+// Client that never reports TeleportFinish can't block transfers forever, in seconds
+const TRANSFER_TIMEOUT: u64 = 30;
+
+#[derive(Default)]
+pub struct PlayerTeleport {
+    unlocked_ids: HashSet<i32>,
+    // Start time of transfer, kept until client reports TeleportFinish or scene changes
+    transfer_start_time: Option<u64>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum TeleportError {
+    #[error("teleporter with id {0} doesn't exist")]
+    NotExist(i32),
+    #[error("teleporter with id {0} is not unlocked")]
+    NotUnlocked(i32),
+    #[error("teleporter with id {0} is already unlocked")]
+    AlreadyUnlocked(i32),
+    #[error("teleporter with id {0} is too far from the player")]
+    TooFar(i32),
+    #[error("another teleport is in progress")]
+    InProgress,
+}
+
+impl From<TeleportError> for ErrorCode {
+    fn from(err: TeleportError) -> Self {
+        match err {
+            TeleportError::NotExist(_) => ErrorCode::ErrTeleportIdNotExist,
+            TeleportError::NotUnlocked(_) => ErrorCode::ErrTeleportIdNotActivate,
+            TeleportError::AlreadyUnlocked(_) => ErrorCode::ErrTeleportIdAlreadyActivate,
+            TeleportError::TooFar(_) => ErrorCode::ErrTeleportPositionIllegal,
+            TeleportError::InProgress => ErrorCode::ErrPlayerIsTeleportCanNotDoTeleport,
+        }
+    }
+}
+
+impl PlayerTeleport {
+    pub fn load_from_save(data: PlayerTeleportData) -> Self {
+        Self {
+            unlocked_ids: data.unlocked_ids.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn build_save_data(&self) -> PlayerTeleportData {
+        PlayerTeleportData {
+            unlocked_ids: self.unlocked_ids.iter().copied().collect(),
+        }
+    }
+
+    pub fn unlocked_ids(&self) -> impl Iterator<Item = i32> + '_ {
+        self.unlocked_ids.iter().copied()
+    }
+
+    // Player has to stand next to the teleporter on the same map
+    pub fn unlock(
+        &mut self,
+        id: i32,
+        map_id: i32,
+        position: &Vector3f,
+    ) -> Result<TeleportUpdateNotify, TeleportError> {
+        let teleporter = teleport_util::get_teleporter(id).ok_or(TeleportError::NotExist(id))?;
+        if teleporter.map_id != map_id
+            || teleporter.transform.position.distance_squared(position)
+                > UNLOCK_DISTANCE * UNLOCK_DISTANCE
+        {
+            return Err(TeleportError::TooFar(id));
+        }
+
+        if !self.unlocked_ids.insert(id) {
+            return Err(TeleportError::AlreadyUnlocked(id));
+        }
+
+        Ok(TeleportUpdateNotify { ids: vec![id] })
+    }
+
+    pub fn start_transfer(&mut self, id: i32) -> Result<&'static Teleporter, TeleportError> {
+        let now = time_util::unix_timestamp();
+        if self
+            .transfer_start_time
+            .is_some_and(|start_time| now < start_time + TRANSFER_TIMEOUT)
+        {
+            return Err(TeleportError::InProgress);
+        }
+
+        let teleporter = teleport_util::get_teleporter(id).ok_or(TeleportError::NotExist(id))?;
+        if !self.unlocked_ids.contains(&id) {
+            return Err(TeleportError::NotUnlocked(id));
+        }
+
+        self.transfer_start_time = Some(now);
+        Ok(teleporter)
+    }
+
+    // Called on TeleportFinish and on scene change, which doesn't end with TeleportFinish
+    pub fn finish_transfer(&mut self) {
+        self.transfer_start_time = None;
+    }
+}
@@ -0,0 +1,121 @@
+use shorekeeper_data::{
+    weapon_breach_data, weapon_conf_data, weapon_level_data, WeaponBreachData, WeaponConfData,
+};
+use shorekeeper_protocol::{WeaponItem, WeaponItemData};
+
+pub struct WeaponInstance {
+    pub id: i32,
+    pub incr_id: i32,
+    pub func_value: i32,
+    pub role_id: i32,
+    pub level: i32,
+    pub exp: i32,
+    pub breach: i32,
+    pub reson_level: i32,
+}
+
+impl WeaponInstance {
+    pub fn new(id: i32, incr_id: i32, role_id: i32) -> Self {
+        Self {
+            id,
+            incr_id,
+            func_value: 0,
+            role_id,
+            level: 1,
+            exp: 0,
+            breach: 0,
+            reson_level: 1,
+        }
+    }
+
+    pub fn conf(&self) -> Option<&'static WeaponConfData> {
+        weapon_conf_data::get(self.id)
+    }
+
+    // Max level reachable with current breach level
+    pub fn level_limit(&self) -> i32 {
+        self.conf()
+            .and_then(|conf| {
+                weapon_breach_data::iter()
+                    .find(|d| d.breach_id == conf.breach_id && d.level == self.breach)
+            })
+            .map(|d| d.level_limit)
+            .unwrap_or(self.level)
+    }
+
+    pub fn next_breach(&self) -> Option<&'static WeaponBreachData> {
+        let conf = self.conf()?;
+        weapon_breach_data::iter()
+            .find(|d| d.breach_id == conf.breach_id && d.level == self.breach + 1)
+    }
+
+    // Adds experience and levels up until the level limit of current breach is reached,
+    // experience that doesn't fit is discarded
+    pub fn add_exp(&mut self, exp: i32) {
+        let Some(conf) = self.conf() else {
+            return;
+        };
+
+        let level_limit = self.level_limit();
+        self.exp = self.exp.saturating_add(exp);
+
+        while self.level < level_limit {
+            let Some(required) = weapon_level_data::iter()
+                .find(|d| d.level_id == conf.level_id && d.level == self.level)
+                .map(|d| d.exp)
+            else {
+                break;
+            };
+
+            if self.exp < required {
+                break;
+            }
+
+            self.exp -= required;
+            self.level += 1;
+        }
+
+        if self.level >= level_limit {
+            self.exp = 0;
+        }
+    }
+
+    pub fn to_protobuf(&self) -> WeaponItem {
+        WeaponItem {
+            id: self.id,
+            incr_id: self.incr_id,
+            func_value: self.func_value,
+            weapon_level: self.level,
+            weapon_exp: self.exp,
+            weapon_breach: self.breach,
+            weapon_reson_level: self.reson_level,
+            role_id: self.role_id,
+        }
+    }
+
+    pub fn load_from_save(data: WeaponItemData) -> Self {
+        Self {
+            id: data.id,
+            incr_id: data.incr_id,
+            func_value: data.func_value,
+            role_id: data.role_id,
+            level: data.level.max(1),
+            exp: data.exp,
+            breach: data.breach,
+            reson_level: data.reson_level.max(1),
+        }
+    }
+
+    pub fn build_save_data(&self) -> WeaponItemData {
+        WeaponItemData {
+            id: self.id,
+            incr_id: self.incr_id,
+            func_value: self.func_value,
+            role_id: self.role_id,
+            level: self.level,
+            exp: self.exp,
+            breach: self.breach,
+            reson_level: self.reson_level,
+        }
+    }
+}
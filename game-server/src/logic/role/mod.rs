@@ -1,12 +1,23 @@
 use std::collections::HashMap;
 
-use crate::logic::utils::load_role_info::load_key_value;
+use crate::logic::player::{ItemUsage, PlayerInventory};
+use crate::logic::utils::load_role_info::attribute_from_data;
 use common::time_util;
 pub use formation::RoleFormation;
-use shorekeeper_data::{base_property_data, role_info_data};
-use shorekeeper_protocol::{ArrayIntInt, RoleData, RoleInfo};
+use shorekeeper_data::{
+    base_property_data, role_breach_data, role_exp_item_data, role_info_data,
+    role_level_consume_data, role_property_growth_data, RoleBreachData, RoleInfoData,
+};
+use shorekeeper_protocol::{
+    ArrayIntDouble, ArrayIntInt, EAttributeType, ErrorCode, RoleData, RoleInfo,
+    RolePhantomEquipInfo,
+};
+pub use skill_tree::RoleSkillNode;
 
 mod formation;
+mod skill_tree;
+
+#[derive(Clone)]
 pub struct Role {
     pub role_id: i32,
     pub name: String,
@@ -14,39 +25,315 @@ pub struct Role {
     pub exp: i32,
     pub breakthrough: i32,
     pub skill_map: HashMap<i32, i32>,
+    pub skill_node_state: Vec<RoleSkillNode>,
+    pub resonant_chain_group_index: i32,
     pub star: i32,
     pub favor: i32,
     pub create_time: u32,
     pub equip_weapon: i32,
+    // Equipped phantoms, position -> incr_id
+    pub phantom_map: HashMap<i32, i32>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum RoleError {
+    #[error("role with id {0} reached its level limit")]
+    LevelMax(i32),
+    #[error("role with id {0} has level {1}, but {2} is required")]
+    LevelNotEnough(i32, i32, i32),
+    #[error("item list is empty")]
+    ItemListEmpty,
+    #[error("invalid count {1} for item with id {0}")]
+    ItemCountOutRange(i32, i32),
+    #[error("item with id {0} is not an exp item")]
+    ItemExpError(i32),
+    #[error("skill with id {0} not found")]
+    SkillNotFound(i32),
+    #[error("skill with id {0} reached its max level")]
+    SkillLevelMax(i32),
+    #[error("skill node with id {0} not found")]
+    SkillNodeNotFound(i32),
+    #[error("skill node with id {0} is already active")]
+    SkillNodeActive(i32),
+    #[error("skill node with id {0} is locked")]
+    SkillNodeLocked(i32),
+}
+
+impl From<RoleError> for ErrorCode {
+    fn from(err: RoleError) -> Self {
+        match err {
+            RoleError::LevelMax(_) => ErrorCode::ErrRoleLevelMax,
+            RoleError::LevelNotEnough(..) => ErrorCode::ErrRoleLevelNotEnough,
+            RoleError::ItemListEmpty => ErrorCode::ErrRoleItemListEmpty,
+            RoleError::ItemCountOutRange(..) => ErrorCode::ErrRoleItemListCountOutRange,
+            RoleError::ItemExpError(_) => ErrorCode::ErrRoleItemExpError,
+            RoleError::SkillNotFound(_) => ErrorCode::ErrRoleGetSkillByIdFailed,
+            RoleError::SkillLevelMax(_) => ErrorCode::ErrRoleLevelMax,
+            RoleError::SkillNodeNotFound(_) => ErrorCode::ErrRolSkillNodeType,
+            RoleError::SkillNodeActive(_) => ErrorCode::ErrRolSkillNodeTypeActive,
+            RoleError::SkillNodeLocked(_) => ErrorCode::ErrRolSkillNodeTypeUlock,
+        }
+    }
 }
 
 impl Role {
     pub const MAIN_CHARACTER_MALE_ID: i32 = 1501;
     pub const MAIN_CHARACTER_FEMALE_ID: i32 = 1502;
+    pub const PHANTOM_SLOT_COUNT: i32 = 5;
+    pub const PHANTOM_MAX_COST: i32 = 12;
 
     pub fn new(role_id: i32) -> Self {
-        let data = role_info_data::iter().find(|d| d.id == role_id).unwrap();
+        let data = role_info_data::get(role_id).unwrap();
 
-        Self {
+        let mut role = Self {
             role_id,
             name: String::with_capacity(0),
-            level: data.max_level,
+            level: 1,
             exp: 0,
             breakthrough: 0,
-            skill_map: HashMap::new(), // TODO!
+            skill_map: HashMap::new(),
+            skill_node_state: Vec::new(),
+            resonant_chain_group_index: 0,
             star: 0,
             favor: 0,
             create_time: time_util::unix_timestamp() as u32,
             equip_weapon: data.init_weapon_item_id,
+            phantom_map: HashMap::new(),
+        };
+
+        role.sync_skill_tree();
+        role
+    }
+
+    pub fn info(&self) -> &'static RoleInfoData {
+        role_info_data::get(self.role_id).unwrap()
+    }
+
+    // Max level reachable with current breakthrough level
+    pub fn level_limit(&self) -> i32 {
+        let info = self.info();
+        role_breach_data::iter()
+            .find(|d| d.breach_group_id == info.breach_id && d.breach_level == self.breakthrough)
+            .map(|d| d.max_level)
+            .unwrap_or(info.max_level)
+    }
+
+    pub fn next_breach(&self) -> Option<&'static RoleBreachData> {
+        let info = self.info();
+        role_breach_data::iter().find(|d| {
+            d.breach_group_id == info.breach_id && d.breach_level == self.breakthrough + 1
+        })
+    }
+
+    // Validates exp items and returns gained exp together with items (and gold) to consume
+    pub fn level_up_cost(&self, items: &[ArrayIntInt]) -> Result<(i32, Vec<ItemUsage>), RoleError> {
+        if self.level >= self.level_limit() {
+            return Err(RoleError::LevelMax(self.role_id));
+        }
+
+        if items.is_empty() {
+            return Err(RoleError::ItemListEmpty);
+        }
+
+        let mut usages = Vec::with_capacity(items.len() + 1);
+        let mut exp = 0i32;
+        let mut gold = 0i32;
+
+        for item in items.iter() {
+            if item.value <= 0 {
+                return Err(RoleError::ItemCountOutRange(item.key, item.value));
+            }
+
+            let exp_item = role_exp_item_data::iter()
+                .find(|d| d.id == item.key)
+                .ok_or(RoleError::ItemExpError(item.key))?;
+
+            exp = exp.saturating_add(exp_item.basic_exp.saturating_mul(item.value));
+            gold = gold.saturating_add(exp_item.cost.saturating_mul(item.value));
+            usages.push(ItemUsage {
+                id: item.key,
+                quantity: item.value,
+            });
         }
+
+        if gold > 0 {
+            usages.push(ItemUsage {
+                id: PlayerInventory::SHELL_CREDIT_ID,
+                quantity: gold,
+            });
+        }
+
+        Ok((exp, usages))
     }
 
-    pub fn to_protobuf(&self) -> RoleInfo {
-        let base_prop: HashMap<i32, i32> = load_key_value(
-            base_property_data::iter()
-                .find(|d| d.id == self.role_id)
-                .unwrap(),
+    // Adds experience and levels up until the level limit of current breakthrough is reached,
+    // experience that doesn't fit is discarded
+    pub fn add_exp(&mut self, exp: i32) {
+        let consume_id = self.info().level_consume_id;
+        (self.level, self.exp) = Self::apply_exp(
+            self.level,
+            self.exp.saturating_add(exp),
+            self.level_limit(),
+            |level| {
+                role_level_consume_data::iter()
+                    .find(|d| d.consume_group_id == consume_id && d.level == level)
+                    .map(|d| d.exp_count)
+            },
         );
+    }
+
+    // Returns (level, exp) after spending exp on levels, required_exp gives exp needed to leave a level
+    fn apply_exp(
+        mut level: i32,
+        mut exp: i32,
+        level_limit: i32,
+        required_exp: impl Fn(i32) -> Option<i32>,
+    ) -> (i32, i32) {
+        while level < level_limit {
+            let Some(required) = required_exp(level) else {
+                break;
+            };
+
+            if exp < required {
+                break;
+            }
+
+            exp -= required;
+            level += 1;
+        }
+
+        if level >= level_limit {
+            exp = 0;
+        }
+
+        (level, exp)
+    }
+
+    // Breakthrough requires role to reach level limit of current breakthrough
+    pub fn check_breakthrough_level(&self) -> Result<(), RoleError> {
+        let level_limit = self.level_limit();
+        if self.level < level_limit {
+            return Err(RoleError::LevelNotEnough(
+                self.role_id,
+                self.level,
+                level_limit,
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn breakthrough_cost(
+        &self,
+    ) -> Result<(&'static RoleBreachData, Vec<ItemUsage>), RoleError> {
+        let next_breach = self
+            .next_breach()
+            .ok_or(RoleError::LevelMax(self.role_id))?;
+
+        Ok((
+            next_breach,
+            Self::build_consume(next_breach.breach_consume.iter(), next_breach.gold_consume),
+        ))
+    }
+
+    // Base attributes scaled by level curves, skill tree and phantom bonuses go to increments
+    pub fn build_attributes(
+        &self,
+        inventory: &PlayerInventory,
+    ) -> HashMap<EAttributeType, (i32, i32)> {
+        let base_property = base_property_data::iter()
+            .find(|d| d.id == self.role_id)
+            .unwrap();
+        let mut attr_map = attribute_from_data(base_property);
+
+        if let Some(growth) = role_property_growth_data::iter().find(|d| {
+            d.role_id == self.role_id
+                && d.level == self.level
+                && d.breach_level == self.breakthrough
+        }) {
+            for (ty, ratio) in [
+                (EAttributeType::LifeMax, growth.life_max_ratio),
+                (EAttributeType::Atk, growth.atk_ratio),
+                (EAttributeType::Def, growth.def_ratio),
+            ] {
+                if let Some((base, _)) = attr_map.get_mut(&ty) {
+                    *base = Self::apply_ratio(*base, ratio);
+                }
+            }
+        }
+
+        for (ty, ratio) in self.skill_tree_properties() {
+            if let Some((base, incr)) = attr_map.get_mut(&ty) {
+                *incr += Self::apply_ratio(*base, ratio);
+            }
+        }
+
+        for bonus in inventory.build_phantom_bonuses(&self.equipped_phantoms()) {
+            if let Some((base, incr)) = attr_map.get_mut(&bonus.attribute_type) {
+                *incr += match bonus.is_ratio {
+                    true => Self::apply_ratio(*base, bonus.value),
+                    false => bonus.value,
+                };
+            }
+        }
+
+        attr_map.insert(EAttributeType::Lv, (self.level, 0));
+        let life_max = attr_map
+            .get(&EAttributeType::LifeMax)
+            .map(|(base, incr)| base + incr)
+            .unwrap_or_default();
+        attr_map.insert(EAttributeType::Life, (life_max, 0));
+
+        attr_map
+    }
+
+    // Incr ids of equipped phantoms
+    pub fn equipped_phantoms(&self) -> Vec<i32> {
+        self.phantom_map.values().copied().collect()
+    }
+
+    pub fn build_phantom_equip_info(&self) -> RolePhantomEquipInfo {
+        RolePhantomEquipInfo {
+            role_id: self.role_id,
+            phantom_incr_id_list: (0..Self::PHANTOM_SLOT_COUNT)
+                .map(|pos| self.phantom_map.get(&pos).copied().unwrap_or_default())
+                .collect(),
+        }
+    }
+
+    pub fn build_final_prop(&self, inventory: &PlayerInventory) -> Vec<ArrayIntDouble> {
+        self.build_attributes(inventory)
+            .iter()
+            .map(|(&ty, (base, incr))| ArrayIntDouble {
+                key: ty.into(),
+                value: (base + incr) as f64,
+            })
+            .collect()
+    }
+
+    pub fn build_base_prop(attr_map: &HashMap<EAttributeType, (i32, i32)>) -> Vec<ArrayIntInt> {
+        attr_map
+            .iter()
+            .map(|(&ty, &(base, _))| ArrayIntInt {
+                key: ty.into(),
+                value: base,
+            })
+            .collect()
+    }
+
+    pub fn build_add_prop(attr_map: &HashMap<EAttributeType, (i32, i32)>) -> Vec<ArrayIntInt> {
+        attr_map
+            .iter()
+            .filter(|(_, &(_, incr))| incr != 0)
+            .map(|(&ty, &(_, incr))| ArrayIntInt {
+                key: ty.into(),
+                value: incr,
+            })
+            .collect()
+    }
+
+    pub fn to_protobuf(&self, inventory: &PlayerInventory) -> RoleInfo {
+        let attr_map = self.build_attributes(inventory);
 
         RoleInfo {
             role_id: self.role_id,
@@ -62,30 +349,42 @@ impl Role {
                 .collect(),
             star: self.star,
             favor: self.favor,
-            base_prop: base_prop
+            base_prop: Self::build_base_prop(&attr_map),
+            add_prop: Self::build_add_prop(&attr_map),
+            skill_node_state: self
+                .skill_node_state
                 .iter()
-                .map(|(&k, &v)| ArrayIntInt { key: k, value: v })
+                .map(RoleSkillNode::to_protobuf)
                 .collect(),
+            resonant_chain_group_index: self.resonant_chain_group_index,
             ..Default::default()
         }
     }
 
     pub fn load_from_save(data: RoleData) -> (i32, Self) {
-        (
-            data.role_id,
-            Self {
-                role_id: data.role_id,
-                name: data.name,
-                level: data.level,
-                exp: data.exp,
-                breakthrough: data.breakthrough,
-                skill_map: data.skill_map,
-                star: data.star,
-                favor: data.favor,
-                create_time: data.create_time,
-                equip_weapon: data.equip_weapon,
-            },
-        )
+        let mut role = Self {
+            role_id: data.role_id,
+            name: data.name,
+            level: data.level,
+            exp: data.exp,
+            breakthrough: data.breakthrough,
+            skill_map: data.skill_map,
+            skill_node_state: data
+                .skill_node_state
+                .into_iter()
+                .map(RoleSkillNode::load_from_save)
+                .collect(),
+            resonant_chain_group_index: data.resonant_chain_group_index,
+            star: data.star,
+            favor: data.favor,
+            create_time: data.create_time,
+            equip_weapon: data.equip_weapon,
+            phantom_map: data.phantom_map,
+        };
+
+        role.sync_skill_tree();
+
+        (data.role_id, role)
     }
 
     pub fn build_save_data(&self) -> RoleData {
@@ -96,11 +395,70 @@ impl Role {
             exp: self.exp,
             breakthrough: self.breakthrough,
             skill_map: self.skill_map.clone(),
+            skill_node_state: self
+                .skill_node_state
+                .iter()
+                .map(RoleSkillNode::build_save_data)
+                .collect(),
+            resonant_chain_group_index: self.resonant_chain_group_index,
             star: self.star,
             favor: self.favor,
             create_time: self.create_time,
             equip_weapon: self.equip_weapon,
+            phantom_map: self.phantom_map.clone(),
             ..Default::default()
         }
     }
+
+    #[inline]
+    fn apply_ratio(value: i32, ratio: i32) -> i32 {
+        (value as i64 * ratio as i64 / 10000) as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Role;
+
+    // Every level requires 100 exp more than the previous one, starting with 100 at level 1
+    fn required_exp(level: i32) -> Option<i32> {
+        (level < 90).then_some(level * 100)
+    }
+
+    #[test]
+    fn apply_exp_keeps_level_when_exp_is_not_enough() {
+        assert_eq!(Role::apply_exp(1, 99, 20, required_exp), (1, 99));
+    }
+
+    #[test]
+    fn apply_exp_levels_up_several_times_and_keeps_rest() {
+        // 100 + 200 + 300 spent on levels 1, 2 and 3
+        assert_eq!(Role::apply_exp(1, 650, 20, required_exp), (4, 50));
+    }
+
+    #[test]
+    fn apply_exp_levels_up_on_exact_exp() {
+        assert_eq!(Role::apply_exp(2, 200, 20, required_exp), (3, 0));
+    }
+
+    #[test]
+    fn apply_exp_discards_exp_at_level_limit() {
+        assert_eq!(Role::apply_exp(1, 100_000, 20, required_exp), (20, 0));
+        assert_eq!(Role::apply_exp(20, 500, 20, required_exp), (20, 0));
+    }
+
+    #[test]
+    fn apply_exp_stops_on_missing_level_data() {
+        assert_eq!(
+            Role::apply_exp(89, 100_000, 100, required_exp),
+            (90, 91_100)
+        );
+    }
+
+    #[test]
+    fn apply_ratio_uses_base_of_10000() {
+        assert_eq!(Role::apply_ratio(1000, 10000), 1000);
+        assert_eq!(Role::apply_ratio(1000, 15050), 1505);
+        assert_eq!(Role::apply_ratio(i32::MAX, 10000), i32::MAX);
+    }
 }
@@ -0,0 +1,190 @@
+use shorekeeper_data::{skill_data, skill_level_data, skill_tree_data, SkillTreeData};
+use shorekeeper_protocol::{ArraySkillNode, EAttributeType, RoleSkillNodeData};
+
+use crate::logic::player::{ItemUsage, PlayerInventory};
+
+use super::{Role, RoleError};
+
+#[derive(Clone)]
+pub struct RoleSkillNode {
+    pub node_id: i32,
+    pub is_active: bool,
+    pub skill_id: i32,
+}
+
+impl RoleSkillNode {
+    const NODE_TYPE_SKILL: i32 = 1;
+    const NODE_TYPE_INHERENT_SKILL: i32 = 2;
+    const NODE_TYPE_PROPERTY: i32 = 3;
+
+    pub fn to_protobuf(&self) -> ArraySkillNode {
+        ArraySkillNode {
+            skill_node_id: self.node_id,
+            is_active: self.is_active,
+            skill_id: self.skill_id,
+        }
+    }
+
+    pub fn load_from_save(data: RoleSkillNodeData) -> Self {
+        Self {
+            node_id: data.node_id,
+            is_active: data.is_active,
+            skill_id: data.skill_id,
+        }
+    }
+
+    pub fn build_save_data(&self) -> RoleSkillNodeData {
+        RoleSkillNodeData {
+            node_id: self.node_id,
+            is_active: self.is_active,
+            skill_id: self.skill_id,
+        }
+    }
+}
+
+impl Role {
+    // Adds nodes of role's skill tree missing in its state, saved roles get nodes added to data later.
+    // Skill slots are active from the start, the rest of the tree is unlocked by RoleActivateSkill
+    pub(super) fn sync_skill_tree(&mut self) {
+        let group = self.info().skill_tree_group_id;
+        let mut nodes = skill_tree_data::iter()
+            .map(|(_, d)| d)
+            .filter(|d| d.node_group == group)
+            .peekable();
+
+        if nodes.peek().is_none() {
+            tracing::warn!(
+                "role {} has no nodes in skill tree group {group}",
+                self.role_id
+            );
+            return;
+        }
+
+        for data in nodes {
+            if self.skill_node_state.iter().any(|n| n.node_id == data.id) {
+                continue;
+            }
+
+            self.skill_node_state.push(RoleSkillNode {
+                node_id: data.id,
+                is_active: data.node_type == RoleSkillNode::NODE_TYPE_SKILL,
+                skill_id: data.skill_id,
+            });
+        }
+
+        for node in self.skill_node_state.iter().filter(|n| n.is_active) {
+            self.skill_map.entry(node.skill_id).or_insert(1);
+        }
+    }
+
+    pub fn skill_level_up_cost(&self, skill_id: i32) -> Result<Vec<ItemUsage>, RoleError> {
+        let level = *self
+            .skill_map
+            .get(&skill_id)
+            .ok_or(RoleError::SkillNotFound(skill_id))?;
+
+        let skill = skill_data::get(skill_id).ok_or(RoleError::SkillNotFound(skill_id))?;
+
+        if level >= skill.max_skill_level {
+            return Err(RoleError::SkillLevelMax(skill_id));
+        }
+
+        let skill_level = skill_level_data::iter()
+            .find(|d| d.skill_level_group_id == skill.skill_level_group_id && d.level == level + 1)
+            .ok_or(RoleError::SkillLevelMax(skill_id))?;
+
+        Ok(Self::build_consume(
+            skill_level.consume.iter(),
+            skill_level.gold_consume,
+        ))
+    }
+
+    // Returns new skill level
+    pub fn level_up_skill(&mut self, skill_id: i32) -> i32 {
+        let level = self.skill_map.entry(skill_id).or_insert(0);
+        *level += 1;
+        *level
+    }
+
+    pub fn skill_node_cost(&self, node_id: i32) -> Result<Vec<ItemUsage>, RoleError> {
+        let node = self.get_skill_node(node_id)?;
+        if node.is_active {
+            return Err(RoleError::SkillNodeActive(node_id));
+        }
+
+        let data = Self::get_skill_node_data(node_id)?;
+        if self.breakthrough < data.unlock_breach_level {
+            return Err(RoleError::SkillNodeLocked(node_id));
+        }
+
+        if !data.parent_nodes.iter().all(|parent| {
+            self.skill_node_state
+                .iter()
+                .any(|n| n.node_id == *parent && n.is_active)
+        }) {
+            return Err(RoleError::SkillNodeLocked(node_id));
+        }
+
+        Ok(Self::build_consume(data.consume.iter(), data.gold_consume))
+    }
+
+    // Activates skill node, returns (skill_id, level) if it unlocked a skill
+    pub fn activate_skill_node(&mut self, node_id: i32) -> Result<Option<(i32, i32)>, RoleError> {
+        let data = Self::get_skill_node_data(node_id)?;
+        let node = self
+            .skill_node_state
+            .iter_mut()
+            .find(|n| n.node_id == node_id)
+            .ok_or(RoleError::SkillNodeNotFound(node_id))?;
+
+        node.is_active = true;
+
+        match data.node_type {
+            RoleSkillNode::NODE_TYPE_SKILL | RoleSkillNode::NODE_TYPE_INHERENT_SKILL => {
+                let level = *self.skill_map.entry(data.skill_id).or_insert(1);
+                Ok(Some((data.skill_id, level)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    // Attribute bonuses from active property nodes, in ten-thousandths of base value
+    pub(super) fn skill_tree_properties(&self) -> impl Iterator<Item = (EAttributeType, i32)> + '_ {
+        self.skill_node_state
+            .iter()
+            .filter(|n| n.is_active)
+            .flat_map(|n| skill_tree_data::get(n.node_id))
+            .filter(|d| d.node_type == RoleSkillNode::NODE_TYPE_PROPERTY)
+            .flat_map(|d| d.property_node_param.iter())
+            .flat_map(|(&ty, &ratio)| EAttributeType::try_from(ty).ok().map(|ty| (ty, ratio)))
+    }
+
+    fn get_skill_node(&self, node_id: i32) -> Result<&RoleSkillNode, RoleError> {
+        self.skill_node_state
+            .iter()
+            .find(|n| n.node_id == node_id)
+            .ok_or(RoleError::SkillNodeNotFound(node_id))
+    }
+
+    fn get_skill_node_data(node_id: i32) -> Result<&'static SkillTreeData, RoleError> {
+        skill_tree_data::get(node_id).ok_or(RoleError::SkillNodeNotFound(node_id))
+    }
+
+    pub(super) fn build_consume<'a>(
+        consume: impl Iterator<Item = (&'a i32, &'a i32)>,
+        gold_consume: i32,
+    ) -> Vec<ItemUsage> {
+        let mut usages: Vec<ItemUsage> = consume
+            .map(|(&id, &quantity)| ItemUsage { id, quantity })
+            .collect();
+
+        if gold_consume > 0 {
+            usages.push(ItemUsage {
+                id: PlayerInventory::SHELL_CREDIT_ID,
+                quantity: gold_consume,
+            });
+        }
+
+        usages
+    }
+}
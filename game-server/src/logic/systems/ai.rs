@@ -0,0 +1,65 @@
+use std::cell::RefMut;
+
+use shorekeeper_protocol::ai::{AiControlSwitchNotify, NNs1};
+
+use crate::{
+    logic::{ecs::component::ComponentContainer, ecs::world::World, player::Player},
+    query_components, query_with,
+};
+
+use super::System;
+
+// Keeps every monster AI controlled by a player in the world, the most hated one takes over
+pub(super) struct AiSystem;
+
+impl System for AiSystem {
+    fn tick(&self, world: &mut World, players: &mut [RefMut<Player>]) {
+        let mut notify = AiControlSwitchNotify::default();
+        let world_entity = world.get_world_entity();
+        let is_in_world = |player_id: i32| players.iter().any(|pl| pl.basic_info.id == player_id);
+
+        for (entity, mut monster_ai) in query_with!(world_entity, MonsterAi) {
+            let hated_player_id = monster_ai
+                .hated_entities()
+                .into_iter()
+                .find_map(|entity_id| {
+                    let (owner,) = query_components!(world_entity, entity_id, OwnerPlayer);
+                    owner.map(|owner| owner.0).filter(|&id| is_in_world(id))
+                });
+
+            // Control is handed back once controller leaves the world
+            let controller_id = hated_player_id
+                .or_else(|| {
+                    is_in_world(monster_ai.controller_id).then_some(monster_ai.controller_id)
+                })
+                .or_else(|| players.first().map(|pl| pl.basic_info.id));
+
+            if let Some(controller_id) = controller_id {
+                monster_ai.set_controller(controller_id);
+            }
+
+            if !monster_ai.controller_changed {
+                continue;
+            }
+
+            notify.ai_control_switch_infos.push(NNs1 {
+                entity_id: i64::from(entity),
+                ai_info: None,
+                player_id: monster_ai.controller_id,
+            });
+            monster_ai.controller_changed = false;
+        }
+
+        if !notify.ai_control_switch_infos.is_empty() {
+            players
+                .iter()
+                .for_each(|player| player.notify(notify.clone()))
+        }
+    }
+}
+
+impl AiSystem {
+    pub fn new() -> Self {
+        Self
+    }
+}
@@ -0,0 +1,52 @@
+use std::cell::RefMut;
+
+use shorekeeper_protocol::{AttributeChangedNotify, EntityLivingStatusNotify};
+
+use crate::{
+    logic::{ecs::component::ComponentContainer, ecs::world::World, player::Player},
+    query_with,
+};
+
+use super::System;
+
+// Broadcasts attributes changed by server logic to every player in the world
+pub(super) struct AttributeSystem;
+
+impl System for AttributeSystem {
+    fn tick(&self, world: &mut World, players: &mut [RefMut<Player>]) {
+        let world_entity = world.get_world_entity();
+
+        for (entity, mut attribute) in query_with!(world_entity, Attribute) {
+            if !attribute.changed {
+                continue;
+            }
+
+            let entity_id = i64::from(entity);
+            let attribute_notify = AttributeChangedNotify {
+                id: entity_id,
+                is_stop_character: attribute.build_gameplay_attributes(),
+            };
+            let living_status_notify = EntityLivingStatusNotify {
+                id: entity_id,
+                living_status: attribute.build_living_status().into(),
+                drop_vision_item: Vec::new(),
+            };
+
+            for player in players.iter() {
+                player.notify(attribute_notify.clone());
+                if attribute.living_status_changed {
+                    player.notify(living_status_notify.clone());
+                }
+            }
+
+            attribute.changed = false;
+            attribute.living_status_changed = false;
+        }
+    }
+}
+
+impl AttributeSystem {
+    pub fn new() -> Self {
+        Self
+    }
+}
@@ -0,0 +1,73 @@
+use std::cell::RefMut;
+
+use common::time_util;
+use shorekeeper_protocol::combat_message::{
+    combat_receive_data, CombatNotifyData, CombatReceiveData, CombatReceivePackNotify,
+};
+use shorekeeper_protocol::{CombatCommon, EAttributeType};
+
+use crate::{
+    logic::{ecs::component::ComponentContainer, ecs::world::World, player::Player},
+    query_with,
+};
+
+use super::System;
+
+// Expires buffs, runs their periodic effects and applies increments to Attribute
+pub(super) struct BuffSystem;
+
+impl System for BuffSystem {
+    fn tick(&self, world: &mut World, players: &mut [RefMut<Player>]) {
+        let now = time_util::unix_timestamp_ms();
+        let mut notify = CombatReceivePackNotify::default();
+
+        for (entity, mut buff, mut attribute) in
+            query_with!(world.get_world_entity(), Buff, Attribute)
+        {
+            let heal_ratio = buff.update(now);
+            if heal_ratio > 0 {
+                let life_max = attribute.value(EAttributeType::LifeMax);
+                attribute.heal((life_max as i64 * heal_ratio as i64 / 10000) as i32);
+            }
+
+            if buff.changed {
+                let bonus = buff.build_bonus(|ty| {
+                    attribute
+                        .attr_map
+                        .get(&ty)
+                        .map(|(base, _)| *base)
+                        .unwrap_or_default()
+                });
+                attribute.set_buff_bonus(bonus);
+                buff.changed = false;
+            }
+
+            notify
+                .data
+                .extend(buff.notifies.drain(..).map(|message| CombatReceiveData {
+                    message: Some(combat_receive_data::Message::CombatNotifyData(
+                        CombatNotifyData {
+                            combat_common: Some(CombatCommon {
+                                entity_id: i64::from(entity),
+                                is_server_request: true,
+                                ..Default::default()
+                            }),
+                            message: Some(message),
+                        },
+                    )),
+                }));
+        }
+
+        if !notify.data.is_empty() {
+            players
+                .iter()
+                .for_each(|player| player.notify(notify.clone()))
+        }
+    }
+}
+
+impl BuffSystem {
+    pub fn new() -> Self {
+        Self
+    }
+}
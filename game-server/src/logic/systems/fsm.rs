@@ -0,0 +1,76 @@
+use std::cell::RefMut;
+
+use shorekeeper_protocol::combat_message::{
+    combat_notify_data, combat_receive_data, CombatNotifyData, CombatReceiveData,
+    CombatReceivePackNotify,
+};
+use shorekeeper_protocol::{CombatCommon, FsmBlackboardNotify, FsmCustomBlackboardNotify};
+
+use crate::{
+    logic::{ecs::component::ComponentContainer, ecs::world::World, player::Player},
+    query_with,
+};
+
+use super::System;
+
+// Broadcasts FSM changes made on the server side
+pub(super) struct FsmSystem;
+
+impl System for FsmSystem {
+    fn tick(&self, world: &mut World, players: &mut [RefMut<Player>]) {
+        let mut notify = CombatReceivePackNotify::default();
+
+        for (entity, mut fsm) in query_with!(world.get_world_entity(), Fsm) {
+            let mut messages = fsm
+                .state_changes
+                .drain(..)
+                .map(combat_notify_data::Message::ChangeStateNotify)
+                .collect::<Vec<_>>();
+
+            if fsm.black_board_changed {
+                messages.push(combat_notify_data::Message::FsmBlackboardNotify(
+                    FsmBlackboardNotify {
+                        fsm_black_boards: fsm.build_black_board(),
+                    },
+                ));
+                fsm.black_board_changed = false;
+            }
+
+            if fsm.custom_black_board_changed {
+                messages.push(combat_notify_data::Message::FsmCustomBlackboardNotify(
+                    FsmCustomBlackboardNotify {
+                        fsm_custom_blackboard_datas: Some(fsm.build_custom_black_board()),
+                    },
+                ));
+                fsm.custom_black_board_changed = false;
+            }
+
+            notify
+                .data
+                .extend(messages.into_iter().map(|message| CombatReceiveData {
+                    message: Some(combat_receive_data::Message::CombatNotifyData(
+                        CombatNotifyData {
+                            combat_common: Some(CombatCommon {
+                                entity_id: i64::from(entity),
+                                is_server_request: true,
+                                ..Default::default()
+                            }),
+                            message: Some(message),
+                        },
+                    )),
+                }));
+        }
+
+        if !notify.data.is_empty() {
+            players
+                .iter()
+                .for_each(|player| player.notify(notify.clone()))
+        }
+    }
+}
+
+impl FsmSystem {
+    pub fn new() -> Self {
+        Self
+    }
+}
@@ -2,7 +2,15 @@ use std::{cell::RefMut, sync::LazyLock};
 
 use super::{ecs::world::World, player::Player};
 
+mod ai;
+mod attribute;
+mod buff;
+mod fsm;
 mod movement;
+use ai::AiSystem;
+use attribute::AttributeSystem;
+use buff::BuffSystem;
+use fsm::FsmSystem;
 use movement::MovementSystem;
 
 macro_rules! enabled_systems {
@@ -25,4 +33,8 @@ pub trait System: Send + Sync + 'static {
 
 enabled_systems! {
     MovementSystem;
+    BuffSystem;
+    AttributeSystem;
+    AiSystem;
+    FsmSystem;
 }
@@ -2,7 +2,7 @@ use common::time_util;
 use shorekeeper_protocol::PlayerSaveData;
 use shorekeeper_protocol::{
     message::Message, AfterJoinSceneNotify, EnterGameResponse, JoinSceneNotify, JsPatchNotify,
-    TransitionOptionPb,
+    MailAddNotify, MailAddReason, TransitionOptionPb,
 };
 use std::collections::hash_map::Entry::Vacant;
 use std::{
@@ -17,9 +17,13 @@ use std::{
     time::Duration,
 };
 
-use super::{ecs::world::World, player::Player, utils::world_util};
+use super::{
+    ecs::world::World,
+    player::{ChatEvent, ChatMessage, Mail, Player, PlayerMail, PlayerSocial, SocialEvent},
+    utils::{quest_util, world_util},
+};
 use crate::logic::ecs::world::WorldEntity;
-use crate::{logic, player_save_task::{self, PlayerSaveReason}, session::Session};
+use crate::{player_save_task::{self, PlayerSaveReason}, session::Session};
 
 const WATER_MASK: &str = include_str!("../../watermask-rr.js");
 const UID_FIX: &str = include_str!("../../uidfix.js");
@@ -31,6 +35,9 @@ pub enum LogicInput {
         enter_rpc_id: u16,
         session: Arc<Session>,
         player_save_data: PlayerSaveData,
+        mails: Vec<Mail>,
+        social: PlayerSocial,
+        private_chats: Vec<(i32, ChatMessage)>,
     },
     RemovePlayer {
         player_id: i32,
@@ -39,6 +46,18 @@ pub enum LogicInput {
         player_id: i32,
         message: Message,
     },
+    AddMail {
+        player_id: i32,
+        mail: Mail,
+    },
+    SocialEvent {
+        player_id: i32,
+        event: SocialEvent,
+    },
+    ChatEvent {
+        player_id: i32,
+        event: ChatEvent,
+    },
 }
 
 #[derive(Clone)]
@@ -117,10 +136,15 @@ fn logic_thread_func(receiver: mpsc::Receiver<LogicInput>, load: Arc<AtomicUsize
 
         state.players.values().for_each(|player| {
             let mut player = player.borrow_mut();
+            if player.instance.is_timed_out(time_util::unix_timestamp()) {
+                super::handler::on_instance_timeout(&mut player);
+            }
+
             if time_util::unix_timestamp() - player.last_save_time > PLAYER_SAVE_PERIOD {
                 player_save_task::push(
                     player.basic_info.id,
                     player.build_save_data(),
+                    player.mail.take_unsaved_claims(),
                     PlayerSaveReason::PeriodicalSave,
                 );
 
@@ -137,12 +161,17 @@ fn handle_logic_input(state: &mut LogicState, input: LogicInput) {
             enter_rpc_id,
             session,
             player_save_data,
+            mails,
+            social,
+            private_chats,
         } => {
             let (player, is_player) = if let Vacant(e) = state.players.entry(player_id) {
-                (
-                    e.insert(RefCell::new(Player::load_from_save(player_save_data))),
-                    true,
-                )
+                let mut player = Player::load_from_save(player_save_data);
+                player.mail = PlayerMail::new(mails);
+                player.social = social;
+                player.chat.set_history(private_chats);
+
+                (e.insert(RefCell::new(player)), true)
             } else {
                 if let Some(player) = state.players.get_mut(&player_id) {
                     (player, false)
@@ -154,11 +183,14 @@ fn handle_logic_input(state: &mut LogicState, input: LogicInput) {
 
             let mut player = player.borrow_mut();
             if is_player {
-                player
-                    .world
-                    .borrow_mut()
-                    .world_entitys
-                    .insert(player.basic_info.cur_map_id, WorldEntity::default());
+                let map_id = player.location.map_id();
+                player.basic_info.cur_map_id = map_id;
+
+                let mut world = player.world.borrow_mut();
+                world.world_entitys.insert(map_id, WorldEntity::default());
+                world.player_cur_map_id = map_id;
+                drop(world);
+
                 state.worlds.insert(player_id, player.world.clone());
             }
 
@@ -166,6 +198,7 @@ fn handle_logic_input(state: &mut LogicState, input: LogicInput) {
             player.set_session(session);
             player.respond(EnterGameResponse::default(), enter_rpc_id);
             player.notify_general_data();
+            quest_util::refresh_quests(&mut player);
 
             player
                 .world
@@ -197,15 +230,7 @@ fn handle_logic_input(state: &mut LogicState, input: LogicInput) {
                 content: CENSORSHIP_FIX.to_string(),
             });
 
-            let map = logic::utils::quadrant_util::get_map(player.location.instance_id);
-            let quadrant_id = map.get_quadrant_id(
-                player.location.position.position.x * 100.0,
-                player.location.position.position.y * 100.0,
-            );
-            player.quadrant_id = quadrant_id;
-
-            let entities = map.get_initial_entities(quadrant_id);
-            world_util::add_entities(&player, &entities);
+            world_util::add_quadrant_entities(&mut player);
 
             drop(player);
 
@@ -221,6 +246,35 @@ fn handle_logic_input(state: &mut LogicState, input: LogicInput) {
 
             super::handler::handle_logic_message(&mut player.borrow_mut(), message);
         }
+        LogicInput::AddMail { player_id, mail } => {
+            let Some(player) = state.players.get(&player_id) else {
+                tracing::debug!("logic_thread: add mail requested, but player with id {player_id} is offline");
+                return;
+            };
+
+            let mut player = player.borrow_mut();
+            let new_mail = player.mail.add(mail).to_protobuf();
+            player.notify(MailAddNotify {
+                new_mail: Some(new_mail),
+                reason: MailAddReason::Gm.into(),
+            });
+        }
+        LogicInput::SocialEvent { player_id, event } => {
+            let Some(player) = state.players.get_mut(&player_id) else {
+                tracing::debug!("logic_thread: social event requested, but player with id {player_id} is offline");
+                return;
+            };
+
+            super::handler::on_social_event(&mut player.borrow_mut(), event);
+        }
+        LogicInput::ChatEvent { player_id, event } => {
+            let Some(player) = state.players.get_mut(&player_id) else {
+                tracing::debug!("logic_thread: chat event requested, but player with id {player_id} is offline");
+                return;
+            };
+
+            super::handler::on_chat_event(&mut player.borrow_mut(), event);
+        }
         LogicInput::RemovePlayer { player_id } => {
             let Some(player) = state.players.remove(&player_id) else {
                 tracing::warn!(
@@ -233,9 +287,11 @@ fn handle_logic_input(state: &mut LogicState, input: LogicInput) {
             // TODO: kick co-op players from removed world
             // TODO: Remove all entitie
 
+            let mut player = player.borrow_mut();
             player_save_task::push(
                 player_id,
-                player.borrow().build_save_data(),
+                player.build_save_data(),
+                player.mail.take_unsaved_claims(),
                 PlayerSaveReason::PlayerLogicStopped,
             );
         }
@@ -0,0 +1,46 @@
+use shorekeeper_protocol::{AchievementGroupProgressNotify, AchievementProgressNotify};
+
+use crate::logic::{
+    event_bus::GameEvent,
+    player::{AchievementError, Player, PlayerAchievement},
+};
+
+pub fn on_game_event(player: &mut Player, event: &GameEvent) {
+    let updated = player.achievement.add_progress(event);
+    notify_progress(player, &updated);
+}
+
+pub fn finish_achievement(player: &mut Player, id: i32) -> Result<(), AchievementError> {
+    player.achievement.finish(id)?;
+    notify_progress(player, &[id]);
+    Ok(())
+}
+
+fn notify_progress(player: &mut Player, ids: &[i32]) {
+    let mut any_finished = false;
+
+    for &id in ids {
+        player.notify(AchievementProgressNotify {
+            achievement_entry: Some(player.achievement.build_entry(id)),
+        });
+
+        if !player.achievement.is_finished(id) {
+            continue;
+        }
+
+        any_finished = true;
+        let Some(group_id) = PlayerAchievement::group_id_of(id) else {
+            continue;
+        };
+
+        if player.achievement.try_finish_group(group_id) {
+            player.notify(AchievementGroupProgressNotify {
+                achievement_group_entry: Some(player.achievement.build_group_entry(group_id)),
+            });
+        }
+    }
+
+    if any_finished {
+        player.notify(player.achievement.build_count_change_notify());
+    }
+}
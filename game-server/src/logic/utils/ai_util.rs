@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use shorekeeper_data::level_entity_config_data;
+use shorekeeper_protocol::{ai::AiHateEntity, ErrorCode};
+
+use crate::{
+    logic::{
+        components::MonsterAi,
+        ecs::{component::ComponentContainer, world::WorldEntity},
+        math::Vector3f,
+    },
+    query_components,
+};
+
+// Used by monsters that don't specify their AI team
+const DEFAULT_AI_TEAM_INIT_ID: i32 = 100;
+// Hated entities are players' roles and summons, list reported by client is capped
+const MAX_HATE_LIST_SIZE: usize = 32;
+// Control of monster AI can only be taken by nearby player, distance is in meters
+const CONTROL_DISTANCE: f32 = 100.0;
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "PascalCase", default)]
+struct AiComponent {
+    disabled: bool,
+    weapon_id: i32,
+    hatred_group_id: i64,
+    ai_team: Option<AiTeam>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct AiTeam {
+    ai_team_level_id: i32,
+}
+
+// AI settings come from AiComponent of level entity, None if monster's AI is disabled
+pub fn build_monster_ai(config_id: i64, controller_id: i32) -> Option<MonsterAi> {
+    let component = level_entity_config_data::get(config_id)
+        .and_then(|data| data.components_data.get("AiComponent"))
+        .map(|component| {
+            AiComponent::deserialize(component).unwrap_or_else(|err| {
+                tracing::warn!("ai_util: invalid AiComponent of entity {config_id}, err: {err}");
+                AiComponent::default()
+            })
+        })
+        .unwrap_or_default();
+
+    if component.disabled {
+        return None;
+    }
+
+    Some(MonsterAi {
+        weapon_id: component.weapon_id,
+        hatred_group_id: component.hatred_group_id,
+        ai_team_init_id: component
+            .ai_team
+            .map(|team| team.ai_team_level_id)
+            .unwrap_or(DEFAULT_AI_TEAM_INIT_ID),
+        combat_message_id: 0,
+        controller_id,
+        controller_changed: true,
+        hate_list: HashMap::new(),
+    })
+}
+
+// Hate list reported by controller replaces the stored one
+pub fn update_hate_list(
+    world: &WorldEntity,
+    player_id: i32,
+    entity_id: i64,
+    hate_list: &[AiHateEntity],
+) -> Result<(), ErrorCode> {
+    let (Some(mut monster_ai),) = query_components!(world, entity_id, MonsterAi) else {
+        return Err(ErrorCode::ErrAiControlComponent);
+    };
+
+    if monster_ai.controller_id != player_id {
+        return Err(ErrorCode::ErrIsNotAiControler);
+    }
+
+    if hate_list.len() > MAX_HATE_LIST_SIZE {
+        return Err(ErrorCode::ErrAiHateComponent);
+    }
+
+    monster_ai.hate_list = hate_list
+        .iter()
+        .map(|hate| (hate.entity_id, hate.hatred_value))
+        .collect();
+    Ok(())
+}
+
+// Monster has to be on the player's map and close enough to the player
+pub fn take_control(
+    world: &WorldEntity,
+    player_id: i32,
+    map_id: i32,
+    player_position: &Vector3f,
+    entity_id: i64,
+) -> Result<(), ErrorCode> {
+    if !world.is_in_world_map(entity_id as i32, map_id) {
+        return Err(ErrorCode::ErrSceneEntityNotExist);
+    }
+
+    let (Some(mut monster_ai), Some(position)) =
+        query_components!(world, entity_id, MonsterAi, Position)
+    else {
+        return Err(ErrorCode::ErrAiControlComponent);
+    };
+
+    if monster_ai.controller_id == player_id {
+        return Err(ErrorCode::ErrAiControlNotChange);
+    }
+
+    if position.0.position.distance_squared(player_position) > CONTROL_DISTANCE * CONTROL_DISTANCE {
+        return Err(ErrorCode::ErrDistanceNotInRangeBetweenEntity);
+    }
+
+    monster_ai.set_controller(player_id);
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use common::time_util;
+use shorekeeper_data::{buff_data, buff_item_data, BuffItemData};
+use shorekeeper_protocol::{BuffItem, ErrorCode};
+
+use crate::{
+    logic::{
+        ecs::component::ComponentContainer,
+        player::{ItemUsage, Player},
+    },
+    query_components,
+};
+
+#[inline(always)]
+pub fn buff_item(item_id: i32) -> Option<&'static BuffItemData> {
+    buff_item_data::iter().find(|d| d.item_id == item_id)
+}
+
+// Food buff is applied to role entity of current formation once the item is consumed
+pub fn use_buff_item(
+    player: &mut Player,
+    item_id: i32,
+    count: i32,
+    role_id: i32,
+) -> Result<BuffItem, ErrorCode> {
+    if count <= 0 {
+        return Err(ErrorCode::ErrBuffItemNumZero);
+    }
+
+    let item = buff_item(item_id).ok_or(ErrorCode::ErrBuffItemConfig)?;
+    let buff = buff_data::iter()
+        .find(|d| d.id == item.buff_id)
+        .ok_or(ErrorCode::ErrBuffItemConfig)?;
+
+    if !player
+        .formation_list
+        .get(&player.cur_formation_id)
+        .is_some_and(|f| f.role_ids.contains(&role_id))
+    {
+        return Err(ErrorCode::ErrBuffItemRoleIdNotExist);
+    }
+
+    if player.inventory.get_item_count(item_id) < count {
+        return Err(ErrorCode::ErrBuffItemNotEnough);
+    }
+
+    let now = time_util::unix_timestamp() as i64;
+    if player
+        .buff_item_cd
+        .get(&item_id)
+        .is_some_and(|&cd_time| now < cd_time)
+    {
+        return Err(ErrorCode::ErrBuffItemCdLimit);
+    }
+
+    let entity_id = {
+        let world_ref = player.world.borrow();
+        let world = world_ref.get_world_entity();
+        let entity_id = world.get_entity_id(role_id);
+
+        if query_components!(world, entity_id, Buff).0.is_none() {
+            return Err(ErrorCode::ErrBuffItemRoleIdNotExist);
+        }
+        entity_id
+    };
+
+    player
+        .consume_items(&[ItemUsage {
+            id: item_id,
+            quantity: count,
+        }])
+        .map_err(|_| ErrorCode::ErrBuffItemNotEnough)?;
+
+    {
+        let world_ref = player.world.borrow();
+        let (Some(mut buff_component),) =
+            query_components!(world_ref.get_world_entity(), entity_id, Buff)
+        else {
+            return Err(ErrorCode::ErrBuffItemRoleIdNotExist);
+        };
+        buff_component.add(buff, entity_id, count, time_util::unix_timestamp_ms());
+    }
+
+    let cd_time = now + item.cd as i64;
+    player.buff_item_cd.insert(item_id, cd_time);
+
+    Ok(BuffItem { item_id, cd_time })
+}
+
+// Runs effect of buffs triggered on current role, client reports their handles
+pub fn execute_buff_effect(
+    player: &Player,
+    index: i32,
+    handle_ids: &[i32],
+) -> Result<(), ErrorCode> {
+    let role_id = player
+        .formation_list
+        .get(&player.cur_formation_id)
+        .map(|formation| formation.cur_role)
+        .ok_or(ErrorCode::ErrNoBuffConf)?;
+
+    let world_ref = player.world.borrow();
+    let world = world_ref.get_world_entity();
+    let (Some(mut buff),) = query_components!(world, world.get_entity_id(role_id), Buff) else {
+        return Err(ErrorCode::ErrNoBuffConf);
+    };
+
+    for &handle_id in handle_ids {
+        buff.execute_effect(handle_id, index)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use shorekeeper_data::level_entity_config_data;
+use shorekeeper_protocol::{CalabashDevelopRewardUnlockNotify, CalabashExpAddNotify};
+
+use crate::logic::{
+    event_bus::GameEvent,
+    player::{ItemUsage, Player, PlayerCalabash},
+};
+
+pub fn on_game_event(player: &mut Player, event: &GameEvent) {
+    if let GameEvent::MonsterKilled { config_id } = event {
+        on_monster_killed(player, *config_id);
+    }
+}
+
+fn on_monster_killed(player: &mut Player, config_id: i64) {
+    let Some(entity) = level_entity_config_data::get(config_id) else {
+        return;
+    };
+
+    let Some(result) = player.calabash.roll_catch(&entity.blueprint_type) else {
+        return;
+    };
+
+    let incr_id = match player.inventory.add_items(&[ItemUsage {
+        id: result.phantom_item_id,
+        quantity: 1,
+    }]) {
+        Ok(change) => {
+            player.notify_inventory_change(&change);
+            change.added_phantoms.first().copied().unwrap_or_default()
+        }
+        Err(err) => {
+            tracing::warn!(
+                "calabash: failed to add caught phantom {}, err: {err}",
+                result.phantom_item_id
+            );
+            return;
+        }
+    };
+
+    let first_catch = player.calabash.record_catch(&result);
+    player.notify(CalabashExpAddNotify {
+        cur_exp: player.calabash.exp(),
+        add_exp: result.add_exp,
+        cur_level: player.calabash.level(),
+        calabash_cfg: Some(player.calabash.build_cfg()),
+    });
+
+    if first_catch {
+        player.notify(CalabashDevelopRewardUnlockNotify {
+            unlocked_develop_reward: Some(PlayerCalabash::build_develop_info(result.monster_id)),
+            item_id: result.phantom_item_id,
+            incr_id,
+        });
+    }
+}
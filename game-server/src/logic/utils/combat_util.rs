@@ -0,0 +1,304 @@
+use rand::Rng;
+use shorekeeper_data::{instance_dungeon_data, revive_data, InstanceDungeonData};
+use shorekeeper_protocol::{
+    damage_context, DamageExecuteRequest, EAttributeType, EEntityType, ERemoveEntityType,
+    ErrorCode, GameplayAttributeData, PlayerDeadNotify, PlayerReviveNotify, ReviveRoleInformation,
+    TeleportReason,
+};
+
+use crate::{
+    logic::{
+        components::Attribute,
+        ecs::{component::ComponentContainer, world::WorldEntity},
+        event_bus::{self, GameEvent},
+        math::{Transform, Vector3f},
+        player::Player,
+        utils::{teleport_util, world_util},
+    },
+    query_components,
+};
+
+// Crit rate and crit damage are in ten-thousandths
+const RATIO_BASE: i32 = 10000;
+// Share of damage blocked by defense is def / (def + DEF_BASE + DEF_PER_LEVEL * attacker_level)
+const DEF_BASE: i32 = 800;
+const DEF_PER_LEVEL: i32 = 8;
+const SKILL_LEVEL_RATIO: f32 = 0.1;
+
+pub struct DamageResult {
+    pub damage: i32,
+    pub is_crit: bool,
+    pub killed: bool,
+}
+
+// Applies damage to the target's Attribute, client crit and block flags are ignored
+pub fn execute_damage(
+    player: &Player,
+    world: &WorldEntity,
+    request: &DamageExecuteRequest,
+) -> Result<DamageResult, ErrorCode> {
+    let skill_level = attacker_skill_level(player, world, request)?;
+
+    let (Some(mut target),) = query_components!(world, request.target_entity_id, Attribute) else {
+        return Err(ErrorCode::ErrSceneEntityNotExist);
+    };
+
+    if !target.is_alive() {
+        return Ok(DamageResult {
+            damage: 0,
+            is_crit: false,
+            killed: false,
+        });
+    }
+
+    // Self inflicted damage (e.g. falling) can't borrow the same component twice
+    let (damage, is_crit) = match request.attacker_entity_id == request.target_entity_id {
+        true => calc_damage(&target, &target, skill_level),
+        false => {
+            let (Some(attacker),) = query_components!(world, request.attacker_entity_id, Attribute)
+            else {
+                return Err(ErrorCode::ErrSceneEntityNotExist);
+            };
+            calc_damage(&attacker, &target, skill_level)
+        }
+    };
+
+    let damage = target.apply_damage(damage);
+
+    if damage > 0 {
+        if let (Some(mut monster_ai),) =
+            query_components!(world, request.target_entity_id, MonsterAi)
+        {
+            monster_ai.add_hatred(request.attacker_entity_id, damage);
+        }
+    }
+
+    Ok(DamageResult {
+        damage,
+        is_crit,
+        killed: !target.is_alive(),
+    })
+}
+
+// Killed monsters are removed from the world, wiped out party is asked to revive
+pub fn on_entity_killed(player: &mut Player, entity_id: i64) {
+    let (config_id, owner_id) = {
+        let world_ref = player.world.borrow();
+        let world = world_ref.get_world_entity();
+        let (config, owner) = query_components!(world, entity_id, EntityConfig, OwnerPlayer);
+        (
+            config
+                .filter(|config| config.entity_type == EEntityType::Monster)
+                .map(|config| config.config_id as i64),
+            owner.map(|owner| owner.0),
+        )
+    };
+
+    if let Some(config_id) = config_id {
+        remove_killed_monster(player, entity_id, config_id);
+    } else if owner_id == Some(player.basic_info.id) && is_party_wiped(player) {
+        player.notify(PlayerDeadNotify {
+            player_id: player.basic_info.id,
+            revive_id: current_instance_data(player)
+                .map(|data| data.revive_id)
+                .unwrap_or_default(),
+            is_show_revive: true,
+            ..Default::default()
+        });
+    }
+}
+
+pub fn remove_killed_monster(player: &mut Player, entity_id: i64, config_id: i64) {
+    player
+        .world
+        .borrow_mut()
+        .get_mut_world_entity()
+        .remove_entity(entity_id as i32);
+
+    player.notify(
+        player.build_player_entity_remove_notify(vec![entity_id], ERemoveEntityType::HpIsZero),
+    );
+    player.instance.on_monster_killed(config_id);
+    event_bus::emit(player, GameEvent::MonsterKilled { config_id });
+}
+
+// All roles of current formation are dead
+pub fn is_party_wiped(player: &Player) -> bool {
+    let world_ref = player.world.borrow();
+    let world = world_ref.get_world_entity();
+
+    let entity_ids = formation_entity_ids(player, world);
+    !entity_ids.is_empty()
+        && entity_ids.into_iter().all(|entity_id| {
+            let (attribute,) = query_components!(world, entity_id, Attribute);
+            attribute.is_some_and(|attribute| !attribute.is_alive())
+        })
+}
+
+// Restores life of the whole formation and moves the player to revive point
+pub fn revive_party(player: &mut Player) {
+    let revive_role_infos = {
+        let world_ref = player.world.borrow();
+        let world = world_ref.get_world_entity();
+
+        formation_entity_ids(player, world)
+            .into_iter()
+            .filter_map(|entity_id| {
+                let (Some(mut attribute),) = query_components!(world, entity_id, Attribute) else {
+                    return None;
+                };
+
+                attribute.revive();
+                Some(ReviveRoleInformation {
+                    entity_id,
+                    cur_hp_attribute: Some(GameplayAttributeData {
+                        attribute_type: EAttributeType::Life.into(),
+                        base_value: attribute.value(EAttributeType::Life),
+                        increment: 0,
+                    }),
+                })
+            })
+            .collect()
+    };
+
+    let (instance_id, transform) = revive_location(player);
+    player.notify(PlayerReviveNotify {
+        player_id: player.basic_info.id,
+        location: Some(transform.get_position_protobuf()),
+        rotator: Some(transform.get_rotation_protobuf()),
+        revive_role_infos,
+        ..Default::default()
+    });
+
+    world_util::teleport_player(player, instance_id, transform, TeleportReason::ApiTeleport);
+}
+
+// Attacker has to be a role of the player or a monster controlled by the player,
+// skill level of roles is taken from the server side role
+fn attacker_skill_level(
+    player: &Player,
+    world: &WorldEntity,
+    request: &DamageExecuteRequest,
+) -> Result<i32, ErrorCode> {
+    let (config, owner, monster_ai) = query_components!(
+        world,
+        request.attacker_entity_id,
+        EntityConfig,
+        OwnerPlayer,
+        MonsterAi
+    );
+    let Some(config) = config else {
+        return Err(ErrorCode::ErrSceneEntityNotExist);
+    };
+
+    if owner.is_some_and(|owner| owner.0 == player.basic_info.id) {
+        let skill_id = match request.damage_context.as_ref().and_then(|c| c.u4s2) {
+            Some(damage_context::U4s2::SkillId(skill_id)) => skill_id,
+            None => 0,
+        };
+
+        return Ok(player
+            .role_list
+            .get(&config.config_id)
+            .and_then(|role| role.skill_map.get(&skill_id))
+            .copied()
+            .unwrap_or(1));
+    }
+
+    match monster_ai.is_some_and(|ai| ai.controller_id == player.basic_info.id) {
+        true => Ok(1),
+        false => {
+            tracing::warn!(
+                "combat_util: player {} doesn't control attacker {}",
+                player.basic_info.id,
+                request.attacker_entity_id
+            );
+            Err(ErrorCode::ErrClientControlDamage)
+        }
+    }
+}
+
+fn calc_damage(attacker: &Attribute, target: &Attribute, skill_level: i32) -> (i32, bool) {
+    let is_crit =
+        rand::thread_rng().gen_range(0..RATIO_BASE) < attacker.value(EAttributeType::Crit);
+
+    let atk = attacker.value(EAttributeType::Atk).max(0) as f32;
+    let skill_ratio = 1.0 + (skill_level.max(1) - 1) as f32 * SKILL_LEVEL_RATIO;
+
+    let def = target.value(EAttributeType::Def).max(0) as f32;
+    let level = attacker.value(EAttributeType::Lv).max(1);
+    let def_ratio = 1.0 - def / (def + (DEF_BASE + DEF_PER_LEVEL * level) as f32);
+
+    let crit_ratio = match is_crit {
+        true => attacker.value(EAttributeType::CritDamage) as f32 / RATIO_BASE as f32,
+        false => 1.0,
+    };
+
+    let damage = (atk * skill_ratio * def_ratio * crit_ratio).round() as i32;
+    (damage.max(1), is_crit)
+}
+
+fn formation_entity_ids(player: &Player, world: &WorldEntity) -> Vec<i64> {
+    player
+        .formation_list
+        .get(&player.cur_formation_id)
+        .map(|formation| {
+            formation
+                .role_ids
+                .iter()
+                .map(|&role_id| world.get_entity_id(role_id))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn current_instance_data(player: &Player) -> Option<&'static InstanceDungeonData> {
+    let instance_id = player.instance.current()?.id;
+    instance_dungeon_data::iter().find(|data| data.id == instance_id)
+}
+
+// Instances revive at the revive point they refer to, open world at the nearest unlocked teleporter
+fn revive_location(player: &Player) -> (i32, Transform) {
+    if let Some(data) = current_instance_data(player).filter(|data| data.revive_id != 0) {
+        let transform = match revive_data::get(data.revive_id)
+            .filter(|revive| revive.map_id == data.map_config_id)
+        {
+            Some(revive) => Transform {
+                position: Vector3f::from_data(&revive.position),
+                rotation: Vector3f::from_data(&revive.rotation),
+            },
+            None => {
+                tracing::warn!(
+                    "combat_util: revive point {} of instance {} doesn't exist",
+                    data.revive_id,
+                    data.id
+                );
+                Transform {
+                    position: Vector3f::from_data(&data.born_position),
+                    rotation: Vector3f::from_data(&data.born_rotation),
+                }
+            }
+        };
+        return (data.id, transform);
+    }
+
+    let map_id = player.location.map_id();
+    let position = &player.location.position.position;
+    player
+        .teleport
+        .unlocked_ids()
+        .filter_map(teleport_util::get_teleporter)
+        .filter(|teleporter| teleporter.map_id == map_id)
+        .min_by(|a, b| {
+            let a = a.transform.position.distance_squared(position);
+            let b = b.transform.position.distance_squared(position);
+            a.total_cmp(&b)
+        })
+        .map(|teleporter| (teleporter.map_id, teleporter.transform.clone()))
+        .unwrap_or_else(|| {
+            (
+                player.location.instance_id,
+                player.location.position.clone(),
+            )
+        })
+}
@@ -0,0 +1,78 @@
+use serde::Deserialize;
+use shorekeeper_data::{fsm_group_data, level_entity_config_data};
+use shorekeeper_protocol::ErrorCode;
+
+use crate::{
+    logic::{
+        components::{Fsm, FsmError},
+        ecs::{component::ComponentContainer, world::WorldEntity},
+    },
+    query_components,
+};
+
+// Used by monsters that don't specify their FSM group
+const DEFAULT_FSM_GROUP_ID: i32 = 10007;
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "PascalCase", default)]
+struct FsmComponent {
+    disabled: bool,
+    fsm_group_id: i32,
+}
+
+// State machines come from FsmComponent of level entity, None if entity has none
+pub fn build_fsm(config_id: i64) -> Option<Fsm> {
+    let component = level_entity_config_data::get(config_id)
+        .and_then(|data| data.components_data.get("FsmComponent"))
+        .map(|component| {
+            FsmComponent::deserialize(component).unwrap_or_else(|err| {
+                tracing::warn!("fsm_util: invalid FsmComponent of entity {config_id}, err: {err}");
+                FsmComponent::default()
+            })
+        })
+        .unwrap_or_default();
+
+    if component.disabled {
+        return None;
+    }
+
+    let group_id = match component.fsm_group_id {
+        0 => DEFAULT_FSM_GROUP_ID,
+        group_id => group_id,
+    };
+
+    // Machines of groups missing from data are created from client transitions
+    let Some(group) = fsm_group_data::iter().find(|d| d.id == group_id) else {
+        tracing::debug!("fsm_util: fsm group {group_id} of entity {config_id} doesn't exist");
+        return Some(Fsm::new(0, &[]));
+    };
+
+    Some(Fsm::new(group.common_hash_code, &group.machines))
+}
+
+pub fn update_fsm<T>(
+    world: &WorldEntity,
+    entity_id: i64,
+    f: impl FnOnce(&mut Fsm) -> Result<T, FsmError>,
+) -> Result<T, ErrorCode> {
+    let (Some(mut fsm),) = query_components!(world, entity_id, Fsm) else {
+        return Err(ErrorCode::ErrFsmComponentNotFound);
+    };
+
+    f(&mut fsm).map_err(|err| {
+        tracing::debug!("fsm_util: entity {entity_id}, {err}");
+        err.into()
+    })
+}
+
+pub fn read_fsm<T>(
+    world: &WorldEntity,
+    entity_id: i64,
+    f: impl FnOnce(&Fsm) -> T,
+) -> Result<T, ErrorCode> {
+    let (Some(fsm),) = query_components!(world, entity_id, Fsm) else {
+        return Err(ErrorCode::ErrFsmComponentNotFound);
+    };
+
+    Ok(f(&fsm))
+}
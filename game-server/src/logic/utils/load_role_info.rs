@@ -5,11 +5,6 @@ use std::collections::HashMap;
 #[macro_export]
 macro_rules! impl_base_prop {
     ($($name:ident),*) => {
-        pub fn load_key_value(base_property: &BasePropertyData) -> HashMap<i32, i32> {
-            HashMap::from([$(
-                    ::paste::paste!((EAttributeType::[<$name:camel>] as i32, base_property.$name as i32)),
-                )*])
-        }
         pub fn attribute_from_data(base_property: &BasePropertyData) -> HashMap<EAttributeType, (i32, i32)> {
             HashMap::from([$(
                     ::paste::paste!((EAttributeType::[<$name:camel>], (base_property.$name, 0))),
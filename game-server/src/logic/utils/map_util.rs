@@ -0,0 +1,27 @@
+use shorekeeper_protocol::MapUnlockFieldNotify;
+
+use crate::logic::{player::Player, utils::quadrant_util};
+
+// Called on player movement, unlocks the area player is currently in
+pub fn update_unlocked_area(player: &mut Player) {
+    let position = player.location.position.position.clone();
+    if !player.map.should_check_area(&position) {
+        return;
+    }
+
+    let Some(map) = quadrant_util::try_get_map(player.location.map_id()) else {
+        return;
+    };
+
+    let Some(area_id) = map.get_area_id(position.x * 100.0, position.y * 100.0) else {
+        return;
+    };
+
+    if player.map.unlock_area(area_id) {
+        tracing::debug!(
+            "unlocked area {area_id}, player_id: {}",
+            player.basic_info.id
+        );
+        player.notify(MapUnlockFieldNotify { field_id: area_id });
+    }
+}
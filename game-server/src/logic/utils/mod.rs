@@ -1,4 +1,15 @@
+pub mod achievement_util;
+pub mod ai_util;
+pub mod buff_util;
+pub mod calabash_util;
+pub mod combat_util;
 pub mod entity_serializer;
+pub mod fsm_util;
 pub mod load_role_info;
+pub mod map_util;
+pub mod monster_util;
 pub mod world_util;
+pub mod payment_util;
 pub mod quadrant_util;
+pub mod teleport_util;
+pub mod quest_util;
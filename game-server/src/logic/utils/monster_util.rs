@@ -0,0 +1,78 @@
+use serde::Deserialize;
+use shorekeeper_data::{
+    base_property_data, level_entity_config_data, monster_property_growth_data, BasePropertyData,
+    MonsterPropertyGrowthData,
+};
+use shorekeeper_protocol::EAttributeType;
+
+use crate::logic::components::Attribute;
+use crate::logic::utils::load_role_info::attribute_from_data;
+
+// Used by monsters whose config doesn't point to a property of their own
+const DEFAULT_PROPERTY_ID: i32 = 600000100;
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "PascalCase", default)]
+struct AttributeComponent {
+    property_id: i32,
+}
+
+// Base stats come from AttributeComponent of level entity,
+// scaled by the growth curve of the same property
+pub fn build_monster_attribute(config_id: i64, level: i32) -> Attribute {
+    let component = level_entity_config_data::get(config_id)
+        .and_then(|data| data.components_data.get("AttributeComponent"))
+        .map(|component| {
+            AttributeComponent::deserialize(component).unwrap_or_else(|err| {
+                tracing::warn!(
+                    "monster_util: invalid AttributeComponent of entity {config_id}, err: {err}"
+                );
+                AttributeComponent::default()
+            })
+        })
+        .unwrap_or_default();
+
+    let base_property = find_base_property(component.property_id).unwrap_or_else(|| {
+        tracing::debug!("monster_util: no property for entity {config_id}, using default");
+        find_base_property(DEFAULT_PROPERTY_ID).unwrap()
+    });
+    let growth = find_growth(base_property.id, level);
+    if growth.is_none() {
+        tracing::debug!(
+            "monster_util: no growth of property {} at level {level}, stats are not scaled",
+            base_property.id
+        );
+    }
+
+    let mut attr_map = attribute_from_data(base_property);
+    if let Some(growth) = growth {
+        for (ty, ratio) in [
+            (EAttributeType::LifeMax, growth.life_max_ratio),
+            (EAttributeType::Atk, growth.atk_ratio),
+            (EAttributeType::Def, growth.def_ratio),
+        ] {
+            if let Some((base, _)) = attr_map.get_mut(&ty) {
+                *base = (*base as i64 * ratio as i64 / 10000) as i32;
+            }
+        }
+    }
+
+    attr_map.insert(EAttributeType::Lv, (level, 0));
+    let life_max = attr_map
+        .get(&EAttributeType::LifeMax)
+        .map(|(base, _)| *base)
+        .unwrap_or_default();
+    attr_map.insert(EAttributeType::Life, (life_max, 0));
+
+    Attribute::new(attr_map)
+}
+
+#[inline(always)]
+fn find_base_property(id: i32) -> Option<&'static BasePropertyData> {
+    base_property_data::iter().find(|d| d.id == id)
+}
+
+#[inline(always)]
+fn find_growth(curve_id: i32, level: i32) -> Option<&'static MonsterPropertyGrowthData> {
+    monster_property_growth_data::iter().find(|d| d.curve_id == curve_id && d.level == level)
+}
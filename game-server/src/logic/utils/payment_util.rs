@@ -0,0 +1,53 @@
+use std::sync::OnceLock;
+
+use common::time_util;
+use shorekeeper_protocol::ErrorCode;
+
+// Charges the player for a real money product, returns receipt id of the approved payment
+pub type PaymentProvider =
+    fn(player_id: i32, product_id: i32, price: i32) -> Result<String, PaymentError>;
+
+static PROVIDER: OnceLock<PaymentProvider> = OnceLock::new();
+
+#[derive(thiserror::Error, Debug)]
+pub enum PaymentError {
+    #[error("payment provider is not set")]
+    NotEnabled,
+    #[error("payment of product {0} was declined: {1}")]
+    Declined(i32, String),
+}
+
+impl From<PaymentError> for ErrorCode {
+    fn from(err: PaymentError) -> Self {
+        match err {
+            PaymentError::NotEnabled => ErrorCode::ErrPayNotEnable,
+            PaymentError::Declined(..) => ErrorCode::ErrPayCreateReceiptFail,
+        }
+    }
+}
+
+pub fn set_payment_provider(provider: PaymentProvider) {
+    if PROVIDER.set(provider).is_err() {
+        tracing::error!("set_payment_provider: provider is already set");
+    }
+}
+
+pub fn pay(player_id: i32, product_id: i32, price: i32) -> Result<String, PaymentError> {
+    let provider = PROVIDER.get().ok_or(PaymentError::NotEnabled)?;
+    provider(player_id, product_id, price)
+}
+
+// Local provider for offline servers, approves every payment without charging anything
+pub fn mock_payment_provider(
+    player_id: i32,
+    product_id: i32,
+    price: i32,
+) -> Result<String, PaymentError> {
+    let receipt_id = format!(
+        "mock-{player_id}-{product_id}-{}",
+        time_util::unix_timestamp_ms()
+    );
+
+    tracing::info!("mock payment: approved product {product_id} ({price}) for player {player_id}, receipt: {receipt_id}");
+    Ok(receipt_id)
+}
@@ -154,6 +154,21 @@ impl Map {
         output
     }
 
+    // Area of the closest level entity around given position
+    pub fn get_area_id(&self, x: f32, y: f32) -> Option<i32> {
+        let quadrant_id = self.get_quadrant_id(x, y);
+        self.get_initial_entities(quadrant_id)
+            .into_iter()
+            .filter(|entity| entity.area_id != 0)
+            .filter_map(|entity| {
+                let position = entity.transform.first()?;
+                let distance = (position.x - x).powi(2) + (position.y - y).powi(2);
+                Some((distance, entity.area_id))
+            })
+            .min_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, area_id)| area_id)
+    }
+
     pub fn get_update_entities(&self, old_quadrant_id: u64, new_quadrant_id: u64) -> (Vec<&LevelEntityConfigData>, Vec<&LevelEntityConfigData>) {
         let old_quadrants = self.get_neighbour_cells(old_quadrant_id);
         let new_quadrants = self.get_neighbour_cells(new_quadrant_id);
@@ -197,9 +212,8 @@ pub fn initialize_quadrant_system() {
     let _ = MAP_TABLE.set(maps);
 }
 
-pub fn get_map(map_id: i32) -> &'static Map {
-    // TODO: Error check for map id
-    MAP_TABLE.get().unwrap().get(&map_id).unwrap()
+pub fn try_get_map(map_id: i32) -> Option<&'static Map> {
+    MAP_TABLE.get().unwrap().get(&map_id)
 }
 
 fn recenter_map(max: f32, min: f32) -> (f32, f32, f32) {
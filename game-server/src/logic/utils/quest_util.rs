@@ -0,0 +1,139 @@
+use shorekeeper_data::{quest_data, QuestActionData};
+use shorekeeper_protocol::{
+    ChildQuestNodeStatus, QuestFinishListNotify, QuestListNotify, UpdateChildQuestNodeStatusNotify,
+};
+
+use crate::logic::player::{ItemUsage, Player, QuestError};
+
+// Quests accepting and finishing each other are chained up to this depth,
+// keeps broken data from recursing forever
+const MAX_CHAIN_DEPTH: u32 = 32;
+
+pub fn accept_quest(player: &mut Player, quest_id: i32) -> Result<(), QuestError> {
+    accept_quest_chained(player, quest_id, 0)
+}
+
+pub fn finish_node(player: &mut Player, quest_id: i32, node_id: i32) -> Result<(), QuestError> {
+    let node = player.quest.finish_node(quest_id, node_id)?;
+
+    player.notify(UpdateChildQuestNodeStatusNotify {
+        tree_owner_id: quest_id,
+        tree_inc_id: 0,
+        node_id,
+        status: ChildQuestNodeStatus::CqnsFinished.into(),
+    });
+
+    // Nodes are finished on client report which isn't verified, only their order in the tree is.
+    // Node rewards are ignored, finish actions still run once the last node is reported
+    let actions = node
+        .actions
+        .iter()
+        .filter(|action| !action.is_reward())
+        .collect::<Vec<_>>();
+    if actions.len() != node.actions.len() {
+        tracing::warn!("quest_util: quest {quest_id} node {node_id} rewards are ignored");
+    }
+    run_actions(player, quest_id, actions, 0);
+
+    if player.quest.is_tree_complete(quest_id) {
+        finish_quest(player, quest_id, 0);
+    }
+
+    Ok(())
+}
+
+// Marks quests with fulfilled requirements as ready and accepts auto accept ones
+pub fn refresh_quests(player: &mut Player) {
+    refresh_quests_chained(player, 0);
+}
+
+fn accept_quest_chained(player: &mut Player, quest_id: i32, depth: u32) -> Result<(), QuestError> {
+    if depth > MAX_CHAIN_DEPTH {
+        return Err(QuestError::ChainTooDeep(quest_id));
+    }
+
+    let data = player
+        .quest
+        .check_accept(quest_id, player.basic_info.level)?;
+
+    player.quest.accept(data);
+    notify_quest_state(player, quest_id);
+    run_actions(player, quest_id, &data.accept_actions, depth);
+
+    // Quests without nodes are completed right away
+    if player.quest.is_tree_complete(quest_id) {
+        finish_quest(player, quest_id, depth);
+    }
+
+    Ok(())
+}
+
+fn refresh_quests_chained(player: &mut Player, depth: u32) {
+    let ready = player.quest.update_ready(player.basic_info.level);
+
+    for data in ready.into_iter().filter(|data| data.auto_accept) {
+        if let Err(err) = accept_quest_chained(player, data.id, depth + 1) {
+            tracing::warn!(
+                "quest_util: failed to auto accept quest {}, err: {err}",
+                data.id
+            );
+        }
+    }
+
+    player.notify(player.quest.build_ready_list_notify());
+}
+
+fn finish_quest(player: &mut Player, quest_id: i32, depth: u32) {
+    let Some(data) = quest_data::iter().find(|d| d.id == quest_id) else {
+        return;
+    };
+
+    player.quest.finish(quest_id);
+    notify_quest_state(player, quest_id);
+    player.notify(QuestFinishListNotify {
+        quest_id: vec![quest_id],
+    });
+
+    run_actions(player, quest_id, &data.finish_actions, depth);
+    refresh_quests_chained(player, depth);
+}
+
+fn run_actions<'a>(
+    player: &mut Player,
+    quest_id: i32,
+    actions: impl IntoIterator<Item = &'a QuestActionData>,
+    depth: u32,
+) {
+    for action in actions {
+        match *action {
+            QuestActionData::AddItem { item_id, count } => {
+                if let Err(err) = player.add_items(&[ItemUsage {
+                    id: item_id,
+                    quantity: count,
+                }]) {
+                    tracing::warn!(
+                        "quest_util: quest {quest_id} failed to add item {item_id}, err: {err}"
+                    );
+                }
+            }
+            QuestActionData::AddExp { exp } => player.add_exp(exp),
+            QuestActionData::UnlockFunction { function_id } => {
+                player.func.unlock(function_id);
+                player.notify(player.func.build_func_open_notify());
+            }
+            QuestActionData::AcceptQuest { quest_id: next_id } => {
+                if let Err(err) = accept_quest_chained(player, next_id, depth + 1) {
+                    tracing::warn!(
+                        "quest_util: quest {quest_id} failed to accept quest {next_id}, err: {err}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn notify_quest_state(player: &Player, quest_id: i32) {
+    player.notify(QuestListNotify {
+        quests: vec![player.quest.build_quest_info(quest_id)],
+    });
+}
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+use shorekeeper_data::RawVectorData;
+
+use crate::logic::math::{Transform, Vector3f};
+
+pub struct Teleporter {
+    pub map_id: i32,
+    pub entity_id: i64,
+    pub transform: Transform,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TeleportComponent {
+    #[serde(default)]
+    disabled: bool,
+    teleporter_id: i32,
+    // Offset from the teleporter entity
+    teleport_position: Option<RawVectorData>,
+}
+
+static TELEPORTER_TABLE: OnceLock<HashMap<i32, Teleporter>> = OnceLock::new();
+
+// Teleporters are level entities with TeleportComponent
+pub fn initialize_teleport_system() {
+    let mut teleporters = HashMap::new();
+
+    for (_, entity) in shorekeeper_data::level_entity_config_data::iter() {
+        let Some(component) = entity.components_data.get("TeleportComponent") else {
+            continue;
+        };
+
+        let component = match TeleportComponent::deserialize(component) {
+            Ok(component) if !component.disabled => component,
+            Ok(_) => continue,
+            Err(err) => {
+                tracing::warn!(
+                    "teleport_util: invalid TeleportComponent of entity {}, err: {err}",
+                    entity.entity_id
+                );
+                continue;
+            }
+        };
+
+        let Some(location) = entity.transform.first() else {
+            continue;
+        };
+
+        let mut position = Vector3f::from(location);
+        if let Some(offset) = component.teleport_position.as_ref() {
+            let offset = Vector3f::from(offset);
+            position.x += offset.x;
+            position.y += offset.y;
+            position.z += offset.z;
+        }
+
+        // Rotation is stored in degrees as (roll, pitch, yaw)
+        let rotation = entity
+            .transform
+            .get(1)
+            .map(|r| Vector3f {
+                x: r.y,
+                y: r.z,
+                z: r.x,
+            })
+            .unwrap_or_default();
+
+        teleporters.insert(
+            component.teleporter_id,
+            Teleporter {
+                map_id: entity.map_id,
+                entity_id: entity.entity_id,
+                transform: Transform { position, rotation },
+            },
+        );
+    }
+
+    tracing::info!("teleport_util: loaded {} teleporters", teleporters.len());
+    let _ = TELEPORTER_TABLE.set(teleporters);
+}
+
+pub fn get_teleporter(id: i32) -> Option<&'static Teleporter> {
+    TELEPORTER_TABLE.get().unwrap().get(&id)
+}
@@ -1,36 +1,33 @@
 use std::cell::{BorrowMutError, RefMut};
 
-use shorekeeper_data::{base_property_data, LevelEntityConfigData};
-use shorekeeper_protocol::{DFsm, EEntityType, EntityAddNotify, EntityConfigType, EntityPb,
-                           EntityRemoveInfo, EntityRemoveNotify, EntityState, FightRoleInfo,
-                           FightRoleInfos, LivingStatus, SceneInformation, SceneMode,
-                           ScenePlayerInformation, SceneTimeInfo};
+use shorekeeper_data::LevelEntityConfigData;
+use shorekeeper_protocol::{AfterJoinSceneNotify, EAttributeType, EEntityType, EntityAddNotify,
+                           EntityConfigType, EntityPb, EntityRemoveInfo, EntityRemoveNotify,
+                           EntityState, FightRoleInfo, FightRoleInfos, JoinSceneNotify,
+                           LivingStatus, SceneInformation, SceneMode, ScenePlayerInformation,
+                           TeleportNotify, TeleportReason, TransitionOptionPb};
 
 use crate::logic::{
     components::{
-        Attribute, EntityConfig, Equip, Movement, OwnerPlayer, PlayerEntityMarker, Position,
+        Attribute, Buff, EntityConfig, Equip, Movement, OwnerPlayer, PlayerEntityMarker, Position,
         Visibility, VisionSkill,
     },
     ecs::component::ComponentContainer,
 };
-use crate::logic::components::{Fsm, MonsterAi};
 use crate::logic::ecs::entity::Entity;
 use crate::logic::ecs::world::{World, WorldEntity};
 use crate::logic::math::Transform;
-use crate::logic::player::Player;
-use crate::logic::utils::entity_serializer;
+use crate::logic::player::{Player, PlayerLocation};
+use crate::logic::utils::{ai_util, entity_serializer, fsm_util, monster_util, quadrant_util};
 use crate::query_with;
 
 #[macro_export]
 macro_rules! create_player_entity_pb {
-    ($role_list:expr, $cur_map_id:expr, $world:expr, $player_id:expr, $position:expr, $explore_tools:expr) => {{
+    ($role_list:expr, $cur_map_id:expr, $world:expr, $player_id:expr, $position:expr, $explore_tools:expr, $inventory:expr) => {{
         let mut pbs = Vec::new();
 
         for role in $role_list {
             let role_id: i32 = role.role_id;
-            let base_property = base_property_data::iter()
-                .find(|d| d.id == role_id)
-                .expect("macro create_role_entity_pb: Base property data not found");
 
             let entity = $world
                 .create_entity(role_id, EEntityType::Player.into(), $cur_map_id)
@@ -46,13 +43,15 @@ macro_rules! create_player_entity_pb {
                 .with(ComponentContainer::Visibility(Visibility(
                     role_id == role_id,
                 )))
-                .with(ComponentContainer::Attribute(Attribute::from_data(
-                    base_property,
-                )))
+                .with(ComponentContainer::Attribute(Attribute::from_role(&role, &$inventory)))
+                .with(ComponentContainer::Buff(Buff::default()))
                 .with(ComponentContainer::Movement(Movement::default()))
                 .with(ComponentContainer::Equip(Equip {
                     weapon_id: role.equip_weapon,
-                    weapon_breach_level: 90, // TODO: store this too
+                    weapon_breach_level: $inventory
+                        .get_role_weapon(role_id)
+                        .map(|w| w.breach)
+                        .unwrap_or_default(),
                 }))
                 .with(ComponentContainer::VisionSkill(VisionSkill {
                     skill_id: $explore_tools.active_explore_skill,
@@ -115,15 +114,16 @@ pub fn add_player_entities(player: &Player) {
                 .with(ComponentContainer::Visibility(Visibility(
                     role.role_id == cur_role_id,
                 )))
-                .with(ComponentContainer::Attribute(Attribute::from_data(
-                    base_property_data::iter()
-                        .find(|d| d.id == role.role_id)
-                        .unwrap(),
-                )))
+                .with(ComponentContainer::Attribute(Attribute::from_role(role, &player.inventory)))
+                .with(ComponentContainer::Buff(Buff::default()))
                 .with(ComponentContainer::Movement(Movement::default()))
                 .with(ComponentContainer::Equip(Equip {
                     weapon_id: role.equip_weapon,
-                    weapon_breach_level: 0, // TODO: store this too
+                    weapon_breach_level: player
+                        .inventory
+                        .get_role_weapon(role.role_id)
+                        .map(|w| w.breach)
+                        .unwrap_or_default(),
                 }))
                 .with(ComponentContainer::VisionSkill(VisionSkill {
                     skill_id: player.explore_tools.active_explore_skill,
@@ -139,7 +139,100 @@ pub fn add_player_entities(player: &Player) {
     }
 }
 
+// Moves player entities to the given location and rebuilds surrounding level entities,
+// changing the map makes the client reload the whole scene
+pub fn teleport_player(
+    player: &mut Player,
+    instance_id: i32,
+    transform: Transform,
+    reason: TeleportReason,
+) {
+    let location = PlayerLocation {
+        instance_id,
+        position: transform,
+    };
+
+    if location.map_id() != player.basic_info.cur_map_id {
+        change_scene(player, location);
+        return;
+    }
+
+    if let Some(old_map) = quadrant_util::try_get_map(player.location.map_id()) {
+        remove_entities(player, &old_map.get_initial_entities(player.quadrant_id));
+    }
+
+    player.location = location;
+    let transform = player.location.position.clone();
+
+    {
+        let world_ref = player.world.borrow();
+        let world = world_ref.get_world_entity();
+
+        for (_, _, owner, mut position) in
+            query_with!(world, PlayerEntityMarker, OwnerPlayer, Position)
+        {
+            if owner.0 == player.basic_info.id {
+                position.0 = transform.clone();
+            }
+        }
+    }
+
+    player.notify(TeleportNotify {
+        map_id: player.basic_info.cur_map_id,
+        pos_x: transform.position.x,
+        pos_y: transform.position.y,
+        pos_z: transform.position.z,
+        pos_a: transform.rotation.y,
+        reason: reason.into(),
+        game_ctx: None,
+        transition_option: Some(TransitionOptionPb::default()),
+    });
+
+    add_quadrant_entities(player);
+}
+
+// Replaces world entities with a fresh set for the map of new location
+pub fn change_scene(player: &mut Player, location: PlayerLocation) {
+    let map_id = location.map_id();
+    player.location = location;
+    player.basic_info.cur_map_id = map_id;
+    player.teleport.finish_transfer();
+
+    {
+        let mut world = player.world.borrow_mut();
+        world.world_entitys.clear();
+        world.world_entitys.insert(map_id, WorldEntity::default());
+        world.player_cur_map_id = map_id;
+    }
+
+    add_player_entities(player);
+    player.notify(JoinSceneNotify {
+        scene_info: Some(build_scene_information(player)),
+        max_entity_id: i64::MAX,
+        transition_option: Some(TransitionOptionPb::default()),
+    });
+    player.notify(AfterJoinSceneNotify::default());
+
+    add_quadrant_entities(player);
+}
+
+// Adds level entities of 3x3 quadrant neighbourhood around the player
+pub fn add_quadrant_entities(player: &mut Player) {
+    let Some(map) = quadrant_util::try_get_map(player.location.map_id()) else {
+        tracing::debug!("map {} has no level entities", player.location.map_id());
+        return;
+    };
+
+    player.quadrant_id = map.get_quadrant_id(
+        player.location.position.position.x * 100.0,
+        player.location.position.position.y * 100.0,
+    );
+    add_entities(player, &map.get_initial_entities(player.quadrant_id));
+}
+
 pub fn build_scene_information(player: &Player) -> SceneInformation {
+    let time_info = player.world.borrow().build_scene_time_info();
+
     SceneInformation {
         scene_id: String::new(),
         instance_id: player.location.instance_id,
@@ -150,11 +243,7 @@ pub fn build_scene_information(player: &Player) -> SceneInformation {
         aoi_data: Some(entity_serializer::build_scene_add_on_init_data(player)),
         player_infos: build_player_info_list(&player.world.borrow_mut()),
         mode: SceneMode::Single.into(),
-        time_info: Some(SceneTimeInfo {
-            owner_time_clock_time_span: 0,
-            hour: 8,
-            minute: 0,
-        }),
+        time_info: Some(time_info),
         cur_context_id: player.basic_info.id as i64,
         ..Default::default()
     }
@@ -220,9 +309,11 @@ fn build_player_info_list(world: &World) -> Vec<ScenePlayerInformation> {
         .collect()
 }
 
-pub fn build_monster_entity(world: &mut WorldEntity, config_id: i32, map_id: i32, transform: Transform) -> Entity {
-    // TODO: Check for more components, AI and so
-    world.create_entity(config_id, EEntityType::Monster.into(), map_id)
+// Monster AI is run by the client of controller player
+pub fn build_monster_entity(world: &mut WorldEntity, config_id: i32, map_id: i32, transform: Transform, level: i32, controller_id: i32) -> Entity {
+    let attribute = monster_util::build_monster_attribute(config_id as i64, level);
+
+    let mut builder = world.create_entity(config_id, EEntityType::Monster.into(), map_id)
         .with(ComponentContainer::EntityConfig(EntityConfig {
             config_id,
             config_type: EntityConfigType::Level,
@@ -231,47 +322,63 @@ pub fn build_monster_entity(world: &mut WorldEntity, config_id: i32, map_id: i32
         }))
         .with(ComponentContainer::Position(Position(transform)))
         .with(ComponentContainer::Visibility(Visibility(true)))
-        .with(ComponentContainer::Attribute(Attribute::from_data(
-            base_property_data::iter()
-                .find(|d| d.id == 600000100) // TODO: Implement monster stats
-                .unwrap(),
-        )))
-        .with(ComponentContainer::MonsterAi(MonsterAi {
-            weapon_id: 0,
-            hatred_group_id: 0,
-            ai_team_init_id: 100,
-            combat_message_id: 0,
-        }))
-        .with(ComponentContainer::Fsm(Fsm {
-            fsms: vec![
-                DFsm {
-                    fsm_id: 10007,
-                    current_state: 10013,
-                    flag: 0,
-                    k_ts: 0,
-                },
-                DFsm {
-                    fsm_id: 10007,
-                    current_state: 10015,
-                    flag: 0,
-                    k_ts: 0,
-                },
-                DFsm {
-                    fsm_id: 10007,
-                    current_state: 10012,
-                    flag: 0,
-                    k_ts: 0,
-                },
-            ],
-            hash_code: 0,
-            common_hash_code: 0,
-            black_board: vec![],
-            fsm_custom_blackboard_datas: None,
-        }))
+        .with(ComponentContainer::Attribute(attribute))
+        .with(ComponentContainer::Buff(Buff::default()));
+
+    if let Some(monster_ai) = ai_util::build_monster_ai(config_id as i64, controller_id) {
+        builder = builder.with(ComponentContainer::MonsterAi(monster_ai));
+    }
+
+    if let Some(fsm) = fsm_util::build_fsm(config_id as i64) {
+        builder = builder.with(ComponentContainer::Fsm(fsm));
+    }
+
+    // TODO: Check for more components
+    builder
         .with(ComponentContainer::Movement(Movement::default()))
         .build()
 }
 
+// Instances with fixed entity level override the one derived from world level
+pub fn monster_level(player: &Player) -> i32 {
+    match player.instance.entity_level() {
+        0 => player.basic_info.monster_level(),
+        level => level,
+    }
+}
+
+// Rescales already spawned monsters to the current monster level, keeping their life ratio,
+// changes are broadcast by AttributeSystem
+pub fn update_monster_levels(player: &Player) {
+    let level = monster_level(player);
+    let world_ref = player.world.borrow();
+
+    for (_, config, mut attribute) in
+        query_with!(world_ref.get_world_entity(), EntityConfig, Attribute)
+    {
+        if config.entity_type != EEntityType::Monster
+            || !attribute.is_alive()
+            || attribute.value(EAttributeType::Lv) == level
+        {
+            continue;
+        }
+
+        let life = attribute.value(EAttributeType::Life) as i64;
+        let life_max = attribute.value(EAttributeType::LifeMax).max(1) as i64;
+
+        let mut attr_map =
+            monster_util::build_monster_attribute(config.config_id as i64, level).attr_map;
+        let new_life_max = attr_map
+            .get(&EAttributeType::LifeMax)
+            .map(|(base, _)| *base as i64)
+            .unwrap_or_default();
+        let new_life = (new_life_max * life / life_max).max(1) as i32;
+        attr_map.insert(EAttributeType::Life, (new_life, 0));
+
+        attribute.set_attr_map(attr_map);
+    }
+}
+
 pub fn remove_entities(player: &Player, entities: &[&LevelEntityConfigData]) {
     let mut removed_entities = Vec::with_capacity(entities.len());
     // Enclose to drop borrow mut ASAP
@@ -295,6 +402,7 @@ pub fn remove_entities(player: &Player, entities: &[&LevelEntityConfigData]) {
 }
 
 pub fn add_entities(player: &Player, entities: &[&LevelEntityConfigData]) {
+    let level = monster_level(player);
     let mut added_entities = Vec::with_capacity(entities.len());
     // Enclose to drop borrow mut ASAP
     {
@@ -309,6 +417,8 @@ pub fn add_entities(player: &Player, entities: &[&LevelEntityConfigData]) {
                     entity.entity_id as i32, // TODO: Should be i64
                     entity.map_id,
                     Transform::from(&entity.transform[..]),
+                    level,
+                    player.basic_info.id,
                 ));
             } else {
                 tracing::debug!("Unhandled entity to be added of type: {}", entity.blueprint_type);
@@ -316,22 +426,28 @@ pub fn add_entities(player: &Player, entities: &[&LevelEntityConfigData]) {
         }
     }
 
-    let mut world_ref = player.world.borrow();
-    let world = world_ref.get_world_entity();
     // Since kuro has issues, we can only send one
     for entity in added_entities {
-        let mut pb = EntityPb {
-            id: entity.entity_id as i64, // TODO: Should be i64
-            ..Default::default()
-        };
+        notify_entity_added(player, entity);
+    }
+}
 
-        world.get_entity_components(entity.entity_id)
-            .into_iter()
-            .for_each(|comp| comp.set_pb_data(&mut pb));
+pub fn notify_entity_added(player: &Player, entity: Entity) {
+    let mut pb = EntityPb {
+        id: entity.entity_id as i64, // TODO: Should be i64
+        ..Default::default()
+    };
 
-        player.notify(EntityAddNotify {
-            entity_pbs: vec![pb],
-            is_add: true,
-        });
-    }
+    player
+        .world
+        .borrow()
+        .get_world_entity()
+        .get_entity_components(entity.entity_id)
+        .into_iter()
+        .for_each(|comp| comp.set_pb_data(&mut pb));
+
+    player.notify(EntityAddNotify {
+        entity_pbs: vec![pb],
+        is_add: true,
+    });
 }
\ No newline at end of file
@@ -0,0 +1,174 @@
+use std::sync::{Arc, OnceLock};
+use tokio::sync::mpsc;
+
+use common::time_util;
+use shorekeeper_database::{models, query, query_as, Error, PgPool};
+use shorekeeper_network::{config::ServiceEndPoint, ServiceClient, ServiceMessage};
+use shorekeeper_protocol::{MailLevel, MessageID, Protobuf, SendMailPush};
+
+use crate::{
+    logic::{
+        player::{ItemUsage, Mail},
+        thread_mgr::LogicInput,
+    },
+    session::SessionManager,
+};
+
+static SENDER: OnceLock<mpsc::Sender<MailUpdate>> = OnceLock::new();
+// Connection to own service end point, mails are sent through SendMailPush like from other services
+static SERVICE_CLIENT: OnceLock<ServiceClient> = OnceLock::new();
+
+#[derive(Debug)]
+pub enum MailUpdate {
+    Read { mail_id: i64, read_time: i64 },
+    Delete { mail_ids: Vec<i64> },
+}
+
+pub struct NewMail {
+    pub title: String,
+    pub content: String,
+    pub sender: String,
+    pub attachments: Vec<ItemUsage>,
+    pub valid_time: i64,
+}
+
+pub fn start(db: Arc<PgPool>, service_id: u32, service_end_point: &'static ServiceEndPoint) {
+    let _ = SENDER.get_or_init(|| {
+        let (tx, rx) = mpsc::channel(32);
+        tokio::spawn(async move { task_loop(rx, db).await });
+
+        tx
+    });
+
+    let _ = SERVICE_CLIENT.get_or_init(|| ServiceClient::new(service_id, service_end_point));
+}
+
+// Called from logic threads to persist mailbox changes
+pub fn push(player_id: i32, update: MailUpdate) {
+    tracing::debug!("mail_task: player_id: {player_id}, update: {update:?}");
+
+    let _ = SENDER.get().unwrap().blocking_send(update);
+}
+
+// Called from logic threads to send mail, delivered by SendMailPush handler
+pub fn request_send(push: SendMailPush) {
+    tracing::debug!("mail_task: requesting mail send, push: {push:?}");
+
+    SERVICE_CLIENT.get().unwrap().push_sync(ServiceMessage {
+        src_service_id: 0,
+        rpc_id: 0,
+        message_id: SendMailPush::MESSAGE_ID,
+        data: push.encode_to_vec().into_boxed_slice(),
+    });
+}
+
+// Removes expired mails and returns the rest of player's mailbox
+pub async fn load_player_mails(db: &PgPool, player_id: i32) -> Result<Vec<Mail>, Error> {
+    query("DELETE FROM t_player_mail WHERE player_id = ($1) AND expire_time <= ($2)")
+        .bind(player_id)
+        .bind(time_util::unix_timestamp() as i64)
+        .execute(db)
+        .await?;
+
+    let rows: Vec<models::PlayerMailRow> =
+        query_as("SELECT * FROM t_player_mail WHERE player_id = ($1)")
+            .bind(player_id)
+            .fetch_all(db)
+            .await?;
+
+    Ok(rows.into_iter().map(mail_from_row).collect())
+}
+
+// Sends mail to a single player, or to every player if player_id is 0.
+// Online recipients receive it through their logic thread right away.
+pub async fn send_mail(
+    db: &PgPool,
+    session_mgr: &SessionManager,
+    player_id: i32,
+    mail: NewMail,
+) -> Result<usize, Error> {
+    let received_time = time_util::unix_timestamp() as i64;
+    let (attachment_ids, attachment_counts): (Vec<i32>, Vec<i32>) = mail
+        .attachments
+        .iter()
+        .map(|item| (item.id, item.quantity))
+        .unzip();
+
+    let rows: Vec<models::PlayerMailRow> = query_as(
+        "INSERT INTO t_player_mail (player_id, title, content, sender, level, attachment_ids, attachment_counts, received_time, expire_time) \
+        SELECT player_id, $2, $3, $4, $5, $6, $7, $8, $9 FROM t_player_data WHERE ($1) = 0 OR player_id = ($1) \
+        RETURNING *",
+    )
+    .bind(player_id)
+    .bind(mail.title.as_str())
+    .bind(mail.content.as_str())
+    .bind(mail.sender.as_str())
+    .bind(i32::from(MailLevel::General))
+    .bind(attachment_ids)
+    .bind(attachment_counts)
+    .bind(received_time)
+    .bind(received_time + mail.valid_time)
+    .fetch_all(db)
+    .await?;
+
+    let count = rows.len();
+    for row in rows.into_iter() {
+        if let Some(session) = session_mgr.get_by_player_id(row.player_id) {
+            session.logic_thread.input(LogicInput::AddMail {
+                player_id: row.player_id,
+                mail: mail_from_row(row),
+            });
+        }
+    }
+
+    Ok(count)
+}
+
+fn mail_from_row(row: models::PlayerMailRow) -> Mail {
+    Mail {
+        id: row.mail_id,
+        title: row.title,
+        content: row.content,
+        sender: row.sender,
+        level: row.level,
+        attachments: row
+            .attachment_ids
+            .into_iter()
+            .zip(row.attachment_counts)
+            .map(|(id, quantity)| ItemUsage { id, quantity })
+            .collect(),
+        received_time: row.received_time,
+        read_time: row.read_time,
+        expire_time: row.expire_time,
+        is_claimed: row.is_claimed,
+    }
+}
+
+async fn task_loop(mut receiver: mpsc::Receiver<MailUpdate>, db: Arc<PgPool>) {
+    loop {
+        let Some(update) = receiver.recv().await else {
+            tracing::warn!("mail_task: channel was closed, exitting");
+            return;
+        };
+
+        let result = match update {
+            MailUpdate::Read { mail_id, read_time } => {
+                query("UPDATE t_player_mail SET read_time = ($1) WHERE mail_id = ($2)")
+                    .bind(read_time)
+                    .bind(mail_id)
+                    .execute(db.as_ref())
+                    .await
+            }
+            MailUpdate::Delete { mail_ids } => {
+                query("DELETE FROM t_player_mail WHERE mail_id = ANY($1)")
+                    .bind(mail_ids)
+                    .execute(db.as_ref())
+                    .await
+            }
+        };
+
+        if let Err(err) = result {
+            tracing::error!("mail_task: failed to update mail, err: {err}");
+        }
+    }
+}
@@ -5,12 +5,15 @@ use common::config_util;
 use config::ServiceConfig;
 use session::SessionManager;
 
+mod chat_task;
 mod config;
 mod gateway_connection;
 mod logic;
+mod mail_task;
 mod player_save_task;
 mod service_message_handler;
 mod session;
+mod social_task;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -22,13 +25,24 @@ async fn main() -> Result<()> {
     ::common::logging::init(::tracing::Level::DEBUG);
     shorekeeper_data::load_all_json_data("assets/logic/BinData")?;
     logic::utils::quadrant_util::initialize_quadrant_system();
+    logic::utils::teleport_util::initialize_teleport_system();
 
     let database = Arc::new(shorekeeper_database::connect_to(&CONFIG.database).await?);
     shorekeeper_database::run_migrations(database.as_ref()).await?;
 
+    logic::gm::set_allowed_players(&CONFIG.gm.allowed_player_ids);
+    logic::handler::set_chat_command_hook(logic::gm::execute);
+    if CONFIG.payment.use_mock_provider {
+        logic::utils::payment_util::set_payment_provider(
+            logic::utils::payment_util::mock_payment_provider,
+        );
+    }
     logic::thread_mgr::start_logic_threads(1);
 
     player_save_task::start(database.clone());
+    mail_task::start(database.clone(), CONFIG.service_id, &CONFIG.service_end_point);
+    social_task::start(database.clone(), &SESSION_MGR);
+    chat_task::start(database.clone(), &SESSION_MGR);
     gateway_connection::init(CONFIG.service_id, &CONFIG.gateway_end_point);
     service_message_handler::run(&CONFIG.service_end_point, &SESSION_MGR, database).await?;
 
@@ -1,7 +1,7 @@
 use std::sync::{Arc, OnceLock};
 use tokio::sync::mpsc;
 
-use shorekeeper_database::{query, PgPool};
+use shorekeeper_database::{query, Error, PgPool};
 use shorekeeper_protocol::{PlayerSaveData, Protobuf};
 
 static SENDER: OnceLock<mpsc::Sender<PlayerSaveQuery>> = OnceLock::new();
@@ -10,6 +10,7 @@ static SENDER: OnceLock<mpsc::Sender<PlayerSaveQuery>> = OnceLock::new();
 pub enum PlayerSaveReason {
     PeriodicalSave,
     PlayerLogicStopped,
+    MailClaimed,
 }
 
 pub fn start(db: Arc<PgPool>) {
@@ -21,7 +22,13 @@ pub fn start(db: Arc<PgPool>) {
     });
 }
 
-pub fn push(player_id: i32, save_data: PlayerSaveData, reason: PlayerSaveReason) {
+// Mails claimed since the last save are marked together with the data holding their items
+pub fn push(
+    player_id: i32,
+    save_data: PlayerSaveData,
+    claimed_mail_ids: Vec<i64>,
+    reason: PlayerSaveReason,
+) {
     tracing::debug!(
         "player_save_task: requesting save for player with id {player_id}, reason: {reason:?}"
     );
@@ -29,12 +36,14 @@ pub fn push(player_id: i32, save_data: PlayerSaveData, reason: PlayerSaveReason)
     let _ = SENDER.get().unwrap().blocking_send(PlayerSaveQuery {
         player_id,
         save_data,
+        claimed_mail_ids,
     });
 }
 
 struct PlayerSaveQuery {
     pub player_id: i32,
     pub save_data: PlayerSaveData,
+    pub claimed_mail_ids: Vec<i64>,
 }
 
 async fn task_loop(mut receiver: mpsc::Receiver<PlayerSaveQuery>, db: Arc<PgPool>) {
@@ -44,18 +53,31 @@ async fn task_loop(mut receiver: mpsc::Receiver<PlayerSaveQuery>, db: Arc<PgPool
             return;
         };
 
-        let bin_data = save_query.save_data.encode_to_vec();
-
-        let _ = query("UPDATE t_player_data SET bin_data = ($1) WHERE player_id = ($2)")
-            .bind(bin_data)
-            .bind(save_query.player_id)
-            .execute(db.as_ref())
-            .await
-            .inspect_err(|err| {
-                tracing::error!(
-                    "player_save_task: failed to save data for player_id: {}, err: {err}",
-                    save_query.player_id
-                )
-            });
+        let _ = save(db.as_ref(), &save_query).await.inspect_err(|err| {
+            tracing::error!(
+                "player_save_task: failed to save data for player_id: {}, err: {err}",
+                save_query.player_id
+            )
+        });
     }
 }
+
+async fn save(db: &PgPool, save_query: &PlayerSaveQuery) -> Result<(), Error> {
+    let bin_data = save_query.save_data.encode_to_vec();
+    let mut tx = db.begin().await?;
+
+    query("UPDATE t_player_data SET bin_data = ($1) WHERE player_id = ($2)")
+        .bind(bin_data)
+        .bind(save_query.player_id)
+        .execute(&mut *tx)
+        .await?;
+
+    if !save_query.claimed_mail_ids.is_empty() {
+        query("UPDATE t_player_mail SET is_claimed = true WHERE mail_id = ANY($1)")
+            .bind(&save_query.claimed_mail_ids)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await
+}
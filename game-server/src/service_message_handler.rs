@@ -5,13 +5,19 @@ use shorekeeper_database::{models, query, query_as, PgPool};
 use shorekeeper_network::{config::ServiceEndPoint, ServiceListener, ServiceMessage};
 use shorekeeper_protocol::{
     message::Message, CreatePlayerDataRequest, CreatePlayerDataResponse, ErrorCode,
-    ForwardClientMessagePush, MessageID, PlayerSaveData, Protobuf, ProtocolUnit,
+    ForwardClientMessagePush, MessageID, PlayerSaveData, Protobuf, ProtocolUnit, SendMailPush,
     StartPlayerSessionRequest, StartPlayerSessionResponse, StopPlayerSessionPush,
 };
 
 use crate::{
-    gateway_connection,
-    logic::{self, player::Player, thread_mgr::LogicInput},
+    chat_task, gateway_connection,
+    logic::{
+        self,
+        player::{ItemUsage, Player},
+        thread_mgr::LogicInput,
+    },
+    mail_task::{self, NewMail},
+    social_task,
     session::{Session, SessionManager},
 };
 
@@ -71,6 +77,9 @@ async fn handler_loop(
             ForwardClientMessagePush::MESSAGE_ID => {
                 on_forward_client_message_push(message, session_mgr).await
             }
+            SendMailPush::MESSAGE_ID => {
+                on_send_mail_push(message, session_mgr, db.as_ref()).await
+            }
             unhandled => tracing::warn!(
                 "unhandled service message id: {unhandled}, from service_id: {}",
                 message.src_service_id
@@ -129,6 +138,48 @@ async fn on_start_player_session_request(
         return;
     };
 
+    let Ok(mails) = mail_task::load_player_mails(db, request.player_id)
+        .await
+        .inspect_err(|err| {
+            tracing::error!(
+                "failed to fetch player mails, player_id: {}, err: {err}",
+                request.player_id
+            )
+        })
+    else {
+        response.code = ErrorCode::QueryPlayerDataFailed.into();
+        send_to_gateway(response, message.rpc_id).await;
+        return;
+    };
+
+    let Ok(social) = social_task::load_player_social(db, session_mgr, request.player_id)
+        .await
+        .inspect_err(|err| {
+            tracing::error!(
+                "failed to fetch player social data, player_id: {}, err: {err}",
+                request.player_id
+            )
+        })
+    else {
+        response.code = ErrorCode::ErrLoadFriendData.into();
+        send_to_gateway(response, message.rpc_id).await;
+        return;
+    };
+
+    let Ok(private_chats) = chat_task::load_private_chats(db, request.player_id)
+        .await
+        .inspect_err(|err| {
+            tracing::error!(
+                "failed to fetch private chats, player_id: {}, err: {err}",
+                request.player_id
+            )
+        })
+    else {
+        response.code = ErrorCode::ErrNoLoadPrivateChatData.into();
+        send_to_gateway(response, message.rpc_id).await;
+        return;
+    };
+
     let logic_thread = logic::thread_mgr::get_least_loaded_thread();
     let session = Arc::new(Session {
         gateway_id: message.src_service_id,
@@ -142,6 +193,9 @@ async fn on_start_player_session_request(
         enter_rpc_id: message.rpc_id,
         session: session.clone(),
         player_save_data,
+        mails,
+        social,
+        private_chats,
     });
 
     session_mgr.add(session.clone());
@@ -215,6 +269,51 @@ async fn on_forward_client_message_push(
     });
 }
 
+async fn on_send_mail_push(
+    message: ServiceMessage,
+    session_mgr: &'static SessionManager,
+    db: &PgPool,
+) {
+    let Ok(push) = SendMailPush::decode(message.data.as_ref()) else {
+        tracing::warn!(
+            "failed to decode SendMailPush, data: {}",
+            hex::encode(message.data.as_ref())
+        );
+        return;
+    };
+
+    if push.valid_time <= 0 || push.attachments.iter().any(|item| item.count <= 0) {
+        tracing::warn!("SendMailPush: invalid mail, push: {push:?}");
+        return;
+    }
+
+    let mail = NewMail {
+        title: push.title,
+        content: push.content,
+        sender: push.sender,
+        attachments: push
+            .attachments
+            .iter()
+            .map(|item| ItemUsage {
+                id: item.item_id,
+                quantity: item.count,
+            })
+            .collect(),
+        valid_time: push.valid_time,
+    };
+
+    match mail_task::send_mail(db, session_mgr, push.player_id, mail).await {
+        Ok(count) => tracing::info!(
+            "SendMailPush: mail sent to {count} player(s), player_id: {}",
+            push.player_id
+        ),
+        Err(err) => tracing::error!(
+            "SendMailPush: failed to send mail, player_id: {}, err: {err}",
+            push.player_id
+        ),
+    }
+}
+
 async fn on_create_player_data_request(message: ServiceMessage, db: &PgPool) {
     let Ok(request) = CreatePlayerDataRequest::decode(message.data.as_ref()) else {
         tracing::warn!(
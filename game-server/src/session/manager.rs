@@ -20,6 +20,20 @@ impl SessionManager {
             .get(&Session::global_id(gateway_id, session_id))
     }
 
+    pub fn sessions(&self) -> Vec<Arc<Session>> {
+        self.session_map
+            .iter()
+            .map(|kv| kv.value().clone())
+            .collect()
+    }
+
+    pub fn get_by_player_id(&self, player_id: i32) -> Option<Arc<Session>> {
+        self.session_map
+            .iter()
+            .find(|kv| kv.player_id == player_id)
+            .map(|kv| kv.value().clone())
+    }
+
     pub fn remove(&self, gateway_id: u32, session_id: u32) -> Option<Arc<Session>> {
         self.session_map
             .remove(&Session::global_id(gateway_id, session_id))
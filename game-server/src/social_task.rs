@@ -0,0 +1,427 @@
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::mpsc;
+
+use common::time_util;
+use shorekeeper_database::{models, query, query_as, Error, PgPool};
+use shorekeeper_protocol::{PlayerSaveData, Protobuf};
+
+use crate::{
+    logic::{
+        player::{Friend, FriendApplication, PlayerSocial, SocialError, SocialEvent, SocialPlayer},
+        thread_mgr::LogicInput,
+    },
+    session::SessionManager,
+};
+
+static SENDER: OnceLock<mpsc::Sender<SocialRequest>> = OnceLock::new();
+
+// Changes already applied to the local state of the requesting player,
+// the task persists them and informs the other side
+#[derive(Debug)]
+pub enum SocialRequest {
+    ApplySend {
+        player_id: i32,
+        target_id: i32,
+    },
+    ApplyHandle {
+        player_id: i32,
+        applicant_id: i32,
+        approve: bool,
+    },
+    DeleteFriend {
+        player_id: i32,
+        friend_id: i32,
+    },
+    Remark {
+        player_id: i32,
+        friend_id: i32,
+        remark: String,
+    },
+    Block {
+        player_id: i32,
+        target_id: i32,
+    },
+    Unblock {
+        player_id: i32,
+        target_id: i32,
+    },
+}
+
+pub fn start(db: Arc<PgPool>, session_mgr: &'static SessionManager) {
+    let _ = SENDER.get_or_init(|| {
+        let (tx, rx) = mpsc::channel(32);
+        tokio::spawn(async move { task_loop(rx, db, session_mgr).await });
+
+        tx
+    });
+}
+
+pub fn push(request: SocialRequest) {
+    tracing::debug!("social_task: requested {request:?}");
+
+    let _ = SENDER.get().unwrap().blocking_send(request);
+}
+
+pub async fn load_player_social(
+    db: &PgPool,
+    session_mgr: &SessionManager,
+    player_id: i32,
+) -> Result<PlayerSocial, Error> {
+    let friends: Vec<models::FriendRow> = query_as("SELECT * FROM t_friend WHERE player_id = ($1)")
+        .bind(player_id)
+        .fetch_all(db)
+        .await?;
+
+    let applications: Vec<models::FriendApplyRow> =
+        query_as("SELECT * FROM t_friend_apply WHERE player_id = ($1)")
+            .bind(player_id)
+            .fetch_all(db)
+            .await?;
+
+    let blocks: Vec<models::BlockListRow> =
+        query_as("SELECT * FROM t_block_list WHERE player_id = ($1)")
+            .bind(player_id)
+            .fetch_all(db)
+            .await?;
+
+    let ids: Vec<i32> = friends
+        .iter()
+        .map(|row| row.friend_id)
+        .chain(applications.iter().map(|row| row.applicant_id))
+        .chain(blocks.iter().map(|row| row.blocked_id))
+        .collect();
+
+    let mut players = fetch_players(db, session_mgr, &ids).await?;
+
+    Ok(PlayerSocial::new(
+        friends
+            .into_iter()
+            .flat_map(|row| {
+                players.get(&row.friend_id).cloned().map(|info| Friend {
+                    info,
+                    remark: row.remark,
+                })
+            })
+            .collect(),
+        applications
+            .into_iter()
+            .flat_map(|row| {
+                players
+                    .get(&row.applicant_id)
+                    .cloned()
+                    .map(|info| FriendApplication {
+                        info,
+                        create_time: row.create_time,
+                    })
+            })
+            .collect(),
+        blocks
+            .into_iter()
+            .flat_map(|row| players.remove(&row.blocked_id))
+            .collect(),
+    ))
+}
+
+async fn fetch_players(
+    db: &PgPool,
+    session_mgr: &SessionManager,
+    ids: &[i32],
+) -> Result<HashMap<i32, SocialPlayer>, Error> {
+    let rows: Vec<models::PlayerDataRow> =
+        query_as("SELECT * FROM t_player_data WHERE player_id = ANY($1)")
+            .bind(ids)
+            .fetch_all(db)
+            .await?;
+
+    Ok(rows
+        .into_iter()
+        .flat_map(|row| {
+            let Ok(save_data) = PlayerSaveData::decode(row.bin_data.as_slice()) else {
+                tracing::error!(
+                    "social_task: player data is corrupted, player id: {}",
+                    row.player_id
+                );
+                return None;
+            };
+
+            let is_online = session_mgr.get_by_player_id(row.player_id).is_some();
+            let mut info =
+                SocialPlayer::from_basic_data(&save_data.basic_data.unwrap_or_default(), is_online);
+            info.player_id = row.player_id;
+
+            Some((row.player_id, info))
+        })
+        .collect())
+}
+
+async fn fetch_player(
+    db: &PgPool,
+    session_mgr: &SessionManager,
+    player_id: i32,
+) -> Result<Option<SocialPlayer>, Error> {
+    Ok(fetch_players(db, session_mgr, &[player_id])
+        .await?
+        .remove(&player_id))
+}
+
+// Hands event to logic thread of the player if they're online,
+// offline players receive the change with their social data on next login
+fn deliver(session_mgr: &SessionManager, player_id: i32, event: SocialEvent) {
+    if let Some(session) = session_mgr.get_by_player_id(player_id) {
+        session
+            .logic_thread
+            .input(LogicInput::SocialEvent { player_id, event });
+    }
+}
+
+async fn task_loop(
+    mut receiver: mpsc::Receiver<SocialRequest>,
+    db: Arc<PgPool>,
+    session_mgr: &'static SessionManager,
+) {
+    loop {
+        let Some(request) = receiver.recv().await else {
+            tracing::warn!("social_task: channel was closed, exitting");
+            return;
+        };
+
+        if let Err(err) = handle_request(db.as_ref(), session_mgr, request).await {
+            tracing::error!("social_task: failed to handle request, err: {err}");
+        }
+    }
+}
+
+async fn handle_request(
+    db: &PgPool,
+    session_mgr: &SessionManager,
+    request: SocialRequest,
+) -> Result<(), Error> {
+    match request {
+        SocialRequest::ApplySend {
+            player_id,
+            target_id,
+        } => on_apply_send(db, session_mgr, player_id, target_id).await,
+        SocialRequest::ApplyHandle {
+            player_id,
+            applicant_id,
+            approve,
+        } => on_apply_handle(db, session_mgr, player_id, applicant_id, approve).await,
+        SocialRequest::DeleteFriend {
+            player_id,
+            friend_id,
+        } => {
+            delete_friendship(db, player_id, friend_id).await?;
+            deliver(
+                session_mgr,
+                friend_id,
+                SocialEvent::FriendDeleted(player_id),
+            );
+            Ok(())
+        }
+        SocialRequest::Remark {
+            player_id,
+            friend_id,
+            remark,
+        } => {
+            query("UPDATE t_friend SET remark = ($1) WHERE player_id = ($2) AND friend_id = ($3)")
+                .bind(remark)
+                .bind(player_id)
+                .bind(friend_id)
+                .execute(db)
+                .await?;
+            Ok(())
+        }
+        SocialRequest::Block {
+            player_id,
+            target_id,
+        } => on_block(db, session_mgr, player_id, target_id).await,
+        SocialRequest::Unblock {
+            player_id,
+            target_id,
+        } => {
+            query("DELETE FROM t_block_list WHERE player_id = ($1) AND blocked_id = ($2)")
+                .bind(player_id)
+                .bind(target_id)
+                .execute(db)
+                .await?;
+            Ok(())
+        }
+    }
+}
+
+// Checks the other side of a friend request, returns the reason it can't be made
+async fn check_target(
+    db: &PgPool,
+    player_id: i32,
+    target_id: i32,
+) -> Result<Option<SocialError>, Error> {
+    if query("SELECT 1 FROM t_player_data WHERE player_id = ($1)")
+        .bind(target_id)
+        .fetch_optional(db)
+        .await?
+        .is_none()
+    {
+        return Ok(Some(SocialError::PlayerNotExist(target_id)));
+    }
+
+    if query("SELECT 1 FROM t_block_list WHERE player_id = ($1) AND blocked_id = ($2)")
+        .bind(target_id)
+        .bind(player_id)
+        .fetch_optional(db)
+        .await?
+        .is_some()
+    {
+        return Ok(Some(SocialError::BlockedByTarget(target_id)));
+    }
+
+    let (friend_count,): (i64,) = query_as("SELECT COUNT(*) FROM t_friend WHERE player_id = ($1)")
+        .bind(target_id)
+        .fetch_one(db)
+        .await?;
+
+    if friend_count as usize >= PlayerSocial::MAX_FRIENDS {
+        return Ok(Some(SocialError::TargetFriendListFull(target_id)));
+    }
+
+    Ok(None)
+}
+
+async fn on_apply_send(
+    db: &PgPool,
+    session_mgr: &SessionManager,
+    player_id: i32,
+    target_id: i32,
+) -> Result<(), Error> {
+    if let Some(err) = check_target(db, player_id, target_id).await? {
+        deliver(session_mgr, player_id, SocialEvent::RequestFailed(err));
+        return Ok(());
+    }
+
+    let create_time = time_util::unix_timestamp() as i64;
+    let result = query(
+        "INSERT INTO t_friend_apply (player_id, applicant_id, create_time) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+    )
+    .bind(target_id)
+    .bind(player_id)
+    .bind(create_time)
+    .execute(db)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Ok(());
+    }
+
+    if let Some(info) = fetch_player(db, session_mgr, player_id).await? {
+        deliver(
+            session_mgr,
+            target_id,
+            SocialEvent::ApplyReceived(FriendApplication { info, create_time }),
+        );
+    }
+
+    Ok(())
+}
+
+async fn on_apply_handle(
+    db: &PgPool,
+    session_mgr: &SessionManager,
+    player_id: i32,
+    applicant_id: i32,
+    approve: bool,
+) -> Result<(), Error> {
+    query("DELETE FROM t_friend_apply WHERE player_id = ($1) AND applicant_id = ($2)")
+        .bind(player_id)
+        .bind(applicant_id)
+        .execute(db)
+        .await?;
+
+    if !approve {
+        return Ok(());
+    }
+
+    if let Some(err) = check_target(db, player_id, applicant_id).await? {
+        deliver(session_mgr, player_id, SocialEvent::RequestFailed(err));
+        return Ok(());
+    }
+
+    // Pending application in the opposite direction is no longer needed
+    query("DELETE FROM t_friend_apply WHERE player_id = ($1) AND applicant_id = ($2)")
+        .bind(applicant_id)
+        .bind(player_id)
+        .execute(db)
+        .await?;
+
+    query(
+        "INSERT INTO t_friend (player_id, friend_id, create_time) VALUES ($1, $2, $3), ($2, $1, $3) ON CONFLICT DO NOTHING",
+    )
+    .bind(player_id)
+    .bind(applicant_id)
+    .bind(time_util::unix_timestamp() as i64)
+    .execute(db)
+    .await?;
+
+    // Both sides add each other once the friendship is stored
+    let mut players = fetch_players(db, session_mgr, &[player_id, applicant_id]).await?;
+    for (receiver_id, friend_id) in [(player_id, applicant_id), (applicant_id, player_id)] {
+        if let Some(info) = players.remove(&friend_id) {
+            deliver(
+                session_mgr,
+                receiver_id,
+                SocialEvent::FriendAdded(Friend {
+                    info,
+                    remark: String::new(),
+                }),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn on_block(
+    db: &PgPool,
+    session_mgr: &SessionManager,
+    player_id: i32,
+    target_id: i32,
+) -> Result<(), Error> {
+    let Some(info) = fetch_player(db, session_mgr, target_id).await? else {
+        tracing::debug!("social_task: block target with id {target_id} doesn't exist");
+        return Ok(());
+    };
+
+    query(
+        "INSERT INTO t_block_list (player_id, blocked_id, create_time) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+    )
+    .bind(player_id)
+    .bind(target_id)
+    .bind(time_util::unix_timestamp() as i64)
+    .execute(db)
+    .await?;
+
+    delete_friendship(db, player_id, target_id).await?;
+    query("DELETE FROM t_friend_apply WHERE (player_id = ($1) AND applicant_id = ($2)) OR (player_id = ($2) AND applicant_id = ($1))")
+        .bind(player_id)
+        .bind(target_id)
+        .execute(db)
+        .await?;
+
+    deliver(
+        session_mgr,
+        target_id,
+        SocialEvent::FriendDeleted(player_id),
+    );
+    deliver(session_mgr, player_id, SocialEvent::PlayerInfo(info));
+
+    Ok(())
+}
+
+async fn delete_friendship(db: &PgPool, player_id: i32, friend_id: i32) -> Result<(), Error> {
+    query("DELETE FROM t_friend WHERE (player_id = ($1) AND friend_id = ($2)) OR (player_id = ($2) AND friend_id = ($1))")
+        .bind(player_id)
+        .bind(friend_id)
+        .execute(db)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,14 @@
+use serde::Deserialize;
+
+use crate::AchievementConditionData;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AchievementData {
+    pub id: i32,
+    pub group_id: i32,
+    pub level: i32,
+    pub condition: AchievementConditionData,
+    pub progress: i32,
+    pub drop_id: i32,
+}
@@ -0,0 +1,8 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AchievementGroupData {
+    pub id: i32,
+    pub drop_id: i32,
+}
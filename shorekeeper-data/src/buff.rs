@@ -0,0 +1,17 @@
+use serde::Deserialize;
+
+use crate::PropValueData;
+
+// Durations are in milliseconds, 0 means permanent buff
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BuffData {
+    pub id: i64,
+    pub duration: i32,
+    pub max_stack: i32,
+    // Attribute increments granted per stack
+    pub modifiers: Vec<PropValueData>,
+    pub period: i32,
+    // Ratio of LifeMax healed per stack every period
+    pub periodic_heal: i32,
+}
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BuffItemData {
+    pub item_id: i32,
+    pub buff_id: i64,
+    pub cd: i32,
+}
@@ -0,0 +1,13 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalabashLevelData {
+    pub level: i32,
+    pub level_up_exp: i32,
+    pub drop_id: i32,
+    pub quality_weights: HashMap<i32, i32>,
+    pub catch_gain: HashMap<i32, i32>,
+}
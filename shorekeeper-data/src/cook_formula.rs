@@ -0,0 +1,15 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CookFormulaData {
+    pub id: i32,
+    pub item_id: i32,
+    pub item_count: i32,
+    pub consume: HashMap<i32, i32>,
+    pub formula_item_id: i32,
+    pub proficiency: i32,
+    pub unlock_level: i32,
+}
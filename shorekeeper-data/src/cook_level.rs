@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CookLevelData {
+    pub level: i32,
+    pub proficiency: i32,
+    pub drop_id: i32,
+}
@@ -0,0 +1,11 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DropPackageData {
+    pub id: i32,
+    pub drop_plan: i32,
+    pub drop_preview: HashMap<i32, i32>,
+}
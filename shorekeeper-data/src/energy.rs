@@ -0,0 +1,11 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct EnergyData {
+    pub energy_type: i32,
+    pub max_count: i32,
+    pub renew_interval: i32,
+    pub renew_count: i32,
+    pub overflow_max: i32,
+}
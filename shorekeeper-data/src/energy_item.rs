@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct EnergyItemData {
+    pub item_id: i32,
+    pub energy_type: i32,
+    pub count: i32,
+}
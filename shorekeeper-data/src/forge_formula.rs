@@ -0,0 +1,12 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ForgeFormulaData {
+    pub id: i32,
+    pub item_id: i32,
+    pub consume: HashMap<i32, i32>,
+    pub formula_item_id: i32,
+}
@@ -0,0 +1,11 @@
+use serde::Deserialize;
+
+use crate::FsmMachineData;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FsmGroupData {
+    pub id: i32,
+    pub common_hash_code: i32,
+    pub machines: Vec<FsmMachineData>,
+}
@@ -0,0 +1,18 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GachaData {
+    pub id: i32,
+    pub gacha_type: i32,
+    pub rule_id: i32,
+    pub item_id: i32,
+    pub gacha_times: Vec<i32>,
+    pub pool_ids: Vec<i32>,
+    pub default_pool_id: i32,
+    pub begin_time: i64,
+    pub end_time: i64,
+    pub daily_limit_times: i32,
+    pub total_limit_times: i32,
+    pub resources_id: String,
+}
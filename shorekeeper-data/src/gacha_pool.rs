@@ -0,0 +1,20 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GachaPoolData {
+    pub id: i32,
+    pub gacha_id: i32,
+    pub title: String,
+    pub description: String,
+    pub ui_type: i32,
+    pub theme_color: String,
+    pub show_id_list: Vec<i32>,
+    pub up_five_star: Vec<i32>,
+    pub five_star: Vec<i32>,
+    pub up_four_star: Vec<i32>,
+    pub four_star: Vec<i32>,
+    pub three_star: Vec<i32>,
+    pub begin_time: i64,
+    pub end_time: i64,
+}
@@ -0,0 +1,14 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GachaRuleData {
+    pub id: i32,
+    pub five_star_rate: i32,
+    pub four_star_rate: i32,
+    pub soft_pity_start: i32,
+    pub soft_pity_rate: i32,
+    pub hard_pity: i32,
+    pub four_star_hard_pity: i32,
+    pub up_rate: i32,
+}
@@ -0,0 +1,8 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct GuideGroupData {
+    pub id: i32,
+    pub pre_group_ids: Vec<i32>,
+}
@@ -0,0 +1,10 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct InstanceEnergyCostData {
+    pub instance_id: i32,
+    pub energy_type: i32,
+    pub cost: i32,
+    pub union_exp: i32,
+}
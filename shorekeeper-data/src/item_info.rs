@@ -0,0 +1,12 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ItemInfoData {
+    pub id: i32,
+    pub item_type: i32,
+    pub name: String,
+    pub quality_id: i32,
+    #[serde(rename = "MaxCapcity")] // kuro!
+    pub max_capacity: i32,
+}
@@ -5,10 +5,21 @@ pub use misc_data::*;
 mod misc_data;
 #[derive(thiserror::Error, Debug)]
 pub enum LoadDataError {
-    #[error("I/O error: {0}")]
-    Io(#[from] std::io::Error),
-    #[error("Failed to parse json: {0}")]
-    Json(#[from] serde_json::Error),
+    #[error("I/O error while reading {0}: {1}")]
+    Io(String, std::io::Error),
+    #[error("Failed to parse json {0}: {1}")]
+    Json(String, serde_json::Error),
+}
+
+// Tables are dumped from the game data, missing one is reported with its path
+fn read_table<T: serde::de::DeserializeOwned>(
+    base_path: &str,
+    name: &str,
+) -> Result<T, LoadDataError> {
+    let path = format!("{base_path}/{name}.json");
+    let json_content =
+        std::fs::read_to_string(&path).map_err(|err| LoadDataError::Io(path.clone(), err))?;
+    serde_json::from_str(&json_content).map_err(|err| LoadDataError::Json(path, err))
 }
 
 macro_rules! json_data {
@@ -32,8 +43,7 @@ macro_rules! json_data {
 
         fn load_json_data(base_path: &str) -> Result<(), LoadDataError> {
             $(paste! {
-                let json_content = std::fs::read_to_string(&format!("{}/{}.json", base_path, stringify!($table_type)))?;
-                let _ = [<$table_type:snake _data>]::TABLE.set(serde_json::from_str(&json_content)?);
+                let _ = [<$table_type:snake _data>]::TABLE.set(read_table(base_path, stringify!($table_type))?);
             })*
 
             Ok(())
@@ -42,7 +52,7 @@ macro_rules! json_data {
 }
 
 macro_rules! json_hash_table_data {
-    ($($table_type:ident, $key_param:expr;)*) => {
+    ($($table_type:ident, $key_param:ident: $key_type:ty;)*) => {
         $(paste! {
             mod [<$table_type:snake>];
             pub use [<$table_type:snake>]::[<$table_type Data>];
@@ -54,21 +64,23 @@ macro_rules! json_hash_table_data {
                 use std::sync::OnceLock;
 
                 pub(crate) type Data = super::[<$table_type Data>];
-                pub(crate) static TABLE: OnceLock<HashMap<i64, Data>> = OnceLock::new();
+                pub(crate) static TABLE: OnceLock<HashMap<$key_type, Data>> = OnceLock::new();
 
-                pub fn iter() -> std::collections::hash_map::Iter<'static, i64, Data> {
+                pub fn iter() -> std::collections::hash_map::Iter<'static, $key_type, Data> {
                     TABLE.get().unwrap().iter()
                 }
+
+                pub fn get(id: $key_type) -> Option<&'static Data> {
+                    TABLE.get().unwrap().get(&id)
+                }
             }
         })*
 
         fn load_json_hash_table_data(base_path: &str) -> Result<(), LoadDataError> {
             $(paste! {
-                let json_content = std::fs::read_to_string(&format!("{}/{}.json", base_path, stringify!($table_type)))?;
                 let _ = [<$table_type:snake _data>]::TABLE.set(
-                    serde_json::from_str::<Vec<[<$table_type:snake _data>]::Data>>(&json_content)?
-                        .iter()
-                        .cloned()
+                    read_table::<Vec<[<$table_type:snake _data>]::Data>>(base_path, stringify!($table_type))?
+                        .into_iter()
                         .map(|element| (element.$key_param, element))
                         .collect::<std::collections::HashMap<_, _>>()
                 );
@@ -86,14 +98,60 @@ pub fn load_all_json_data(base_path: &str) -> Result<(), LoadDataError> {
 }
 
 json_data! {
-    RoleInfo;
-    WeaponConf;
     BaseProperty;
     InstanceDungeon;
     FunctionCondition;
     ExploreTools;
+    WeaponLevel;
+    WeaponBreach;
+    WeaponReson;
+    WeaponExpItem;
+    RoleLevelConsume;
+    RoleExpItem;
+    RoleBreach;
+    RolePropertyGrowth;
+    SkillLevel;
+    Gacha;
+    GachaPool;
+    GachaRule;
+    DropPackage;
+    Quest;
+    GuideGroup;
+    Tutorial;
+    Achievement;
+    AchievementGroup;
+    SynthesisFormula;
+    SynthesisLevel;
+    ForgeFormula;
+    CookFormula;
+    CookLevel;
+    PhantomItem;
+    PhantomMainProp;
+    PhantomSubProp;
+    PhantomFetter;
+    PhantomLevel;
+    PhantomExpItem;
+    PhantomCatch;
+    CalabashLevel;
+    ShopItem;
+    PayShopItem;
+    Energy;
+    EnergyItem;
+    InstanceEnergyCost;
+    PlayerLevel;
+    WorldLevel;
+    MonsterPropertyGrowth;
+    FsmGroup;
+    Buff;
+    BuffItem;
 }
 
 json_hash_table_data! {
-    LevelEntityConfig, entity_id;
+    LevelEntityConfig, entity_id: i64;
+    RoleInfo, id: i32;
+    WeaponConf, item_id: i32;
+    ItemInfo, id: i32;
+    Skill, id: i32;
+    SkillTree, id: i32;
+    Revive, id: i32;
 }
\ No newline at end of file
@@ -54,3 +54,69 @@ pub struct EntranceEntityData {
     pub dungeon_id: i32,
     pub entrance_entity_id: i32,
 }
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct QuestNodeData {
+    pub id: i32,
+    pub next_node_ids: Vec<i32>,
+    pub actions: Vec<QuestActionData>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "Type")]
+pub enum QuestActionData {
+    #[serde(rename_all = "PascalCase")]
+    AddItem { item_id: i32, count: i32 },
+    #[serde(rename_all = "PascalCase")]
+    AddExp { exp: i32 },
+    #[serde(rename_all = "PascalCase")]
+    UnlockFunction { function_id: i32 },
+    #[serde(rename_all = "PascalCase")]
+    AcceptQuest { quest_id: i32 },
+}
+
+impl QuestActionData {
+    pub fn is_reward(&self) -> bool {
+        matches!(self, Self::AddItem { .. } | Self::AddExp { .. })
+    }
+}
+
+// Id filters set to 0 match any target
+#[derive(Deserialize)]
+#[serde(tag = "Type")]
+pub enum AchievementConditionData {
+    #[serde(rename_all = "PascalCase")]
+    RoleObtained { role_id: i32 },
+    #[serde(rename_all = "PascalCase")]
+    MonsterKilled { config_id: i64 },
+    #[serde(rename_all = "PascalCase")]
+    TeleportUnlocked { teleporter_id: i32 },
+    #[serde(rename_all = "PascalCase")]
+    ItemCrafted { item_id: i32 },
+    // Reported by client through AchievementFinishRequest
+    Client,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FsmMachineData {
+    pub fsm_id: i32,
+    pub init_state: i32,
+    pub states: Vec<FsmStateData>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FsmStateData {
+    pub id: i32,
+    pub transitions: Vec<FsmTransitionData>,
+}
+
+// Transition is allowed once all of its conditions were passed
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct FsmTransitionData {
+    pub to_state: i32,
+    pub condition_count: i32,
+}
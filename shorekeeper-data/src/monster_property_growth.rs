@@ -0,0 +1,13 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MonsterPropertyGrowthData {
+    pub id: i32,
+    // Property id of monsters using the curve
+    pub curve_id: i32,
+    pub level: i32,
+    pub life_max_ratio: i32,
+    pub atk_ratio: i32,
+    pub def_ratio: i32,
+}
@@ -0,0 +1,15 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PayShopItemData {
+    pub id: i32,
+    pub shop_id: i32,
+    pub item_id: i32,
+    pub item_count: i32,
+    pub buy_limit: i32,
+    pub price_id: i32,
+    pub price_count: i32,
+    pub update_type: i32,
+    pub shop_item_type: i32,
+}
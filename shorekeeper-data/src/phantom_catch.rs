@@ -0,0 +1,11 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PhantomCatchData {
+    pub monster_id: i32,
+    pub blueprint_type: String,
+    pub monster_rarity: i32,
+    pub catch_rate: i32,
+    pub phantom_item_ids: Vec<i32>,
+}
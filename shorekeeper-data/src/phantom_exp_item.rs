@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PhantomExpItemData {
+    pub id: i32,
+    pub basic_exp: i32,
+    pub cost: i32,
+}
@@ -0,0 +1,12 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PhantomFetterData {
+    pub id: i32,
+    pub group_id: i32,
+    pub num: i32,
+    pub attribute_type: i32,
+    pub is_ratio: bool,
+    pub value: i32,
+}
@@ -0,0 +1,11 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PhantomItemData {
+    pub item_id: i32,
+    pub rarity: i32,
+    pub cost: i32,
+    pub fetter_group_id: i32,
+    pub fodder_exp: i32,
+}
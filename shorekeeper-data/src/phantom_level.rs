@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PhantomLevelData {
+    pub rarity: i32,
+    pub level: i32,
+    pub exp: i32,
+}
@@ -0,0 +1,12 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PhantomMainPropData {
+    pub id: i32,
+    pub cost: i32,
+    pub attribute_type: i32,
+    pub is_ratio: bool,
+    pub base_value: i32,
+    pub growth_value: i32,
+}
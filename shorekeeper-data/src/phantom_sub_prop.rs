@@ -0,0 +1,10 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PhantomSubPropData {
+    pub id: i32,
+    pub attribute_type: i32,
+    pub is_ratio: bool,
+    pub values: Vec<i32>,
+}
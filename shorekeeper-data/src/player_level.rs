@@ -0,0 +1,10 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlayerLevelData {
+    pub level: i32,
+    // 0 at max level
+    pub need_exp: i32,
+    pub world_level: i32,
+}
@@ -0,0 +1,16 @@
+use serde::Deserialize;
+
+use crate::{QuestActionData, QuestNodeData};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct QuestData {
+    pub id: i32,
+    pub quest_type: i32,
+    pub pre_quest_ids: Vec<i32>,
+    pub accept_level: i32,
+    pub auto_accept: bool,
+    pub accept_actions: Vec<QuestActionData>,
+    pub nodes: Vec<QuestNodeData>,
+    pub finish_actions: Vec<QuestActionData>,
+}
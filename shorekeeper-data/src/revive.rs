@@ -0,0 +1,12 @@
+use serde::Deserialize;
+
+use crate::VectorData;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ReviveData {
+    pub id: i32,
+    pub map_id: i32,
+    pub position: VectorData,
+    pub rotation: VectorData,
+}
@@ -0,0 +1,14 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RoleBreachData {
+    pub id: i32,
+    pub breach_group_id: i32,
+    pub breach_level: i32,
+    pub max_level: i32,
+    pub breach_consume: HashMap<i32, i32>,
+    pub gold_consume: i32,
+}
@@ -0,0 +1,10 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RoleLevelConsumeData {
+    pub id: i32,
+    pub consume_group_id: i32,
+    pub level: i32,
+    pub exp_count: i32,
+}
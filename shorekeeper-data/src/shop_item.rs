@@ -0,0 +1,15 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ShopItemData {
+    pub id: i32,
+    pub shop_id: i32,
+    pub item_id: i32,
+    pub item_num: i32,
+    pub money_id: i32,
+    pub price: i32,
+    pub original_price: i32,
+    pub limit_num: i32,
+    pub update_type: i32,
+}
@@ -0,0 +1,11 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SkillData {
+    pub id: i32,
+    pub skill_group_id: i32,
+    pub skill_type: i32,
+    pub max_skill_level: i32,
+    pub skill_level_group_id: i32,
+}
@@ -0,0 +1,18 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SkillTreeData {
+    pub id: i32,
+    pub node_group: i32,
+    pub node_index: i32,
+    pub node_type: i32,
+    pub parent_nodes: Vec<i32>,
+    pub unlock_breach_level: i32,
+    pub skill_id: i32,
+    pub property_node_param: HashMap<i32, i32>,
+    pub consume: HashMap<i32, i32>,
+    pub gold_consume: i32,
+}
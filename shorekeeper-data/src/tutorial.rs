@@ -0,0 +1,8 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TutorialData {
+    pub id: i32,
+    pub drop_id: i32,
+}
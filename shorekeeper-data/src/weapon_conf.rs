@@ -8,6 +8,7 @@ pub struct WeaponConfData {
     pub item_id: i32,
     pub weapon_name: String,
     pub quality_id: i32,
+    pub weapon_type: i32,
     pub model_id: i32,
     pub transform_id: i32,
     pub models: Vec<i32>,
@@ -0,0 +1,10 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct WeaponLevelData {
+    pub id: i32,
+    pub level_id: i32,
+    pub level: i32,
+    pub exp: i32,
+}
@@ -0,0 +1,8 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct WorldLevelData {
+    pub level: i32,
+    pub monster_level: i32,
+}
@@ -0,0 +1,16 @@
+CREATE TABLE t_player_mail (
+	mail_id bigint primary key generated always as identity,
+	player_id int NOT NULL,
+	title varchar(128) NOT NULL,
+	content text NOT NULL,
+	sender varchar(64) NOT NULL,
+	level int NOT NULL,
+	attachment_ids int[] NOT NULL,
+	attachment_counts int[] NOT NULL,
+	received_time bigint NOT NULL,
+	read_time bigint NOT NULL DEFAULT 0,
+	expire_time bigint NOT NULL,
+	is_claimed boolean NOT NULL DEFAULT false
+);
+
+CREATE INDEX idx_player_mail_player_id ON t_player_mail (player_id);
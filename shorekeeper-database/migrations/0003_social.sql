@@ -0,0 +1,21 @@
+CREATE TABLE t_friend (
+	player_id int NOT NULL,
+	friend_id int NOT NULL,
+	remark varchar(32) NOT NULL DEFAULT '',
+	create_time bigint NOT NULL,
+	primary key (player_id, friend_id)
+);
+
+CREATE TABLE t_friend_apply (
+	player_id int NOT NULL,
+	applicant_id int NOT NULL,
+	create_time bigint NOT NULL,
+	primary key (player_id, applicant_id)
+);
+
+CREATE TABLE t_block_list (
+	player_id int NOT NULL,
+	blocked_id int NOT NULL,
+	create_time bigint NOT NULL,
+	primary key (player_id, blocked_id)
+);
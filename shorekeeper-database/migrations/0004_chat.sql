@@ -0,0 +1,12 @@
+CREATE TABLE t_private_chat (
+	msg_id varchar(64) primary key,
+	sender_id int NOT NULL,
+	target_id int NOT NULL,
+	content_type int NOT NULL,
+	content text NOT NULL,
+	send_time bigint NOT NULL,
+	is_offline boolean NOT NULL DEFAULT false
+);
+
+CREATE INDEX idx_private_chat_sender_id ON t_private_chat (sender_id);
+CREATE INDEX idx_private_chat_target_id ON t_private_chat (target_id);
@@ -26,3 +26,52 @@ pub struct PlayerDataRow {
     pub name: String,
     pub bin_data: Vec<u8>,
 }
+
+#[derive(FromRow)]
+pub struct PlayerMailRow {
+    pub mail_id: i64,
+    pub player_id: i32,
+    pub title: String,
+    pub content: String,
+    pub sender: String,
+    pub level: i32,
+    pub attachment_ids: Vec<i32>,
+    pub attachment_counts: Vec<i32>,
+    pub received_time: i64,
+    pub read_time: i64,
+    pub expire_time: i64,
+    pub is_claimed: bool,
+}
+
+#[derive(FromRow)]
+pub struct FriendRow {
+    pub player_id: i32,
+    pub friend_id: i32,
+    pub remark: String,
+    pub create_time: i64,
+}
+
+#[derive(FromRow)]
+pub struct FriendApplyRow {
+    pub player_id: i32,
+    pub applicant_id: i32,
+    pub create_time: i64,
+}
+
+#[derive(FromRow)]
+pub struct BlockListRow {
+    pub player_id: i32,
+    pub blocked_id: i32,
+    pub create_time: i64,
+}
+
+#[derive(FromRow)]
+pub struct PrivateChatRow {
+    pub msg_id: String,
+    pub sender_id: i32,
+    pub target_id: i32,
+    pub content_type: i32,
+    pub content: String,
+    pub send_time: i64,
+    pub is_offline: bool,
+}